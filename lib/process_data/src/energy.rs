@@ -0,0 +1,211 @@
+//! Estimates the power draw of individual processes by sampling the energy counters of the CPU packages (RAPL) and
+//! apportioning the energy consumed in between two samples to the processes according to the CPU time they used in
+//! the same interval, similar to what powertop does.
+//!
+//! This is an estimate: it ignores that not all CPU time is equally expensive (e.g. vector instructions or different
+//! core types) and attributes the idle power of the packages to the processes that happened to run.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+
+#[derive(Debug, Clone)]
+struct RaplDomain {
+    energy_path: PathBuf,
+    /// The value at which the counter wraps around to 0
+    max_energy_range_uj: u64,
+    last_energy_uj: Option<u64>,
+}
+
+impl RaplDomain {
+    /// Returns the energy in microjoules consumed since the last time this has been called, `None` for the first call
+    /// or if the counter can't be read
+    fn energy_delta_uj(&mut self) -> Option<u64> {
+        let energy_uj = read_u64(&self.energy_path);
+        let last_energy_uj = std::mem::replace(&mut self.last_energy_uj, energy_uj);
+
+        Some(energy_delta(
+            last_energy_uj?,
+            energy_uj?,
+            self.max_energy_range_uj,
+        ))
+    }
+}
+
+/// Samples the combined energy counters of all CPU packages exposed through the powercap interface (this includes
+/// AMD CPUs, whose RAPL domains are exposed under the `intel-rapl` name as well).
+///
+/// Note that on kernels since 5.10, the energy counters can only be read by root unless the permissions of
+/// `energy_uj` have been relaxed, in which case no power can be estimated.
+#[derive(Debug, Clone, Default)]
+pub struct RaplSampler {
+    domains: Vec<RaplDomain>,
+    last_sample: Option<Instant>,
+}
+
+impl RaplSampler {
+    pub fn new() -> Self {
+        Self::with_powercap_path(POWERCAP_PATH)
+    }
+
+    fn with_powercap_path<P: AsRef<Path>>(powercap_path: P) -> Self {
+        let domains = std::fs::read_dir(powercap_path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| is_package_domain(path))
+                    .map(|path| RaplDomain {
+                        max_energy_range_uj: read_u64(path.join("max_energy_range_uj"))
+                            .unwrap_or(u64::MAX),
+                        energy_path: path.join("energy_uj"),
+                        last_energy_uj: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            domains,
+            last_sample: None,
+        }
+    }
+
+    /// Whether any CPU package domains have been found, this doesn't mean that their counters can be read
+    pub fn is_available(&self) -> bool {
+        !self.domains.is_empty()
+    }
+
+    /// Whether CPU package domains have been found and all of their energy counters can be read, which usually
+    /// requires root (see above)
+    pub fn is_readable(&self) -> bool {
+        self.is_available()
+            && self
+                .domains
+                .iter()
+                .all(|domain| read_u64(&domain.energy_path).is_some())
+    }
+
+    /// Returns the average power in watts the CPU packages have drawn since the last time this has been called.
+    /// Returns `None` for the first call or if the energy counters can't be read.
+    pub fn sample(&mut self) -> Option<f64> {
+        if self.domains.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .last_sample
+            .replace(now)
+            .map(|last_sample| now.duration_since(last_sample).as_secs_f64());
+
+        // make sure every domain is sampled so that their last values are up to date
+        let energy_deltas: Vec<_> = self
+            .domains
+            .iter_mut()
+            .map(RaplDomain::energy_delta_uj)
+            .collect();
+        let energy_uj: u64 = energy_deltas.into_iter().sum::<Option<u64>>()?;
+
+        let elapsed = elapsed.filter(|elapsed| *elapsed > 0.0)?;
+
+        Some(energy_uj as f64 / 1_000_000.0 / elapsed)
+    }
+}
+
+/// Returns the part of `package_power` that's attributed to a process that used `cpu_time` out of the `total_cpu_time`
+/// that all processes used in the same interval (in any unit, as long as it's the same for both)
+pub fn attribute_power(package_power: f64, cpu_time: u64, total_cpu_time: u64) -> f64 {
+    if total_cpu_time == 0 {
+        0.0
+    } else {
+        package_power * (cpu_time.min(total_cpu_time) as f64 / total_cpu_time as f64)
+    }
+}
+
+/// Top-level RAPL domains are called e.g. `intel-rapl:0` while their subdomains (cores, uncore, DRAM) are called e.g.
+/// `intel-rapl:0:1`. Platform-wide (`psys`) domains are top-level as well but they're not about the CPU packages.
+fn is_package_domain(path: &Path) -> bool {
+    let is_top_level = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(|file_name| file_name.strip_prefix("intel-rapl:"))
+        .is_some_and(|index| !index.contains(':'));
+
+    is_top_level
+        && std::fs::read_to_string(path.join("name"))
+            .is_ok_and(|name| name.trim().starts_with("package"))
+}
+
+fn energy_delta(last_energy_uj: u64, energy_uj: u64, max_energy_range_uj: u64) -> u64 {
+    if energy_uj >= last_energy_uj {
+        energy_uj - last_energy_uj
+    } else {
+        // the counter has wrapped around
+        max_energy_range_uj
+            .saturating_sub(last_energy_uj)
+            .saturating_add(energy_uj)
+    }
+}
+
+fn read_u64<P: AsRef<Path>>(path: P) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{attribute_power, energy_delta, RaplSampler};
+
+    #[test]
+    fn energy_delta_wraparound() {
+        assert_eq!(500, energy_delta(1_000, 1_500, 10_000));
+        assert_eq!(1_500, energy_delta(9_000, 500, 10_000));
+    }
+
+    #[test]
+    fn power_attribution() {
+        assert_eq!(5.0, attribute_power(20.0, 25, 100));
+        assert_eq!(20.0, attribute_power(20.0, 100, 100));
+        assert_eq!(0.0, attribute_power(20.0, 0, 100));
+        assert_eq!(0.0, attribute_power(20.0, 10, 0));
+    }
+
+    #[test]
+    fn package_domains() {
+        let powercap =
+            std::env::temp_dir().join(format!("resources-powercap-{}", std::process::id()));
+
+        for (domain, name, energy) in [
+            ("intel-rapl:0", "package-0", "1000000"),
+            ("intel-rapl:0:0", "core", "400000"),
+            ("intel-rapl:1", "psys", "5000000"),
+        ] {
+            let domain = powercap.join(domain);
+            std::fs::create_dir_all(&domain).unwrap();
+            std::fs::write(domain.join("name"), format!("{name}\n")).unwrap();
+            std::fs::write(domain.join("energy_uj"), format!("{energy}\n")).unwrap();
+            std::fs::write(domain.join("max_energy_range_uj"), "262143328850\n").unwrap();
+        }
+
+        let mut sampler = RaplSampler::with_powercap_path(&powercap);
+        assert!(sampler.is_available());
+        assert!(sampler.is_readable());
+        assert_eq!(None, sampler.sample());
+
+        std::fs::write(powercap.join("intel-rapl:0").join("energy_uj"), "3000000\n").unwrap();
+        assert!(sampler.sample().is_some_and(|power| power > 0.0));
+
+        // stands in for a counter that's only readable by root
+        std::fs::remove_file(powercap.join("intel-rapl:0").join("energy_uj")).unwrap();
+        assert!(sampler.is_available());
+        assert!(!sampler.is_readable());
+        assert_eq!(None, sampler.sample());
+
+        std::fs::remove_dir_all(&powercap).unwrap();
+
+        assert!(!RaplSampler::with_powercap_path(&powercap).is_available());
+    }
+}
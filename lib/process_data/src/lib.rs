@@ -1,4 +1,9 @@
+pub mod energy;
+pub mod net_io;
+pub mod open_files;
 pub mod pci_slot;
+pub mod proc_events;
+pub mod usage_history;
 
 use anyhow::{bail, Context, Result};
 use glob::glob;
@@ -9,23 +14,59 @@ use nvml_wrapper::error::NvmlError;
 use nvml_wrapper::struct_wrappers::device::{ProcessInfo, ProcessUtilizationSample};
 use nvml_wrapper::{Device, Nvml};
 use pci_slot::PciSlot;
+use rayon::prelude::*;
+use rayon::ThreadPool;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Display;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{ErrorKind, Read, Seek, Write};
 use std::os::linux::fs::MetadataExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::{LazyLock, RwLock};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "log")]
+use log::{debug, trace};
+
+// without the `log` feature, the arguments are still type-checked (so that variables only used for logging don't
+// trigger warnings) but never evaluated
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
 
 const STAT_OFFSET: usize = 2; // we split the stat contents where the executable name ends, which is the second element
+const STAT_STATE: usize = 2 - STAT_OFFSET;
 const STAT_PARENT_PID: usize = 3 - STAT_OFFSET;
+const STAT_PGRP: usize = 4 - STAT_OFFSET;
+const STAT_TPGID: usize = 7 - STAT_OFFSET;
+const STAT_MINOR_FAULTS: usize = 9 - STAT_OFFSET;
+const STAT_MAJOR_FAULTS: usize = 11 - STAT_OFFSET;
 const STAT_USER_CPU_TIME: usize = 13 - STAT_OFFSET;
 const STAT_SYSTEM_CPU_TIME: usize = 14 - STAT_OFFSET;
 const STAT_NICE: usize = 18 - STAT_OFFSET;
 const STAT_STARTTIME: usize = 21 - STAT_OFFSET;
+const STAT_PROCESSOR: usize = 38 - STAT_OFFSET;
+const STAT_RT_PRIORITY: usize = 39 - STAT_OFFSET;
+const STAT_POLICY: usize = 40 - STAT_OFFSET;
+const STAT_GUEST_TIME: usize = 42 - STAT_OFFSET;
 
 static USERS_CACHE: LazyLock<HashMap<libc::uid_t, String>> = LazyLock::new(|| unsafe {
     uzers::all_users()
@@ -37,12 +78,87 @@ static PAGESIZE: LazyLock<usize> = LazyLock::new(sysconf::pagesize);
 
 static NUM_CPUS: LazyLock<usize> = LazyLock::new(num_cpus::get);
 
-static RE_UID: Lazy<Regex> = lazy_regex!(r"Uid:\s*(\d+)");
+/// Upper bound for the number of threads reading processes in parallel, more threads mostly contend for the same
+/// locks in the kernel while making Resources itself show up at the top of the processes list
+const MAX_SCAN_THREADS: usize = 4;
+
+/// Thread pool reading processes in parallel, `None` if it couldn't be created, processes are read one after another
+/// in that case
+static SCAN_POOL: LazyLock<Option<ThreadPool>> = LazyLock::new(|| {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads((*NUM_CPUS).clamp(1, MAX_SCAN_THREADS))
+        .thread_name(|index| format!("process-scan-{index}"))
+        .build()
+        .inspect_err(|error| debug!("Unable to create process scanning thread pool: {error}"))
+        .ok()
+});
+
+/// Upper bound for the number of threads that may be stuck reading a process at the same time when scanning with
+/// `ScanOptions::deadline`, since they can't be cancelled and only exit once their read returns
+const MAX_STUCK_SCAN_WORKERS: usize = MAX_SCAN_THREADS;
+
+/// Number of threads (stuck or not) currently reading processes with a deadline
+static LIVE_SCAN_WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+static CGROUP2_MOUNT_POINT: LazyLock<PathBuf> = LazyLock::new(|| {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .ok()
+        .and_then(|mountinfo| ProcessData::cgroup2_mount_point(&mountinfo))
+        .unwrap_or_else(|| PathBuf::from("/sys/fs/cgroup"))
+});
+
+const DRM_MAJOR: u32 = 226;
+
+/// Major number of the char devices of the `accel` subsystem (`/dev/accel/accel*`) used by newer NPU drivers, which
+/// unlike DRM's isn't fixed
+static ACCEL_MAJOR: LazyLock<Option<u32>> = LazyLock::new(|| {
+    std::fs::read_to_string("/proc/devices")
+        .ok()
+        .and_then(|devices| char_device_major(&devices, "accel"))
+});
+
+static CLOCK_TICKS: LazyLock<Option<u64>> = LazyLock::new(|| {
+    sysconf::sysconf(sysconf::SysconfVariable::ScClkTck)
+        .ok()
+        .and_then(|ticks| u64::try_from(ticks).ok())
+        .filter(|ticks| *ticks > 0)
+});
+
+static BOOT_TIME: LazyLock<Option<SystemTime>> = LazyLock::new(|| {
+    let uptime_secs = std::fs::read_to_string("/proc/uptime")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse::<f64>()
+        .ok()?;
+
+    SystemTime::now().checked_sub(Duration::from_secs_f64(uptime_secs))
+});
+
+static RE_UID: Lazy<Regex> = lazy_regex!(r"Uid:\s*(\d+)\s+(\d+)");
 
 static RE_AFFINITY: Lazy<Regex> = lazy_regex!(r"Cpus_allowed:\s*([0-9A-Fa-f]+)");
 
 static RE_SWAP_USAGGE: Lazy<Regex> = lazy_regex!(r"VmSwap:\s*([0-9]+)\s*kB");
 
+static RE_SMAPS_SWAP: Lazy<Regex> = lazy_regex!(r"(?m)^Swap:\s*([0-9]+)\s*kB");
+
+static RE_PEAK_MEMORY_USAGE: Lazy<Regex> = lazy_regex!(r"VmPeak:\s*([0-9]+)\s*kB");
+
+static RE_PEAK_RESIDENT: Lazy<Regex> = lazy_regex!(r"VmHWM:\s*([0-9]+)\s*kB");
+
+static RE_RSS_SHMEM: Lazy<Regex> = lazy_regex!(r"RssShmem:\s*([0-9]+)\s*kB");
+
+static RE_HUGETLB_PAGES: Lazy<Regex> = lazy_regex!(r"HugetlbPages:\s*([0-9]+)\s*kB");
+
+static RE_VOLUNTARY_CTXT_SWITCHES: Lazy<Regex> =
+    lazy_regex!(r"(?m)^voluntary_ctxt_switches:\s*([0-9]+)");
+
+static RE_NONVOLUNTARY_CTXT_SWITCHES: Lazy<Regex> =
+    lazy_regex!(r"nonvoluntary_ctxt_switches:\s*([0-9]+)");
+
+static RE_MAX_OPEN_FILES: Lazy<Regex> = lazy_regex!(r"Max open files\s+(\d+|unlimited)");
+
 static RE_IO_READ: Lazy<Regex> = lazy_regex!(r"read_bytes:\s*(\d+)");
 
 static RE_IO_WRITE: Lazy<Regex> = lazy_regex!(r"write_bytes:\s*(\d+)");
@@ -52,17 +168,21 @@ static RE_DRM_DRIVER: Lazy<Regex> = lazy_regex!(r"drm-driver:\s*(.+)");
 static RE_DRM_PDEV: Lazy<Regex> =
     lazy_regex!(r"drm-pdev:\s*([0-9A-Fa-f]{4}:[0-9A-Fa-f]{2}:[0-9A-Fa-f]{2}\.[0-9A-Fa-f])");
 
+// Engine keys may be reported per instance (e.g. amdgpu's `drm-engine-enc_1` for the second video encode ring), all
+// matching keys are summed up
+
 // AMD only
-static RE_DRM_ENGINE_GFX: Lazy<Regex> = lazy_regex!(r"drm-engine-gfx:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_GFX: Lazy<Regex> = lazy_regex!(r"drm-engine-gfx(?:[-_]?\d+)?:\s*(\d+)\s*ns");
 
 // AMD only
-static RE_DRM_ENGINE_COMPUTE: Lazy<Regex> = lazy_regex!(r"drm-engine-compute:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_COMPUTE: Lazy<Regex> =
+    lazy_regex!(r"drm-engine-compute(?:[-_]?\d+)?:\s*(\d+)\s*ns");
 
 // AMD only
-static RE_DRM_ENGINE_ENC: Lazy<Regex> = lazy_regex!(r"drm-engine-enc:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_ENC: Lazy<Regex> = lazy_regex!(r"drm-engine-enc(?:[-_]?\d+)?:\s*(\d+)\s*ns");
 
 // AMD only
-static RE_DRM_ENGINE_DEC: Lazy<Regex> = lazy_regex!(r"drm-engine-dec:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_DEC: Lazy<Regex> = lazy_regex!(r"drm-engine-dec(?:[-_]?\d+)?:\s*(\d+)\s*ns");
 
 // AMD only
 static RE_DRM_MEMORY_VRAM: Lazy<Regex> = lazy_regex!(r"drm-memory-vram:\s*(\d+)\s*KiB");
@@ -71,18 +191,58 @@ static RE_DRM_MEMORY_VRAM: Lazy<Regex> = lazy_regex!(r"drm-memory-vram:\s*(\d+)\
 static RE_DRM_MEMORY_GTT: Lazy<Regex> = lazy_regex!(r"drm-memory-gtt:\s*(\d+)\s*KiB");
 
 // Intel and v3d only
-static RE_DRM_ENGINE_RENDER: Lazy<Regex> = lazy_regex!(r"drm-engine-render:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_RENDER: Lazy<Regex> =
+    lazy_regex!(r"drm-engine-(?:[a-z0-9]+_)?render(?:-?\d+)?:\s*(\d+)\s*ns");
 
 // Intel only
-static RE_DRM_ENGINE_VIDEO: Lazy<Regex> = lazy_regex!(r"drm-engine-video:\s*(\d+)\s*ns");
+static RE_DRM_ENGINE_VIDEO: Lazy<Regex> =
+    lazy_regex!(r"drm-engine-(?:[a-z0-9]+_)?video(?:-?\d+)?:\s*(\d+)\s*ns");
 
 // v3d only
 static RE_DRM_TOTAL_MEMORY: Lazy<Regex> = lazy_regex!(r"drm-total-memory:\s*(\d+)\s*KiB");
 
+// NPU only
+static RE_DRM_ENGINE_NPU: Lazy<Regex> = lazy_regex!(r"drm-engine-npu[^:]*:\s*(\d+)\s*ns");
+
+// NPU only
+static RE_DRM_SHARED_MEMORY: Lazy<Regex> = lazy_regex!(r"drm-shared-memory:\s*(\d+)\s*KiB");
+
+// NVIDIA only, e.g. `drm-engine-nvenc0`
+static RE_DRM_ENGINE_NVENC: Lazy<Regex> = lazy_regex!(r"drm-engine-nvenc\d*:\s*(\d+)\s*ns");
+
+// NVIDIA only, e.g. `drm-engine-nvdec0`
+static RE_DRM_ENGINE_NVDEC: Lazy<Regex> = lazy_regex!(r"drm-engine-nvdec\d*:\s*(\d+)\s*ns");
+
+// NVIDIA and nouveau, standardized memory region key whose unit is optional (bytes if missing), e.g.
+// `drm-total-vram0`
+static RE_DRM_TOTAL_VRAM: Lazy<Regex> = lazy_regex!(r"drm-total-vram\d*:\s*(\d+)[ \t]*(KiB|MiB)?");
+
+// xe only, busy GPU cycles of an engine class, e.g. `drm-cycles-rcs`
+static RE_DRM_CYCLES: Lazy<Regex> = lazy_regex!(r"drm-cycles-([a-z]+):\s*(\d+)");
+
+// xe only, e.g. `drm-total-cycles-rcs`
+static RE_DRM_TOTAL_CYCLES: Lazy<Regex> = lazy_regex!(r"drm-total-cycles-[a-z]+:\s*(\d+)");
+
+// xe only, number of engines of a class, e.g. `drm-engine-capacity-vcs`
+static RE_DRM_ENGINE_CAPACITY: Lazy<Regex> = lazy_regex!(r"drm-engine-capacity-([a-z]+):\s*(\d+)");
+
+// xe only
+static RE_DRM_TOTAL_GTT: Lazy<Regex> = lazy_regex!(r"drm-total-gtt:\s*(\d+)[ \t]*(KiB|MiB)?");
+
+// any engine utilization key, as opposed to e.g. `drm-engine-capacity-*`
+static RE_DRM_ENGINE_ANY: Lazy<Regex> = lazy_regex!(r"drm-engine-[^:]+:\s*\d+\s*ns");
+
+/// Drivers whose fdinfo entries describe NPUs rather than GPUs
+const NPU_DRIVERS: &[&str] = &["amdxdna", "amdxdna_accel_driver", "intel_vpu"];
+
+/// Drivers of NVIDIA GPUs that may export usage stats through DRM fdinfo (the open kernel modules do), these are
+/// preferred over NVML if present
+const NVIDIA_DRM_DRIVERS: &[&str] = &["nvidia-drm", "nvidia"];
+
 static NVML: Lazy<Result<Nvml, NvmlError>> = Lazy::new(Nvml::init);
 
-static NVML_DEVICES: Lazy<Vec<(PciSlot, Device)>> = Lazy::new(|| {
-    if let Ok(nvml) = NVML.as_ref() {
+static NVML_DEVICES: Lazy<Vec<(PciSlot, Device)>> = Lazy::new(|| match NVML.as_ref() {
+    Ok(nvml) => {
         let device_count = nvml.device_count().unwrap_or(0);
         let mut return_vec = Vec::with_capacity(device_count as usize);
         for i in 0..device_count {
@@ -94,11 +254,30 @@ static NVML_DEVICES: Lazy<Vec<(PciSlot, Device)>> = Lazy::new(|| {
             }
         }
         return_vec
-    } else {
+    }
+    Err(error) => {
+        debug!("Unable to initialize NVML, skipping NVIDIA process stats: {error}");
         Vec::new()
     }
 });
 
+static NVIDIA_TOTAL_VRAM: Lazy<HashMap<PciSlot, u64>> = Lazy::new(|| {
+    NVML_DEVICES
+        .iter()
+        .filter_map(|(pci_slot, gpu)| {
+            gpu.memory_info()
+                .ok()
+                .map(|memory_info| (*pci_slot, memory_info.total))
+        })
+        .collect()
+});
+
+const DEFAULT_NVIDIA_SAMPLE_WINDOW: Duration = Duration::from_secs(5);
+
+static NVIDIA_SAMPLE_WINDOW: RwLock<Duration> = RwLock::new(DEFAULT_NVIDIA_SAMPLE_WINDOW);
+
+static NVIDIA_PROCESS_QUERY: RwLock<NvidiaProcessQuery> = RwLock::new(NvidiaProcessQuery::Both);
+
 static NVIDIA_PROCESSES_STATS: Lazy<RwLock<HashMap<PciSlot, Vec<ProcessUtilizationSample>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
@@ -119,6 +298,294 @@ pub enum Containerization {
     None,
     Flatpak,
     Snap,
+    /// Detected heuristically: either the executable lies within an AppImage's `.mount_*` squashfs mount point or
+    /// `APPIMAGE` is set in the process' environment. AppImages that were extracted beforehand (e.g. using
+    /// `--appimage-extract`) and processes whose environment can't be read due to missing permissions won't be
+    /// recognized if their executable is located elsewhere.
+    AppImage,
+    /// Detected from the process' cgroup, which is `docker-<id>.scope` (systemd cgroup driver) or `docker/<id>`
+    /// (cgroupfs cgroup driver)
+    Docker,
+    /// Detected from the process' cgroup, which is `libpod-<id>.scope` for both rootful and rootless containers
+    Podman,
+    /// Detected from the process' cgroup, which is `lxc.payload.<name>` (LXC 4.0+ and LXD/Incus) or `lxc/<name>`
+    Lxc,
+}
+
+/// Scheduling state of a process as reported by the kernel in `/proc/<pid>/stat`
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessState {
+    /// Running or runnable (`R`)
+    Running,
+    /// Waiting for an event, e.g. input (`S`)
+    #[default]
+    Sleeping,
+    /// Waiting for I/O without being interruptible by signals (`D`). Processes stuck in this state for a long time
+    /// usually hang on a slow or unresponsive device or network file system.
+    DiskSleep,
+    /// Stopped by a signal, e.g. `SIGSTOP` (`T`)
+    Stopped,
+    /// Stopped by a debugger (`t`)
+    TracingStop,
+    /// Terminated, but not yet reaped by its parent (`Z`)
+    Zombie,
+    /// Terminated and about to vanish (`X`)
+    Dead,
+    /// Idle kernel thread (`I`)
+    Idle,
+    /// Parked kernel thread (`P`)
+    Parked,
+    /// Any other state, e.g. ones only used by older kernels
+    Unknown,
+}
+
+impl ProcessState {
+    pub fn from_stat_char(state: char) -> Self {
+        match state {
+            'R' => Self::Running,
+            'S' => Self::Sleeping,
+            'D' => Self::DiskSleep,
+            'T' => Self::Stopped,
+            't' => Self::TracingStop,
+            'Z' => Self::Zombie,
+            'X' | 'x' => Self::Dead,
+            'I' => Self::Idle,
+            'P' => Self::Parked,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Returns the character `ps` and `top` use for this state, `?` for `ProcessState::Unknown`
+    pub fn as_char(&self) -> char {
+        match self {
+            Self::Running => 'R',
+            Self::Sleeping => 'S',
+            Self::DiskSleep => 'D',
+            Self::Stopped => 'T',
+            Self::TracingStop => 't',
+            Self::Zombie => 'Z',
+            Self::Dead => 'X',
+            Self::Idle => 'I',
+            Self::Parked => 'P',
+            Self::Unknown => '?',
+        }
+    }
+}
+
+/// Scheduling policy of a process, see sched(7)
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchedulingPolicy {
+    /// The default time-sharing policy (`SCHED_OTHER`), whose share of CPU time depends on the niceness
+    #[default]
+    Other,
+    /// Realtime policy without time slicing (`SCHED_FIFO`), runs until it blocks or is preempted by a process with a
+    /// higher realtime priority
+    Fifo,
+    /// Realtime policy with time slicing among processes of the same realtime priority (`SCHED_RR`)
+    RoundRobin,
+    /// Like `Other`, but for non-interactive CPU-bound processes that are slightly disfavored (`SCHED_BATCH`)
+    Batch,
+    /// Only runs if nothing else wants to run (`SCHED_IDLE`)
+    Idle,
+    /// Realtime policy scheduling by deadlines (`SCHED_DEADLINE`), which takes precedence over all other policies
+    Deadline,
+    Unknown,
+}
+
+impl SchedulingPolicy {
+    pub fn from_raw(policy: i32) -> Self {
+        match policy {
+            libc::SCHED_OTHER => Self::Other,
+            libc::SCHED_FIFO => Self::Fifo,
+            libc::SCHED_RR => Self::RoundRobin,
+            libc::SCHED_BATCH => Self::Batch,
+            libc::SCHED_IDLE => Self::Idle,
+            libc::SCHED_DEADLINE => Self::Deadline,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Returns the value `sched_setscheduler()` expects for this policy, `None` for `SchedulingPolicy::Unknown`
+    pub fn as_raw(&self) -> Option<i32> {
+        match self {
+            Self::Other => Some(libc::SCHED_OTHER),
+            Self::Fifo => Some(libc::SCHED_FIFO),
+            Self::RoundRobin => Some(libc::SCHED_RR),
+            Self::Batch => Some(libc::SCHED_BATCH),
+            Self::Idle => Some(libc::SCHED_IDLE),
+            Self::Deadline => Some(libc::SCHED_DEADLINE),
+            Self::Unknown => None,
+        }
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, Self::Fifo | Self::RoundRobin | Self::Deadline)
+    }
+}
+
+/// I/O scheduling class of a process, see ioprio_set(2)
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IoPriorityClass {
+    /// No class has been set explicitly, the process is treated as `BestEffort` with a level derived from its
+    /// niceness
+    #[default]
+    None,
+    /// Gets access to the disk first regardless of what else is going on in the system
+    Realtime,
+    BestEffort,
+    /// Only gets access to the disk when no other process needs it
+    Idle,
+}
+
+/// I/O priority of a process as used by the I/O schedulers that support it (BFQ and, partially, mq-deadline)
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IoPriority {
+    pub class: IoPriorityClass,
+    /// Priority within the class from 0 (highest) to 7 (lowest), meaningless for `IoPriorityClass::Idle`
+    pub level: u8,
+}
+
+impl IoPriority {
+    const CLASS_SHIFT: i32 = 13;
+    const LEVEL_MASK: i32 = (1 << Self::CLASS_SHIFT) - 1;
+
+    /// Decodes a value as returned by `ioprio_get()`
+    pub fn from_raw(ioprio: i32) -> Self {
+        let class = match ioprio >> Self::CLASS_SHIFT {
+            1 => IoPriorityClass::Realtime,
+            2 => IoPriorityClass::BestEffort,
+            3 => IoPriorityClass::Idle,
+            _ => IoPriorityClass::None,
+        };
+
+        Self {
+            class,
+            level: (ioprio & Self::LEVEL_MASK).clamp(0, 7) as u8,
+        }
+    }
+
+    /// Encodes this priority as expected by `ioprio_set()`
+    pub fn as_raw(&self) -> i32 {
+        let class = match self.class {
+            IoPriorityClass::None => 0,
+            IoPriorityClass::Realtime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        };
+
+        (class << Self::CLASS_SHIFT) | i32::from(self.level.min(7))
+    }
+
+    /// Returns the priority the kernel actually applies, which for `IoPriorityClass::None` is best-effort with a level
+    /// derived from the niceness of the process
+    pub fn effective(&self, niceness: Niceness) -> Self {
+        if self.class == IoPriorityClass::None {
+            Self {
+                class: IoPriorityClass::BestEffort,
+                level: ((i16::from(*niceness) + 20) / 5).clamp(0, 7) as u8,
+            }
+        } else {
+            *self
+        }
+    }
+}
+
+/// Which kinds of contexts are considered when NVML is asked for the processes running on an NVIDIA GPU, see
+/// `ProcessData::set_nvidia_process_query()`
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NvidiaProcessQuery {
+    Graphics,
+    Compute,
+    /// Processes with both a graphics and a compute context are only reported once
+    #[default]
+    Both,
+}
+
+/// Compares the CPUs a process is allowed to run on with where it actually ran, see `ProcessData::affinity_report()`
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffinityReport {
+    /// Number of CPUs the process is allowed to run on
+    pub allowed_cpus: usize,
+    /// Number of CPUs in the system
+    pub total_cpus: usize,
+    /// Whether the allowed CPUs are a subset of all CPUs, e.g. due to `taskset` or a cpuset
+    pub restricted: bool,
+    /// Whether the CPU the process last ran on is among the allowed ones, `None` if it's unknown
+    pub last_cpu_allowed: Option<bool>,
+}
+
+/// System-wide memory and swap figures from `/proc/meminfo` in bytes, see `system_memory()`
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryInfo {
+    /// Usable RAM (`MemTotal`), i.e. physical RAM minus reserved memory and the kernel binary
+    pub total: u64,
+    /// Completely unused RAM (`MemFree`)
+    pub free: u64,
+    /// Estimate of the memory available for new allocations without swapping (`MemAvailable`)
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    /// Memory used by tmpfs and shared memory segments (`Shmem`)
+    pub shared: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl MemoryInfo {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    /// Parses the contents of `/proc/meminfo`. Fields that are missing are treated as an error except for
+    /// `Buffers`, `Cached` and `Shmem` which default to 0.
+    pub fn parse(meminfo: &str) -> Result<Self> {
+        let mut values: HashMap<&str, u64> = HashMap::new();
+        for line in meminfo.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let mut value = value.split_whitespace();
+            let (Some(Ok(amount)), unit) = (value.next().map(str::parse::<u64>), value.next())
+            else {
+                continue;
+            };
+            // the kernel calls them kB, but they're actually KiB
+            let bytes = match unit {
+                Some("kB") => amount.saturating_mul(1024),
+                _ => amount,
+            };
+            values.insert(key.trim(), bytes);
+        }
+
+        let required = |key: &str| {
+            values
+                .get(key)
+                .copied()
+                .with_context(|| format!("{key} missing from meminfo"))
+        };
+        let optional = |key: &str| values.get(key).copied().unwrap_or_default();
+
+        Ok(Self {
+            total: required("MemTotal")?,
+            free: required("MemFree")?,
+            available: required("MemAvailable")?,
+            buffers: optional("Buffers"),
+            cached: optional("Cached"),
+            shared: optional("Shmem"),
+            swap_total: required("SwapTotal")?,
+            swap_free: required("SwapFree")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Copy)]
+pub enum DisplayProtocol {
+    Wayland,
+    X11,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Copy, PartialOrd, Ord)]
@@ -151,65 +618,761 @@ impl Display for GpuIdentifier {
 /// are irrelevant, nvidia bool is set to true)
 ///
 /// Intel: enc and dec are not separated, both are accumulated in enc, also mem is always going to be 0
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Copy)]
+///
+/// Intel (xe): Instead of ns, gfx, enc and dec are GPU cycles and total_cycles is the number of cycles the GPU has
+/// ticked in total, so usage is the difference of an engine's cycles divided by the difference of total_cycles, see
+/// `GpuUsageStats::cycles_fraction_since()`. enc and dec are not separated either.
+///
+/// Regardless of the manufacturer, mem is always in bytes (never KiB)
+///
+/// Multi-die cards such as AMD's MI200 series expose every graphics compute die (GCD) as its own PCI function
+/// with its own DRM node, so stats are reported per GCD rather than summed up per physical card
+///
+/// The serialized form carries a schema version (see `GPU_USAGE_STATS_VERSION`) so that `resources-processes` and
+/// Resources can exchange stats even if their versions differ: missing fields are filled with defaults and unknown
+/// fields are ignored.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize, Copy)]
+#[serde(from = "GpuUsageStatsWire", into = "GpuUsageStatsWire")]
 pub struct GpuUsageStats {
     pub gfx: u64,
+    /// Used video memory in bytes
     pub mem: u64,
     pub enc: u64,
     pub dec: u64,
     pub nvidia: bool,
+    /// GPU cycles elapsed in total for drivers that report engine usage in cycles rather than ns (i.e. xe), 0 for
+    /// every other driver
+    pub total_cycles: u64,
+}
+
+/// Schema version of the serialized form of `GpuUsageStats`, to be bumped whenever its fields change
+pub const GPU_USAGE_STATS_VERSION: u8 = 2;
+
+/// Serialized form of `GpuUsageStats`. New fields must be appended (since the struct may be serialized positionally)
+/// and need a sensible default for payloads of older versions.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct GpuUsageStatsWire {
+    gfx: u64,
+    mem: u64,
+    enc: u64,
+    dec: u64,
+    nvidia: bool,
+    /// 0 for payloads from before the version was introduced
+    version: u8,
+    /// Since version 2
+    total_cycles: u64,
+}
+
+impl From<GpuUsageStatsWire> for GpuUsageStats {
+    fn from(wire: GpuUsageStatsWire) -> Self {
+        // versions 0 and 1 lack `total_cycles`, which defaults to 0 (i.e. ns) just like for drivers without cycles
+        Self {
+            gfx: wire.gfx,
+            mem: wire.mem,
+            enc: wire.enc,
+            dec: wire.dec,
+            nvidia: wire.nvidia,
+            total_cycles: wire.total_cycles,
+        }
+    }
+}
+
+impl From<GpuUsageStats> for GpuUsageStatsWire {
+    fn from(stats: GpuUsageStats) -> Self {
+        Self {
+            gfx: stats.gfx,
+            mem: stats.mem,
+            enc: stats.enc,
+            dec: stats.dec,
+            nvidia: stats.nvidia,
+            version: GPU_USAGE_STATS_VERSION,
+            total_cycles: stats.total_cycles,
+        }
+    }
+}
+
+/// Kinds of GPU engines that per-process statistics are gathered for
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+pub enum GpuEngine {
+    Graphics,
+    /// Video encoding, also includes video decoding for GPUs with a combined media engine (i.e. Intel)
+    Encode,
+    Decode,
+}
+
+impl GpuUsageStats {
+    /// Whether any engine of the GPU has been used by the process. For NVIDIA GPUs this refers to the current sampling
+    /// window, for every other vendor to the process' whole lifetime since their counters are cumulative. Merely
+    /// holding video memory doesn't count as usage.
+    pub fn is_active(&self) -> bool {
+        self.gfx > 0 || self.enc > 0 || self.dec > 0
+    }
+
+    /// Returns the engine the process has used the most, `None` if it hasn't used any engine at all. Ties are resolved
+    /// in favor of `GpuEngine::Graphics`, then `GpuEngine::Encode`.
+    ///
+    /// Since all engines of a process share the same unit (either percentages for NVIDIA or nanoseconds for every
+    /// other vendor), their values can be compared directly. Keep in mind that nanosecond counters accumulate over the
+    /// whole lifetime of the process though, use `GpuUsagePercent::dominant_engine()` for the current usage.
+    pub fn dominant_engine(&self) -> Option<GpuEngine> {
+        dominant_engine(self.gfx as f64, self.enc as f64, self.dec as f64)
+    }
+
+    /// Returns the raw counter (or percentage for NVIDIA) of `engine`
+    pub fn engine(&self, engine: GpuEngine) -> u64 {
+        match engine {
+            GpuEngine::Graphics => self.gfx,
+            GpuEngine::Encode => self.enc,
+            GpuEngine::Decode => self.dec,
+        }
+    }
+
+    /// For drivers reporting engine usage in GPU cycles (see `total_cycles`), returns the fraction (0 to 1) of the
+    /// cycles elapsed since `previous` that `engine` has been busy. Returns `None` if either stats don't use cycles or
+    /// if no cycles have elapsed, in which case the usage has to be calculated from the elapsed time instead.
+    pub fn cycles_fraction_since(&self, previous: &Self, engine: GpuEngine) -> Option<f64> {
+        if self.total_cycles == 0 || previous.total_cycles == 0 {
+            return None;
+        }
+
+        let total_delta = self.total_cycles.checked_sub(previous.total_cycles)?;
+        if total_delta == 0 {
+            return None;
+        }
+
+        let delta = self.engine(engine).saturating_sub(previous.engine(engine));
+
+        Some((delta as f64 / total_delta as f64).clamp(0.0, 1.0))
+    }
+}
+
+/// Usage of the engines of a GPU by a process in percent (0 to 100), see `ProcessData::gpu_usage_percent_since()`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuUsagePercent {
+    pub gfx: f64,
+    pub enc: f64,
+    pub dec: f64,
+}
+
+impl GpuUsagePercent {
+    /// Returns the engine the process has used the most in the interval, see `GpuUsageStats::dominant_engine()`
+    pub fn dominant_engine(&self) -> Option<GpuEngine> {
+        dominant_engine(self.gfx, self.enc, self.dec)
+    }
+}
+
+fn dominant_engine(gfx: f64, enc: f64, dec: f64) -> Option<GpuEngine> {
+    [
+        (GpuEngine::Graphics, gfx),
+        (GpuEngine::Encode, enc),
+        (GpuEngine::Decode, dec),
+    ]
+    .into_iter()
+    .filter(|(_, usage)| *usage > 0.0)
+    .reduce(|dominant, candidate| {
+        if candidate.1 > dominant.1 {
+            candidate
+        } else {
+            dominant
+        }
+    })
+    .map(|(engine, _)| engine)
+}
+
+/// Optional stateful smoother applying an exponential moving average to the per-process GPU usage of successive scans,
+/// keyed by PID and GPU. Raw values remain available through `ProcessData::gpu_usage_percent_since()`.
+///
+/// `alpha` (0 to 1) is the weight of the newest value, lower values smooth more aggressively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GpuUsageSmoother {
+    alpha: f64,
+    state: HashMap<(libc::pid_t, GpuIdentifier), GpuUsagePercent>,
+}
+
+impl GpuUsageSmoother {
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            state: HashMap::new(),
+        }
+    }
+
+    /// Feeds a new usage value for `pid` on the GPU `identifier` and returns the smoothed value. The first value of a
+    /// (PID, GPU) pair is passed through unchanged.
+    pub fn smooth(
+        &mut self,
+        pid: libc::pid_t,
+        identifier: GpuIdentifier,
+        usage: GpuUsagePercent,
+    ) -> GpuUsagePercent {
+        let alpha = self.alpha;
+        let ema = |new: f64, old: f64| alpha * new + (1.0 - alpha) * old;
+
+        let smoothed = match self.state.get(&(pid, identifier)) {
+            Some(old) => GpuUsagePercent {
+                gfx: ema(usage.gfx, old.gfx),
+                enc: ema(usage.enc, old.enc),
+                dec: ema(usage.dec, old.dec),
+            },
+            None => usage,
+        };
+
+        self.state.insert((pid, identifier), smoothed);
+        smoothed
+    }
+
+    /// Smooths all values of `ProcessData::gpu_usage_percent_since()` for the process of `current`.
+    pub fn smooth_process(
+        &mut self,
+        previous: &ProcessData,
+        current: &ProcessData,
+    ) -> BTreeMap<GpuIdentifier, GpuUsagePercent> {
+        current
+            .gpu_usage_percent_since(previous)
+            .into_iter()
+            .map(|(identifier, usage)| (identifier, self.smooth(current.pid, identifier, usage)))
+            .collect()
+    }
+
+    /// Drops the state of all processes whose PID isn't in `pids`, should be called after every scan so that a PID
+    /// being reused doesn't inherit the state of a previous process.
+    pub fn retain_pids(&mut self, pids: &HashSet<libc::pid_t>) {
+        self.state.retain(|(pid, _), _| pids.contains(pid));
+    }
+}
+
+/// Represents NPU usage statistics per-process
+///
+/// usage is the amount of nanoseconds spent for that process, all memory values are in bytes
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize, Copy)]
+pub struct NpuUsageStats {
+    pub usage: u64,
+    /// Total memory, i.e. `device_mem` + `shared_mem`
+    pub mem: u64,
+    /// Memory private to this process on the device
+    pub device_mem: u64,
+    /// Memory shared with the host or other processes
+    pub shared_mem: u64,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Copy, PartialOrd, Ord)]
+pub enum AcceleratorKind {
+    Gpu,
+    Npu,
+}
+
+/// Combined view of the GPU and NPU usage of a process, see `ProcessData::accelerator_stats()`
+///
+/// usage is to be interpreted like `GpuUsageStats::gfx` for GPUs and like `NpuUsageStats::usage` for NPUs, mem is
+/// always in bytes
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Copy)]
+pub struct AcceleratorStats {
+    pub kind: AcceleratorKind,
+    pub usage: u64,
+    pub mem: u64,
+    pub nvidia: bool,
+}
+
+/// Whether the I/O statistics of a process could be read
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IoStatsAccess {
+    #[default]
+    Readable,
+    /// The kernel doesn't provide I/O statistics for this process (or was built without `CONFIG_TASK_IO_ACCOUNTING`)
+    Absent,
+    /// The I/O statistics exist but we lack the privileges to read them (usually processes of other users)
+    Denied,
+}
+
+enum FdinfoStats {
+    Gpu(GpuIdentifier, GpuUsageStats),
+    Npu(PciSlot, NpuUsageStats),
+}
+
+/// Error returned when a process has exited before (or while) its data could be gathered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessVanished(pub libc::pid_t);
+
+impl std::error::Error for ProcessVanished {}
+
+impl Display for ProcessVanished {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "process with PID {} has vanished", self.0)
+    }
+}
+
+/// Error returned when an fdinfo file has been cut off while reading it, see `ProcessData::read_fdinfo()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FdinfoTruncated;
+
+impl std::error::Error for FdinfoTruncated {}
+
+impl Display for FdinfoTruncated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "fdinfo is truncated")
+    }
+}
+
+/// Raw contents of the files within `/proc/[pid]/` that `ProcessData` is parsed from
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct ProcFiles {
+    pub stat: String,
+    pub statm: String,
+    pub status: String,
+    pub comm: String,
+    pub cmdline: String,
+    pub io: Option<String>,
+    /// Why `io` is `None`
+    pub io_access: IoStatsAccess,
+    pub cgroup: Option<String>,
+    pub wchan: Option<String>,
+}
+
+impl ProcFiles {
+    /// Reads the files of the process at `proc_path`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if one of the mandatory files (i.e. the non-`Option` ones) couldn't be read
+    pub fn read<P: AsRef<Path>>(proc_path: P) -> Result<Self> {
+        let proc_path = proc_path.as_ref();
+        // read stat first, so that reading a process that's already gone fails without trying the other files
+        let stat = std::fs::read_to_string(proc_path.join("stat"))?;
+        let (io, io_access) = Self::read_io(proc_path);
+
+        Ok(Self {
+            stat,
+            statm: std::fs::read_to_string(proc_path.join("statm"))?,
+            status: std::fs::read_to_string(proc_path.join("status"))?,
+            comm: std::fs::read_to_string(proc_path.join("comm"))?,
+            cmdline: std::fs::read_to_string(proc_path.join("cmdline"))?,
+            io,
+            io_access,
+            cgroup: std::fs::read_to_string(proc_path.join("cgroup")).ok(),
+            wchan: std::fs::read_to_string(proc_path.join("wchan")).ok(),
+        })
+    }
+
+    fn read_io(proc_path: &Path) -> (Option<String>, IoStatsAccess) {
+        match std::fs::read_to_string(proc_path.join("io")) {
+            Ok(io) => (Some(io), IoStatsAccess::Readable),
+            Err(error) if error.kind() == ErrorKind::PermissionDenied => {
+                (None, IoStatsAccess::Denied)
+            }
+            Err(_) => (None, IoStatsAccess::Absent),
+        }
+    }
+}
+
+/// Controls which optional (and more expensive) pieces of information are gathered when scanning processes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// Count the open file descriptors and read their soft limit from `/proc/<pid>/limits`
+    pub fd_stats: bool,
+    /// Read the memory usage of the (cgroups v2) cgroup the process belongs to
+    pub cgroup_memory: bool,
+    /// Read the scheduler statistics from `/proc/<pid>/schedstat`
+    pub schedstat: bool,
+    /// Sum up the swapped-out memory from `/proc/<pid>/smaps_rollup`, which requires the kernel to walk all memory
+    /// mappings of the process and is therefore considerably more expensive than the rest of the scan
+    pub smaps_swap: bool,
+    /// Read the OOM killer's badness score and its adjustment from `/proc/<pid>/oom_score` and `oom_score_adj`
+    pub oom_score: bool,
+    /// Query the I/O priority of the process through `ioprio_get()`
+    pub io_priority: bool,
+    /// Don't resolve the names of the users processes belong to while scanning, leaving `ProcessData::user` and
+    /// `ProcessData::effective_user` empty. Use `ProcessData::username()` and `ProcessData::effective_username()` to
+    /// resolve them when they're actually needed.
+    pub lazy_usernames: bool,
+    /// Attribute the traffic of TCP sockets to the processes holding them, see the `net_io` module for the
+    /// limitations of this
+    pub network_io: bool,
+    /// Maximum time gathering the data of a single process may take before it's skipped.
+    ///
+    /// Reads from `/proc` can block indefinitely in rare cases (e.g. processes in frozen cgroups or stuck in
+    /// uninterruptible sleep on a network file system). With a deadline, processes are parsed on a worker thread
+    /// that is abandoned once it exceeds the deadline, so that one hung process can't stall the whole scan. The
+    /// abandoned thread stays around until the blocking read returns, which is why this is opt-in. Once
+    /// `MAX_STUCK_SCAN_WORKERS` threads are stuck, the remaining processes are skipped.
+    pub deadline: Option<Duration>,
+    /// Tell whether the processes have been frozen through the cgroup freezer, which is looked up once per cgroup
+    pub frozen: bool,
 }
 
 /// Data that could be transferred using `resources-processes`, separated from
 /// `Process` mainly due to `Icon` not being able to derive `Serialize` and
 /// `Deserialize`.
+///
+/// Fields missing from the serialized form (e.g. when it was produced by an older `resources-processes`) are filled
+/// with their defaults.
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProcessData {
     pub pid: libc::pid_t,
+    /// PID of the parent process as seen from the PID namespace whose `/proc` was read (i.e. the host's when running
+    /// on the host), even if the process lives in another PID namespace, see `ProcessData::pid_namespace()`
     pub parent_pid: libc::pid_t,
+    /// ID of the process group the process belongs to
+    pub pgrp: libc::pid_t,
+    /// ID of the foreground process group of the process' controlling terminal, -1 if it has none
+    pub tpgid: libc::pid_t,
+    /// Real UID of the process
+    pub uid: libc::uid_t,
+    /// Name of the user belonging to `uid`, empty if it wasn't resolved due to `ScanOptions::lazy_usernames`
     pub user: String,
+    /// The user whose privileges the process is running with, may differ from `user` for setuid binaries or
+    /// processes that dropped their privileges
+    pub effective_user: String,
+    pub effective_uid: libc::uid_t,
     pub comm: String,
     pub commandline: String,
+    pub state: ProcessState,
     pub user_cpu_time: u64,
     pub system_cpu_time: u64,
+    /// Time spent running a virtual CPU for a guest OS in clock ticks, already included in `user_cpu_time`
+    pub guest_time: u64,
+    /// Number of page faults that could be resolved without reading from disk
+    pub minor_faults: u64,
+    /// Number of page faults that required reading from disk, a high rate of these indicates thrashing
+    pub major_faults: u64,
+    /// Number of times the process gave up the CPU on its own, e.g. to wait for I/O
+    pub voluntary_ctxt_switches: Option<u64>,
+    /// Number of times the process was forced off the CPU, a high rate of these indicates CPU contention
+    pub nonvoluntary_ctxt_switches: Option<u64>,
     pub niceness: Niceness,
+    /// Scheduling policy of the process' main thread, other threads may use a different one (e.g. the realtime
+    /// threads of audio servers)
+    pub scheduling_policy: SchedulingPolicy,
+    /// Realtime priority (1 to 99) of the process' main thread, 0 for non-realtime scheduling policies
+    pub realtime_priority: u32,
+    /// I/O priority of the process' main thread, `None` if it couldn't be queried
+    pub io_priority: Option<IoPriority>,
     pub affinity: Vec<bool>,
+    /// CPU the process has last been running on
+    pub last_cpu: Option<usize>,
     pub memory_usage: usize,
+    /// Swapped-out memory in bytes according to `VmSwap` in `/proc/<pid>/status`, which is cheap to read but only
+    /// covers private anonymous memory, see `smaps_swap_usage` for a more accurate figure
     pub swap_usage: usize,
+    /// Swapped-out memory in bytes summed up from the `Swap` lines of `/proc/<pid>/smaps_rollup`. Unlike `swap_usage`,
+    /// this also includes swapped-out shared memory (e.g. shmem and tmpfs pages mapped by the process). Only gathered
+    /// if requested through `ScanOptions::smaps_swap`
+    pub smaps_swap_usage: Option<usize>,
+    /// Peak virtual memory size in bytes (`VmPeak`), `None` for processes that don't report it (e.g. kernel threads)
+    pub peak_memory_usage: Option<usize>,
+    /// Peak resident set size in bytes (`VmHWM`), `None` for processes that don't report it (e.g. kernel threads)
+    pub peak_resident: Option<usize>,
+    /// Resident shared memory in bytes (`RssShmem`, i.e. System V shared memory, shared anonymous mappings and mapped
+    /// tmpfs files). Since it's accounted to every process mapping it, summing it up over several processes counts it
+    /// multiple times. `None` for processes that don't report it (e.g. kernel threads)
+    pub shared_memory: Option<usize>,
+    /// Memory in bytes backed by explicitly requested huge pages (`HugetlbPages`, e.g. used by databases and VMs),
+    /// which isn't part of `memory_usage`. `None` for processes that don't report it (e.g. kernel threads)
+    pub hugepages_usage: Option<usize>,
     pub starttime: u64, // in clock ticks, see man proc(5)!
     pub cgroup: Option<String>,
+    /// Path of the process' cgroup within the unified (cgroups v2) hierarchy, e.g.
+    /// `/user.slice/user-1000.slice/session-2.scope`
+    pub cgroup_path: Option<String>,
+    /// Memory usage of the whole cgroup (e.g. systemd scope or service) the process belongs to in bytes, only
+    /// gathered if requested through `ScanOptions::cgroup_memory`
+    pub cgroup_memory_usage: Option<u64>,
+    /// Whether the process' cgroup is frozen, either through its own `cgroup.freeze` or that of an ancestor. Frozen
+    /// processes don't run but, unlike processes halted by `SIGSTOP`, aren't in the stopped state (T). Only gathered
+    /// if requested through `ScanOptions::frozen`.
+    pub frozen: bool,
+    /// Kernel function the process is sleeping in, `None` if it's not waiting
+    pub wchan: Option<String>,
     pub containerization: Containerization,
+    /// ID of the Docker or Podman container or name of the LXC container the process runs in
+    pub container_id: Option<String>,
     pub read_bytes: Option<u64>,
     pub write_bytes: Option<u64>,
+    /// Tells apart whether `read_bytes` and `write_bytes` are `None` due to missing privileges or because there are
+    /// no I/O statistics for this process at all
+    pub io_access: IoStatsAccess,
+    /// Bytes sent over the process' currently open TCP sockets, only gathered if requested through
+    /// `ScanOptions::network_io`. Since closed sockets drop out, this may decrease between scans.
+    pub net_sent_bytes: Option<u64>,
+    /// Bytes received over the process' currently open TCP sockets, only gathered if requested through
+    /// `ScanOptions::network_io`. Since closed sockets drop out, this may decrease between scans.
+    pub net_received_bytes: Option<u64>,
+    /// Time the process spent waiting for a CPU while being runnable in nanoseconds, only gathered if requested
+    /// through `ScanOptions::schedstat`
+    pub cpu_wait_time: Option<u64>,
+    /// Number of timeslices the process has run on a CPU, only gathered if requested through `ScanOptions::schedstat`
+    pub timeslices: Option<u64>,
+    /// Number of open file descriptors, only gathered if requested through `ScanOptions::fd_stats`
+    pub open_fds: Option<u64>,
+    /// Soft limit for the number of open file descriptors, only gathered if requested through
+    /// `ScanOptions::fd_stats`
+    pub fd_soft_limit: Option<u64>,
+    /// Badness score the OOM killer currently assigns to the process (0 to 1000 plus the adjustment), the process
+    /// with the highest score is killed first
+    pub oom_score: Option<u32>,
+    /// Adjustment of `oom_score` from -1000 (never kill this process) to 1000 (always kill this process first)
+    pub oom_score_adj: Option<i16>,
+    /// Label of the Linux Security Module confining the process, e.g.
+    /// `unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023` for SELinux or `/usr/sbin/cupsd (enforce)` for
+    /// AppArmor. `None` if no LSM providing labels is active.
+    pub security_context: Option<String>,
     pub timestamp: u64,
     /// Key: PCI Slot ID of the GPU
     pub gpu_usage_stats: BTreeMap<GpuIdentifier, GpuUsageStats>,
+    /// Key: PCI Slot ID of the GPU, value: time (in milliseconds since the Unix epoch) of the newest NVML utilization
+    /// sample the NVIDIA stats in `gpu_usage_stats` are based on. Missing if there was no sample within the sampling
+    /// window (e.g. for processes that have just started), see `gpu_usage_age()`
+    pub gpu_usage_sampled_at: BTreeMap<GpuIdentifier, u64>,
+    /// Key: PCI Slot ID of the NPU
+    pub npu_usage_stats: BTreeMap<PciSlot, NpuUsageStats>,
 }
 
 impl ProcessData {
-    fn sanitize_cgroup<S: AsRef<str>>(cgroup: S) -> Option<String> {
-        let cgroups_v2_line = cgroup.as_ref().split('\n').find(|s| s.starts_with("0::"))?;
-        if cgroups_v2_line.ends_with(".scope") {
-            let cgroups_segments: Vec<&str> = cgroups_v2_line.split('-').collect();
-            if cgroups_segments.len() > 1 {
-                cgroups_segments
-                    .get(cgroups_segments.len() - 2)
-                    .map(|s| unescape::unescape(s).unwrap_or_else(|| (*s).to_string()))
+    /// Returns the path of the process' cgroup within the unified (cgroups v2) hierarchy
+    fn cgroup_v2_path(cgroup: &str) -> Option<&str> {
+        cgroup
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .map(str::trim)
+    }
+
+    fn cgroup2_mount_point(mountinfo: &str) -> Option<PathBuf> {
+        mountinfo.lines().find_map(|line| {
+            let (mount, filesystem) = line.split_once(" - ")?;
+            if filesystem.split(' ').next()? == "cgroup2" {
+                mount.split(' ').nth(4).map(PathBuf::from)
             } else {
                 None
             }
-        } else if cgroups_v2_line.ends_with(".service") {
-            let cgroups_segments: Vec<&str> = cgroups_v2_line.split('/').collect();
-            if let Some(last) = cgroups_segments.last() {
-                last[0..last.len() - 8]
-                    .split('@')
-                    .next()
-                    .map(|s| unescape::unescape(s).unwrap_or_else(|| s.to_string()))
-                    .map(|s| {
-                        if s.contains("dbus-:") {
-                            s.split('-').last().unwrap_or(&s).to_string()
-                        } else {
-                            s
+        })
+    }
+
+    /// Reads `memory.current` of the given cgroup, processes in the root cgroup (e.g. kernel threads) have none
+    fn cgroup_memory_usage(cgroup_path: &str) -> Option<u64> {
+        let relative_path = cgroup_path.trim_start_matches('/');
+        if relative_path.is_empty() {
+            return None;
+        }
+
+        std::fs::read_to_string(
+            CGROUP2_MOUNT_POINT
+                .join(relative_path)
+                .join("memory.current"),
+        )
+        .ok()
+        .and_then(|memory| memory.trim().parse().ok())
+    }
+
+    /// Returns how close the (cgroups v2) cgroup of the process with the given PID is to its memory limit as a
+    /// fraction of `memory.current` and `memory.max`. This is more meaningful than the process' own memory usage for
+    /// containers and services with a memory limit. `None` if the cgroup has no limit or can't be read.
+    pub fn memory_pressure(pid: libc::pid_t) -> Option<f64> {
+        let cgroup_dir = Self::cgroup_dir(pid)?;
+        let current = std::fs::read_to_string(cgroup_dir.join("memory.current")).ok()?;
+        let max = std::fs::read_to_string(cgroup_dir.join("memory.max")).ok()?;
+
+        Self::memory_fraction(&current, &max)
+    }
+
+    /// Returns the energy in microjoules consumed by the (cgroups v2) cgroup of the process with the given PID, which
+    /// allows approximating the energy usage of individual applications.
+    ///
+    /// This is best-effort: mainline Linux doesn't account energy per cgroup, so this only works on kernels that
+    /// expose an `energy_uj` counter in the cgroup's directory and returns `None` everywhere else.
+    pub fn energy_uj(pid: libc::pid_t) -> Option<u64> {
+        std::fs::read_to_string(Self::cgroup_dir(pid)?.join("energy_uj"))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Returns the PIDs of the processes that are members of the (cgroups v2) cgroup at `cgroup_path` or any of its
+    /// descendants (which are frozen along with it), `None` for the root cgroup or if a cgroup can't be read
+    pub fn cgroup_pids(cgroup_path: &str) -> Option<Vec<libc::pid_t>> {
+        let relative_path = cgroup_path.trim_start_matches('/');
+        if relative_path.is_empty() {
+            return None;
+        }
+
+        let mut pids = Vec::new();
+        Self::collect_cgroup_pids(&CGROUP2_MOUNT_POINT.join(relative_path), &mut pids)?;
+
+        Some(pids)
+    }
+
+    fn collect_cgroup_pids(cgroup_dir: &Path, pids: &mut Vec<libc::pid_t>) -> Option<()> {
+        let procs = std::fs::read_to_string(cgroup_dir.join("cgroup.procs")).ok()?;
+        pids.extend(
+            procs
+                .lines()
+                .filter_map(|pid| pid.trim().parse::<libc::pid_t>().ok()),
+        );
+
+        for entry in std::fs::read_dir(cgroup_dir).ok()?.flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                Self::collect_cgroup_pids(&entry.path(), pids)?;
+            }
+        }
+
+        Some(())
+    }
+
+    /// Returns whether the (cgroups v2) cgroup at `cgroup_path` is frozen according to its `cgroup.events`, which
+    /// unlike `cgroup.freeze` also reflects cgroups that are frozen because one of their ancestors is
+    #[must_use]
+    pub fn is_cgroup_frozen(cgroup_path: &str) -> bool {
+        let relative_path = cgroup_path.trim_start_matches('/');
+        if relative_path.is_empty() {
+            return false;
+        }
+
+        std::fs::read_to_string(
+            CGROUP2_MOUNT_POINT
+                .join(relative_path)
+                .join("cgroup.events"),
+        )
+        .is_ok_and(|events| Self::frozen_from_cgroup_events(&events))
+    }
+
+    fn frozen_from_cgroup_events(events: &str) -> bool {
+        events
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .any(|(key, value)| key == "frozen" && value.trim() == "1")
+    }
+
+    /// Thaws the (cgroups v2) cgroup at `cgroup_path` along with those of its ancestors that have been frozen through
+    /// their `cgroup.freeze`, since a cgroup stays frozen as long as one of its ancestors is
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if one of the frozen cgroups isn't writable, e.g. because it doesn't belong to the user
+    pub fn thaw_cgroup(cgroup_path: &str) -> Result<()> {
+        let mut cgroup_path = cgroup_path.trim_end_matches('/');
+
+        while !cgroup_path.trim_start_matches('/').is_empty() {
+            let freeze = std::fs::read_to_string(
+                CGROUP2_MOUNT_POINT
+                    .join(cgroup_path.trim_start_matches('/'))
+                    .join("cgroup.freeze"),
+            );
+
+            if freeze.is_ok_and(|freeze| freeze.trim() == "1") {
+                Self::set_cgroup_frozen(cgroup_path, false)?;
+            }
+
+            cgroup_path = cgroup_path
+                .rsplit_once('/')
+                .map_or("", |(parent, _)| parent);
+        }
+
+        Ok(())
+    }
+
+    /// Freezes or thaws the (cgroups v2) cgroup at `cgroup_path` along with all of its descendants using the cgroup
+    /// freezer. Unlike `SIGSTOP`, this can't be noticed or ignored by the processes and also catches processes that
+    /// are forked in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` for the root cgroup (which can't be frozen), on kernels without the cgroup freezer or if
+    /// the cgroup isn't writable, e.g. because it doesn't belong to the user
+    pub fn set_cgroup_frozen(cgroup_path: &str, frozen: bool) -> Result<()> {
+        let relative_path = cgroup_path.trim_start_matches('/');
+        if relative_path.is_empty() {
+            bail!("the root cgroup can't be frozen");
+        }
+
+        std::fs::write(
+            CGROUP2_MOUNT_POINT
+                .join(relative_path)
+                .join("cgroup.freeze"),
+            if frozen { "1" } else { "0" },
+        )
+        .with_context(|| format!("unable to set frozen state of cgroup {cgroup_path}"))
+    }
+
+    /// Returns the directory of the (cgroups v2) cgroup of the process with the given PID, `None` for the root cgroup
+    fn cgroup_dir(pid: libc::pid_t) -> Option<PathBuf> {
+        let cgroup = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+        let relative_path = Self::cgroup_v2_path(&cgroup)?.trim_start_matches('/');
+        if relative_path.is_empty() {
+            return None;
+        }
+
+        Some(CGROUP2_MOUNT_POINT.join(relative_path))
+    }
+
+    fn memory_fraction(current: &str, max: &str) -> Option<f64> {
+        let current = current.trim().parse::<u64>().ok()?;
+        // unlimited cgroups contain "max"
+        let max = max.trim().parse::<u64>().ok().filter(|max| *max > 0)?;
+
+        Some((current as f64 / max as f64).clamp(0.0, 1.0))
+    }
+
+    /// Recognizes the OCI and LXC containers whose cgroups the container runtimes create, returning the kind of
+    /// container along with its ID (Docker and Podman) or name (LXC)
+    fn container_from_cgroup_path(cgroup_path: &str) -> Option<(Containerization, String)> {
+        let is_container_id =
+            |id: &str| id.len() >= 12 && id.chars().all(|c| c.is_ascii_hexdigit());
+
+        let mut segments = cgroup_path.split('/').filter(|segment| !segment.is_empty());
+        while let Some(segment) = segments.next() {
+            if let Some(id) = segment
+                .strip_prefix("docker-")
+                .and_then(|scope| scope.strip_suffix(".scope"))
+                .filter(|id| is_container_id(id))
+            {
+                return Some((Containerization::Docker, id.to_string()));
+            } else if let Some(id) = segment
+                .strip_prefix("libpod-")
+                .and_then(|scope| scope.strip_suffix(".scope"))
+                .filter(|id| is_container_id(id))
+            // also rules out "libpod-conmon-<id>.scope"
+            {
+                return Some((Containerization::Podman, id.to_string()));
+            } else if let Some(name) = segment.strip_prefix("lxc.payload.") {
+                return Some((Containerization::Lxc, name.to_string()));
+            } else if segment == "docker" {
+                if let Some(id) = segments.next().filter(|id| is_container_id(id)) {
+                    return Some((Containerization::Docker, id.to_string()));
+                }
+            } else if segment == "lxc" {
+                if let Some(name) = segments.next() {
+                    return Some((Containerization::Lxc, name.to_string()));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn sanitize_cgroup<S: AsRef<str>>(cgroup: S) -> Option<String> {
+        let cgroups_v2_line = cgroup.as_ref().split('\n').find(|s| s.starts_with("0::"))?;
+        if cgroups_v2_line.ends_with(".scope") {
+            let cgroups_segments: Vec<&str> = cgroups_v2_line.split('-').collect();
+            if cgroups_segments.len() > 1 {
+                cgroups_segments
+                    .get(cgroups_segments.len() - 2)
+                    .map(|s| unescape::unescape(s).unwrap_or_else(|| (*s).to_string()))
+            } else {
+                None
+            }
+        } else if cgroups_v2_line.ends_with(".service") {
+            let cgroups_segments: Vec<&str> = cgroups_v2_line.split('/').collect();
+            if let Some(last) = cgroups_segments.last() {
+                last[0..last.len() - 8]
+                    .split('@')
+                    .next()
+                    .map(|s| unescape::unescape(s).unwrap_or_else(|| s.to_string()))
+                    .map(|s| {
+                        if s.contains("dbus-:") {
+                            s.split('-').last().unwrap_or(&s).to_string()
+                        } else {
+                            s
                         }
                     })
             } else {
@@ -220,19 +1383,229 @@ impl ProcessData {
         }
     }
 
-    fn get_uid(proc_path: &Path) -> Result<u32> {
-        let status = std::fs::read_to_string(proc_path.join("status"))?;
-        if let Some(captures) = RE_UID.captures(&status) {
-            let first_num_str = captures.get(1).context("no uid found")?;
-            first_num_str
+    fn is_appimage(proc_path: &Path) -> bool {
+        let mounted_executable = std::fs::read_link(proc_path.join("exe"))
+            .map(|exe| {
+                exe.components().any(|component| {
+                    component
+                        .as_os_str()
+                        .to_string_lossy()
+                        .starts_with(".mount_")
+                })
+            })
+            .unwrap_or(false);
+
+        mounted_executable
+            || std::fs::read(proc_path.join("environ"))
+                .map(|environ| {
+                    environ
+                        .split(|byte| *byte == b'\0')
+                        .any(|variable| variable.starts_with(b"APPIMAGE="))
+                })
+                .unwrap_or(false)
+    }
+
+    fn io_priority(pid: libc::pid_t) -> Option<IoPriority> {
+        const IOPRIO_WHO_PROCESS: usize = 1;
+
+        unsafe { syscalls::syscall!(syscalls::Sysno::ioprio_get, IOPRIO_WHO_PROCESS, pid) }
+            .ok()
+            .map(|ioprio| IoPriority::from_raw(ioprio as i32))
+    }
+
+    /// Returns the root directory of the process with the given PID as seen from our mount namespace. If it differs
+    /// from `/`, the process is running in a chroot or a container. `None` if we lack the privileges to find out.
+    pub fn root(pid: libc::pid_t) -> Option<PathBuf> {
+        std::fs::read_link(format!("/proc/{pid}/root")).ok()
+    }
+
+    /// Returns the inode of the mount namespace of the process with the given PID. Processes sharing the same mount
+    /// namespace are usually part of the same container. `None` if we lack the privileges to find out.
+    pub fn mnt_namespace(pid: libc::pid_t) -> Option<u64> {
+        Self::namespace_inode(pid, "mnt")
+    }
+
+    /// Returns the inode of the PID namespace of the process with the given PID. Processes in a PID namespace other
+    /// than ours form an isolated process tree (e.g. a container) and know each other by PIDs that differ from the
+    /// ones in our `/proc`, the PIDs reported by `ProcessData` are always the ones of our namespace. `None` if we lack
+    /// the privileges to find out.
+    pub fn pid_namespace(pid: libc::pid_t) -> Option<u64> {
+        Self::namespace_inode(pid, "pid")
+    }
+
+    fn namespace_inode(pid: libc::pid_t, namespace: &str) -> Option<u64> {
+        let target = std::fs::read_link(format!("/proc/{pid}/ns/{namespace}")).ok()?;
+        Self::parse_namespace_link(&target.to_string_lossy(), namespace)
+    }
+
+    fn parse_namespace_link(target: &str, namespace: &str) -> Option<u64> {
+        target
+            .strip_prefix(namespace)?
+            .strip_prefix(":[")?
+            .strip_suffix(']')?
+            .parse()
+            .ok()
+    }
+
+    /// Makes a best-effort guess whether the process with the given PID is a Wayland or an X11 client based on
+    /// its environment. Since this requires reading `/proc/<pid>/environ`, it is not part of the regular scan and
+    /// will return `None` for processes of other users unless we're privileged.
+    pub fn display_protocol(pid: libc::pid_t) -> Option<DisplayProtocol> {
+        std::fs::read(format!("/proc/{pid}/environ"))
+            .ok()
+            .and_then(|environ| Self::display_protocol_from_environ(&environ))
+    }
+
+    /// Returns the environment variables of the process with the given PID in the order they're stored in. This is
+    /// the environment the process was started with, changes the process made to its own environment afterwards
+    /// (e.g. using `setenv()`) are usually not reflected.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the process doesn't exist (anymore) or belongs to another user and we're not privileged
+    pub fn environ(pid: libc::pid_t) -> Result<Vec<(String, String)>> {
+        let environ = std::fs::read(format!("/proc/{pid}/environ"))
+            .with_context(|| format!("unable to read environment of {pid}"))?;
+        Ok(Self::parse_environ(&environ))
+    }
+
+    fn parse_environ(environ: &[u8]) -> Vec<(String, String)> {
+        environ
+            .split(|byte| *byte == b'\0')
+            .filter(|variable| !variable.is_empty())
+            .map(|variable| {
+                let variable = String::from_utf8_lossy(variable);
+                match variable.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => (variable.to_string(), String::new()),
+                }
+            })
+            .collect()
+    }
+
+    fn display_protocol_from_environ(environ: &[u8]) -> Option<DisplayProtocol> {
+        let mut wayland_display = false;
+        let mut x11_display = false;
+        let mut forced_x11 = false;
+
+        for variable in environ.split(|byte| *byte == b'\0') {
+            let Some((key, value)) = std::str::from_utf8(variable)
+                .ok()
+                .and_then(|variable| variable.split_once('='))
+            else {
+                continue;
+            };
+
+            match key {
+                "WAYLAND_DISPLAY" => wayland_display = !value.is_empty(),
+                "DISPLAY" => x11_display = !value.is_empty(),
+                // toolkits that were explicitly told to use X11 even though a Wayland compositor is available
+                "GDK_BACKEND" | "SDL_VIDEODRIVER" => forced_x11 |= value.starts_with("x11"),
+                "QT_QPA_PLATFORM" => forced_x11 |= value.starts_with("xcb"),
+                _ => {}
+            }
+        }
+
+        if wayland_display && !forced_x11 {
+            Some(DisplayProtocol::Wayland)
+        } else if x11_display {
+            Some(DisplayProtocol::X11)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the time spent on the CPU (ns), the time spent waiting for a CPU (ns) and the number of timeslices
+    fn parse_schedstat(schedstat: &str) -> Option<(u64, u64, u64)> {
+        let mut values = schedstat
+            .split_whitespace()
+            .map(|value| value.parse::<u64>().ok());
+
+        Some((values.next()??, values.next()??, values.next()??))
+    }
+
+    /// Returns the sum of all `Swap` rows of `smaps` or `smaps_rollup` in bytes
+    fn smaps_swap_usage(smaps: &str) -> Option<usize> {
+        RE_SMAPS_SWAP
+            .captures_iter(smaps)
+            .map(|captures| captures.get(1)?.as_str().parse::<usize>().ok())
+            .sum::<Option<usize>>()
+            .map(|kib| kib.saturating_mul(1024))
+    }
+
+    fn resolve_username(uid: libc::uid_t) -> String {
+        USERS_CACHE
+            .get(&uid)
+            .cloned()
+            .unwrap_or(String::from("root"))
+    }
+
+    /// Returns the name of the user the process belongs to, resolving it from `uid` if it wasn't resolved during the
+    /// scan (see `ScanOptions::lazy_usernames`)
+    pub fn username(&self) -> String {
+        if self.user.is_empty() {
+            Self::resolve_username(self.uid)
+        } else {
+            self.user.clone()
+        }
+    }
+
+    /// Like `username()`, but for the user whose privileges the process is running with
+    pub fn effective_username(&self) -> String {
+        if self.effective_user.is_empty() {
+            Self::resolve_username(self.effective_uid)
+        } else {
+            self.effective_user.clone()
+        }
+    }
+
+    /// Returns the soft limit of the `Max open files` row, `None` if it's missing or unlimited
+    fn fd_soft_limit(limits: &str) -> Option<u64> {
+        RE_MAX_OPEN_FILES
+            .captures(limits)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse().ok())
+    }
+
+    /// Returns the real and the effective UID
+    fn get_uids(status: &str) -> Result<(u32, u32)> {
+        if let Some(captures) = RE_UID.captures(status) {
+            let real_uid = captures
+                .get(1)
+                .context("no uid found")?
                 .as_str()
                 .parse::<u32>()
-                .context("couldn't parse uid in /status")
+                .context("couldn't parse uid in /status")?;
+            let effective_uid = captures
+                .get(2)
+                .context("no effective uid found")?
+                .as_str()
+                .parse::<u32>()
+                .context("couldn't parse effective uid in /status")?;
+            Ok((real_uid, effective_uid))
         } else {
-            Ok(0)
+            Ok((0, 0))
         }
     }
 
+    /// Returns the total amount of video memory in bytes of the NVIDIA GPU in the given PCI slot, so that the
+    /// per-process `mem` values can be put into perspective without querying NVML again
+    pub fn nvidia_total_vram(pci_slot: &PciSlot) -> Option<u64> {
+        NVIDIA_TOTAL_VRAM.get(pci_slot).copied()
+    }
+
+    /// Sets how far back NVML utilization samples are considered when gathering per-process NVIDIA stats. This
+    /// should roughly match the interval in which the stats are refreshed. Defaults to 5 seconds.
+    pub fn set_nvidia_sample_window(window: Duration) {
+        *NVIDIA_SAMPLE_WINDOW.write().unwrap() = window;
+    }
+
+    /// Sets whether processes with graphics contexts, compute contexts or both are reported for NVIDIA GPUs, e.g.
+    /// for monitors of headless compute machines. Defaults to `NvidiaProcessQuery::Both`.
+    pub fn set_nvidia_process_query(query: NvidiaProcessQuery) {
+        *NVIDIA_PROCESS_QUERY.write().unwrap() = query;
+    }
+
     pub fn update_nvidia_stats() {
         {
             let mut stats = NVIDIA_PROCESSES_STATS.write().unwrap();
@@ -246,444 +1619,3517 @@ impl ProcessData {
         }
     }
 
-    pub fn all_process_data() -> Result<Vec<Self>> {
-        Self::update_nvidia_stats();
+    /// Cheaper variant of `update_nvidia_stats()` that is used for every refresh: the cached stats are updated in
+    /// place, reusing their allocations, and GPUs that NVML reports to have no running processes aren't queried for
+    /// per-process stats at all.
+    pub fn refresh_nvidia_stats() {
+        let mut stats = NVIDIA_PROCESSES_STATS.write().unwrap();
+        let mut infos = NVIDIA_PROCESS_INFOS.write().unwrap();
+
+        for (pci_slot, gpu) in NVML_DEVICES.iter() {
+            let device_stats = stats.entry(*pci_slot).or_default();
+            let device_infos = infos.entry(*pci_slot).or_default();
 
-        let mut process_data = vec![];
-        for entry in glob("/proc/[0-9]*/").context("unable to glob")?.flatten() {
-            let data = ProcessData::try_from_path(&entry);
+            device_stats.clear();
+            device_infos.clear();
 
-            if let Ok(data) = data {
-                process_data.push(data);
+            let idle = match *NVIDIA_PROCESS_QUERY.read().unwrap() {
+                NvidiaProcessQuery::Graphics => {
+                    matches!(gpu.running_graphics_processes_count(), Ok(0))
+                }
+                NvidiaProcessQuery::Compute => {
+                    matches!(gpu.running_compute_processes_count(), Ok(0))
+                }
+                NvidiaProcessQuery::Both => matches!(
+                    (
+                        gpu.running_graphics_processes_count(),
+                        gpu.running_compute_processes_count()
+                    ),
+                    (Ok(0), Ok(0))
+                ),
+            };
+            if idle {
+                continue;
             }
-        }
 
-        Ok(process_data)
+            device_stats.extend(Self::nvidia_device_process_stats(gpu));
+            device_infos.extend(Self::nvidia_device_process_infos(gpu));
+        }
     }
 
-    pub fn try_from_path<P: AsRef<Path>>(proc_path: P) -> Result<Self> {
-        let proc_path = proc_path.as_ref();
-        let stat = std::fs::read_to_string(proc_path.join("stat"))?;
-        let statm = std::fs::read_to_string(proc_path.join("statm"))?;
-        let status = std::fs::read_to_string(proc_path.join("status"))?;
-        let comm = std::fs::read_to_string(proc_path.join("comm"))?;
-        let commandline = std::fs::read_to_string(proc_path.join("cmdline"))?;
-        let io = std::fs::read_to_string(proc_path.join("io")).ok();
+    /// Returns the GPU usage statistics of a single process without gathering the rest of its data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with a `ProcessVanished` if there is no process with the given PID
+    pub fn gpu_usage_for_pid(pid: libc::pid_t) -> Result<BTreeMap<GpuIdentifier, GpuUsageStats>> {
+        let proc_path = PathBuf::from("/proc").join(pid.to_string());
+        if !proc_path.exists() {
+            return Err(ProcessVanished(pid).into());
+        }
 
-        let pid = proc_path
-            .file_name()
-            .context("proc_path terminates in ..")?
-            .to_str()
-            .context("can't turn OsStr to str")?
-            .parse()?;
+        Self::update_nvidia_stats();
 
-        let user = USERS_CACHE
-            .get(&Self::get_uid(proc_path)?)
-            .cloned()
-            .unwrap_or(String::from("root"));
+        Ok(Self::accelerator_usage_stats(&proc_path, pid).0)
+    }
 
-        let stat = stat
-            .split(')') // since we don't care about the pid or the executable name, split after the executable name to make our life easier
-            .last()
-            .context("stat doesn't have ')'")?
-            .split(' ')
-            .skip(1) // the first element would be a space, let's ignore that
-            .collect::<Vec<_>>();
+    /// Returns the NPU usage statistics of a single process without gathering the rest of its data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with a `ProcessVanished` if there is no process with the given PID
+    pub fn npu_usage_for_pid(pid: libc::pid_t) -> Result<BTreeMap<PciSlot, NpuUsageStats>> {
+        let proc_path = PathBuf::from("/proc").join(pid.to_string());
+        if !proc_path.exists() {
+            return Err(ProcessVanished(pid).into());
+        }
 
-        let statm = statm.split(' ').collect::<Vec<_>>();
+        Ok(Self::fdinfo_usage_stats(&proc_path, pid)
+            .map(|(_, npu_stats)| npu_stats)
+            .unwrap_or_default())
+    }
 
-        let comm = comm.replace('\n', "");
+    /// Returns the point in time at which the process was started or `None` if the boot time or the clock tick
+    /// rate are unavailable
+    pub fn start_time(&self) -> Option<SystemTime> {
+        let clock_ticks = (*CLOCK_TICKS)?;
+        let since_boot = Duration::from_secs_f64(self.starttime as f64 / clock_ticks as f64);
+        (*BOOT_TIME)?.checked_add(since_boot)
+    }
 
-        // -2 to accommodate for only collecting after the second item (which is the executable name as mentioned above)
-        let parent_pid = stat
-            .get(STAT_PARENT_PID)
-            .context("wrong stat file format")
-            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
-        let user_cpu_time = stat
-            .get(STAT_USER_CPU_TIME)
-            .context("wrong stat file format")
-            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
-        let system_cpu_time = stat
-            .get(STAT_SYSTEM_CPU_TIME)
-            .context("wrong stat file format")
-            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
-        let nice = stat
-            .get(STAT_NICE)
-            .context("wrong stat file format")
-            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
-        let starttime = stat
-            .get(STAT_STARTTIME)
-            .context("wrong stat file format")
-            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+    /// Returns for how long the process has been running or `None` if that can't be determined
+    pub fn uptime(&self) -> Option<Duration> {
+        let start_time = self.start_time()?;
+        // the boot time is only accurate to a few milliseconds, so very young processes might appear to have been
+        // started in the future
+        Some(
+            SystemTime::now()
+                .duration_since(start_time)
+                .unwrap_or_default(),
+        )
+    }
 
-        let mut affinity = Vec::with_capacity(*NUM_CPUS);
-        RE_AFFINITY
-            .captures(&status)
-            .and_then(|captures| captures.get(1))
-            .map(|capture| capture.as_str())
-            .unwrap_or_default()
-            .chars()
-            .map(|c| c.to_digit(16).unwrap_or_default())
-            .rev()
-            .for_each(|int| {
-                // we want the bits and there are 4 bits in a hex digit
-                (0..4).for_each(|i| {
-                    // this if should prevent wrong size affinity vecs if the thread count is not divisible by 4
-                    if affinity.len() < *NUM_CPUS {
-                        affinity.push((int & (1 << i)) != 0);
-                    }
-                });
+    /// Returns the name of the process without the kernel's truncation of `comm` to 15 bytes by preferring the
+    /// name of the executable or the first argument of the command line if `comm` appears to be a cut-off version of
+    /// either of them. Falls back to `comm`.
+    pub fn full_comm(&self) -> String {
+        let exe_name = std::fs::read_link(format!("/proc/{}/exe", self.pid))
+            .ok()
+            .and_then(|exe| {
+                exe.file_name().map(|name| {
+                    name.to_string_lossy()
+                        .trim_end_matches(" (deleted)")
+                        .to_string()
+                })
             });
 
-        let swap_usage = RE_SWAP_USAGGE
-            .captures(&status)
-            .and_then(|captures| captures.get(1))
-            .map(|capture| capture.as_str())
-            .unwrap_or_default()
-            .parse::<usize>()
-            .unwrap_or_default() // kworkers don't have swap usage
-            .saturating_mul(1000);
+        Self::untruncated_comm(&self.comm, exe_name.as_deref(), &self.commandline)
+    }
+
+    fn untruncated_comm(comm: &str, exe_name: Option<&str>, commandline: &str) -> String {
+        // TASK_COMM_LEN is 16 including the terminating null byte
+        if comm.len() < 15 {
+            return comm.to_string();
+        }
+
+        let argv0_name = commandline
+            .split('\0')
+            .next()
+            .and_then(|argv0| Path::new(argv0).file_name())
+            .and_then(|name| name.to_str());
+
+        exe_name
+            .into_iter()
+            .chain(argv0_name)
+            .find(|name| name.len() > comm.len() && name.starts_with(comm))
+            .unwrap_or(comm)
+            .to_string()
+    }
+
+    /// Makes a best-effort guess of the ID of the application (i.e. the name of its desktop entry without `.desktop`,
+    /// e.g. `org.mozilla.firefox`) the process belongs to, which can then be resolved to a name and an icon.
+    ///
+    /// Desktop environments launch applications (including Flatpaks) into a systemd unit named
+    /// `app-[<launcher>-]<app id>-<random>.scope` or `app-[<launcher>-]<app id>[@<random>].service`, which is the most
+    /// reliable source. Otherwise, the name of the executable is returned, which often but not always matches.
+    pub fn desktop_id_hint(&self) -> Option<String> {
+        self.cgroup_path
+            .as_deref()
+            .and_then(Self::app_id_from_cgroup_path)
+            .or_else(|| {
+                self.commandline
+                    .split('\0')
+                    .next()
+                    .and_then(|argv0| Path::new(argv0).file_name())
+                    .and_then(|name| name.to_str())
+                    .or(Some(self.comm.trim()))
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+            })
+    }
+
+    fn app_id_from_cgroup_path(cgroup_path: &str) -> Option<String> {
+        let unit = cgroup_path.rsplit('/').next()?.strip_prefix("app-")?;
+
+        // dashes within the app ID are escaped as `\x2d`, so they can't be mistaken for separators
+        let app_id = if let Some(scope) = unit.strip_suffix(".scope") {
+            let mut segments = scope.rsplit('-');
+            segments.next()?; // random part
+            segments.next()?
+        } else if let Some(service) = unit.strip_suffix(".service") {
+            service.split('@').next()?.rsplit('-').next()?
+        } else {
+            return None;
+        };
+
+        Some(unescape::unescape(app_id).unwrap_or_else(|| app_id.to_string()))
+            .filter(|app_id| !app_id.is_empty())
+    }
+
+    /// Returns the innermost systemd slice the process belongs to (e.g. `user-1000.slice`, `system.slice` or
+    /// `machine.slice`), `None` if it's not part of any slice
+    pub fn systemd_slice(&self) -> Option<String> {
+        self.cgroup_path
+            .as_deref()?
+            .split('/')
+            .rfind(|component| component.ends_with(".slice"))
+            .map(str::to_string)
+    }
+
+    /// Returns the ID of the logind session the process belongs to (e.g. "2" for `session-2.scope`), `None` for
+    /// processes outside of login sessions like system services or those started by the user's service manager
+    pub fn login_session(&self) -> Option<String> {
+        self.cgroup_path
+            .as_deref()?
+            .split('/')
+            .find_map(|component| component.strip_prefix("session-")?.strip_suffix(".scope"))
+            .filter(|session| !session.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Returns whether the process runs in any kind of sandbox or container, see `sandbox_kind()` for the details
+    pub fn is_sandboxed(&self) -> bool {
+        self.containerization != Containerization::None
+    }
+
+    /// Returns the kind of sandbox or container the process runs in
+    pub fn sandbox_kind(&self) -> Containerization {
+        self.containerization
+    }
+
+    /// Returns `container_id` shortened to 12 characters for Docker and Podman like `docker ps` and `podman ps` display
+    /// it, LXC container names are returned as they are
+    pub fn container_display_name(&self) -> Option<&str> {
+        let container_id = self.container_id.as_deref()?;
+        match self.containerization {
+            Containerization::Docker | Containerization::Podman => {
+                container_id.get(..12).or(Some(container_id))
+            }
+            _ => Some(container_id),
+        }
+    }
+
+    /// Returns whether the process is confined by its LSM policy according to `security_context`. Processes in
+    /// AppArmor's complain mode only have their violations logged and therefore count as unconfined. `None` if there
+    /// is no security context.
+    pub fn is_confined(&self) -> Option<bool> {
+        self.security_context
+            .as_deref()
+            .map(Self::security_context_confined)
+    }
+
+    fn security_context_confined(security_context: &str) -> bool {
+        if let Some((_, mode)) = security_context.rsplit_once(" (") {
+            // AppArmor: "<profile> (<mode>)"
+            mode.trim_end_matches(')') == "enforce"
+        } else if let Some(selinux_type) = security_context.split(':').nth(2) {
+            // SELinux: "<user>:<role>:<type>:<level>"
+            !selinux_type.starts_with("unconfined") && selinux_type != "kernel_t"
+        } else {
+            // AppArmor reports plain "unconfined" for processes without a profile
+            security_context != "unconfined"
+        }
+    }
+
+    /// Merges `gpu_usage_stats` and `npu_usage_stats` into a single map, tagging each entry with its kind of device
+    pub fn accelerator_stats(&self) -> BTreeMap<GpuIdentifier, AcceleratorStats> {
+        let gpus = self.gpu_usage_stats.iter().map(|(identifier, stats)| {
+            (
+                *identifier,
+                AcceleratorStats {
+                    kind: AcceleratorKind::Gpu,
+                    usage: stats.gfx,
+                    mem: stats.mem,
+                    nvidia: stats.nvidia,
+                },
+            )
+        });
+
+        let npus = self.npu_usage_stats.iter().map(|(pci_slot, stats)| {
+            (
+                GpuIdentifier::PciSlot(*pci_slot),
+                AcceleratorStats {
+                    kind: AcceleratorKind::Npu,
+                    usage: stats.usage,
+                    mem: stats.mem,
+                    nvidia: false,
+                },
+            )
+        });
+
+        gpus.chain(npus).collect()
+    }
+
+    /// Returns how old the usage stats of the given GPU were when the process was scanned, so that consumers can
+    /// mark stale values. AMD and Intel stats are read at scan time and thus always fresh, NVIDIA stats are based on
+    /// the newest NVML sample within the sampling window. `None` if there are no stats for this GPU or no NVML sample
+    /// of the process, in which case the NVIDIA usage values are just placeholders.
+    pub fn gpu_usage_age(&self, identifier: &GpuIdentifier) -> Option<Duration> {
+        let stats = self.gpu_usage_stats.get(identifier)?;
+        if !stats.nvidia {
+            return Some(Duration::ZERO);
+        }
+
+        let sampled_at = self.gpu_usage_sampled_at.get(identifier)?;
+        Some(Duration::from_millis(
+            self.timestamp.saturating_sub(*sampled_at),
+        ))
+    }
+
+    /// Number of GPUs whose engines the process uses, see `GpuUsageStats::is_active()`
+    pub fn active_gpu_count(&self) -> usize {
+        self.gpu_usage_stats
+            .values()
+            .filter(|stats| stats.is_active())
+            .count()
+    }
+
+    /// Number of NPUs the process has spent time on
+    pub fn active_npu_count(&self) -> usize {
+        self.npu_usage_stats
+            .values()
+            .filter(|stats| stats.usage > 0)
+            .count()
+    }
+
+    /// Reports whether the process' CPU affinity is restricted and whether the scheduler honors it, so that users can
+    /// verify that pinning a process (e.g. using `taskset`) works
+    pub fn affinity_report(&self) -> AffinityReport {
+        let allowed_cpus = self.affinity.iter().filter(|allowed| **allowed).count();
+
+        AffinityReport {
+            allowed_cpus,
+            total_cpus: self.affinity.len(),
+            restricted: allowed_cpus < self.affinity.len(),
+            last_cpu_allowed: self
+                .last_cpu
+                .and_then(|last_cpu| self.affinity.get(last_cpu).copied()),
+        }
+    }
+
+    /// Whether the process last ran on a CPU outside of its affinity mask. Right after the affinity of a running
+    /// process has been changed, this can be `true` for a moment until the process is migrated.
+    pub fn affinity_violation(&self) -> bool {
+        self.affinity_report().last_cpu_allowed == Some(false)
+    }
+
+    /// Whether the process belongs to the foreground process group of its controlling terminal, i.e. it's the job that
+    /// currently receives the terminal's input. `None` if the process has no controlling terminal.
+    pub fn is_foreground(&self) -> Option<bool> {
+        (self.tpgid > 0).then_some(self.pgrp == self.tpgid)
+    }
+
+    /// Calculates how much of each GPU's engines the process used since the previous scan `previous` of the same
+    /// process.
+    ///
+    /// For AMD and Intel GPUs, the difference of the engine time counters is divided by the elapsed time between both
+    /// scans (or by the elapsed GPU cycles for xe), NVIDIA values are already percentages and are passed through.
+    /// Returns an empty map if `previous` belongs to another process (including a previous process with the same,
+    /// since reused, PID) or if no time has passed.
+    pub fn gpu_usage_percent_since(
+        &self,
+        previous: &Self,
+    ) -> BTreeMap<GpuIdentifier, GpuUsagePercent> {
+        if self.pid != previous.pid || self.starttime != previous.starttime {
+            return BTreeMap::new();
+        }
+
+        let time_delta_ns = self
+            .timestamp
+            .saturating_sub(previous.timestamp)
+            .saturating_mul(1_000_000);
+
+        self.gpu_usage_stats
+            .iter()
+            .filter_map(|(identifier, stats)| {
+                let percent = if stats.nvidia {
+                    GpuUsagePercent {
+                        gfx: stats.gfx as f64,
+                        enc: stats.enc as f64,
+                        dec: stats.dec as f64,
+                    }
+                } else {
+                    let previous_stats = previous.gpu_usage_stats.get(identifier)?;
+
+                    if let (Some(gfx), Some(enc), Some(dec)) = (
+                        stats.cycles_fraction_since(previous_stats, GpuEngine::Graphics),
+                        stats.cycles_fraction_since(previous_stats, GpuEngine::Encode),
+                        stats.cycles_fraction_since(previous_stats, GpuEngine::Decode),
+                    ) {
+                        return Some((
+                            *identifier,
+                            GpuUsagePercent {
+                                gfx: gfx * 100.0,
+                                enc: enc * 100.0,
+                                dec: dec * 100.0,
+                            },
+                        ));
+                    }
+
+                    if time_delta_ns == 0 {
+                        return None;
+                    }
+
+                    let percent = |current: u64, previous: u64| {
+                        (current.saturating_sub(previous) as f64 / time_delta_ns as f64 * 100.0)
+                            .clamp(0.0, 100.0)
+                    };
+
+                    GpuUsagePercent {
+                        gfx: percent(stats.gfx, previous_stats.gfx),
+                        enc: percent(stats.enc, previous_stats.enc),
+                        dec: percent(stats.dec, previous_stats.dec),
+                    }
+                };
+
+                Some((*identifier, percent))
+            })
+            .collect()
+    }
+
+    /// Computes the usage of the engines of every GPU by all processes between two full scans in one pass, i.e. the
+    /// sum of `gpu_usage_percent_since()` of every process, capped at 100 %.
+    ///
+    /// Processes are matched by PID and start time, so a process that exited and a new one that reused its PID aren't
+    /// mixed up. AMD and Intel processes that aren't part of both scans are ignored since their usage in the interval
+    /// can't be determined, NVIDIA processes only need to be part of `current`.
+    pub fn gpu_usage_diff(
+        previous: &[ProcessData],
+        current: &[ProcessData],
+    ) -> BTreeMap<GpuIdentifier, GpuUsagePercent> {
+        let previous: HashMap<(libc::pid_t, u64), &ProcessData> = previous
+            .iter()
+            .map(|process| ((process.pid, process.starttime), process))
+            .collect();
+
+        let mut usages: BTreeMap<GpuIdentifier, GpuUsagePercent> = BTreeMap::new();
+
+        for process in current {
+            // comparing a process to itself yields nothing for counters but still passes through NVIDIA percentages
+            let previous_process = previous
+                .get(&(process.pid, process.starttime))
+                .copied()
+                .unwrap_or(process);
+
+            for (identifier, percent) in process.gpu_usage_percent_since(previous_process) {
+                let usage = usages.entry(identifier).or_default();
+                usage.gfx += percent.gfx;
+                usage.enc += percent.enc;
+                usage.dec += percent.dec;
+            }
+        }
+
+        usages
+            .into_iter()
+            .map(|(identifier, usage)| {
+                (
+                    identifier,
+                    GpuUsagePercent {
+                        gfx: usage.gfx.clamp(0.0, 100.0),
+                        enc: usage.enc.clamp(0.0, 100.0),
+                        dec: usage.dec.clamp(0.0, 100.0),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Derives the usage (as a fraction) of every GPU by summing up the graphics engine time its clients spent between
+    /// two scans instead of relying on the usage reported by the hardware, see `gpu_usage_diff()`.
+    ///
+    /// Like there, AMD and Intel processes are only counted if they're part of both scans, while NVIDIA processes
+    /// also count if they're only part of `current` because their NVML utilization already covers the interval.
+    pub fn client_aggregate_usage(
+        previous: &[ProcessData],
+        current: &[ProcessData],
+    ) -> BTreeMap<GpuIdentifier, f64> {
+        Self::gpu_usage_diff(previous, current)
+            .into_iter()
+            .map(|(identifier, usage)| (identifier, usage.gfx / 100.0))
+            .collect()
+    }
+
+    /// Returns the `n` processes with the highest value of `key` (e.g. memory usage or CPU time) in descending order
+    /// without sorting all of `processes`
+    pub fn top_processes_by<F: Fn(&ProcessData) -> u64>(
+        processes: &[ProcessData],
+        n: usize,
+        key: F,
+    ) -> Vec<&ProcessData> {
+        let mut top: Vec<&ProcessData> = processes.iter().collect();
+
+        if n == 0 {
+            return Vec::new();
+        } else if n < top.len() {
+            top.select_nth_unstable_by_key(n - 1, |process| std::cmp::Reverse(key(process)));
+            top.truncate(n);
+        }
+
+        top.sort_unstable_by_key(|process| std::cmp::Reverse(key(process)));
+        top
+    }
+
+    /// Returns the process with the PID `root_pid` and all of its (direct and indirect) children found in `processes`
+    pub fn subtree(root_pid: libc::pid_t, processes: &[ProcessData]) -> Vec<&ProcessData> {
+        let mut children: HashMap<libc::pid_t, Vec<&ProcessData>> = HashMap::new();
+        for process in processes {
+            // the kernel's idle task (PID 0) is the parent of itself in some cases
+            if process.pid != process.parent_pid {
+                children
+                    .entry(process.parent_pid)
+                    .or_default()
+                    .push(process);
+            }
+        }
+
+        let mut subtree: Vec<&ProcessData> = processes
+            .iter()
+            .filter(|process| process.pid == root_pid)
+            .collect();
+
+        let mut visited = HashSet::from([root_pid]);
+        let mut queue = VecDeque::from([root_pid]);
+        while let Some(pid) = queue.pop_front() {
+            for child in children.get(&pid).into_iter().flatten() {
+                if visited.insert(child.pid) {
+                    subtree.push(child);
+                    queue.push_back(child.pid);
+                }
+            }
+        }
+
+        subtree
+    }
+
+    /// Sums up the video memory in bytes used on every GPU by the process with the PID `root_pid` and all of its
+    /// children (e.g. the GPU processes of Chromium or the games spawned by a launcher).
+    ///
+    /// Since NVML may attribute the same allocation to several processes, the sum for NVIDIA GPUs is capped at the
+    /// GPU's total video memory.
+    pub fn subtree_gpu_memory(
+        root_pid: libc::pid_t,
+        processes: &[ProcessData],
+    ) -> BTreeMap<GpuIdentifier, u64> {
+        let mut memory: BTreeMap<GpuIdentifier, u64> = BTreeMap::new();
+
+        for process in Self::subtree(root_pid, processes) {
+            for (identifier, stats) in &process.gpu_usage_stats {
+                let sum = memory.entry(*identifier).or_default();
+                *sum = sum.saturating_add(stats.mem);
+
+                if let (true, GpuIdentifier::PciSlot(pci_slot)) = (stats.nvidia, identifier) {
+                    if let Some(total_vram) = Self::nvidia_total_vram(pci_slot) {
+                        *sum = (*sum).min(total_vram);
+                    }
+                }
+            }
+        }
+
+        memory
+    }
+
+    /// Returns the PIDs of all descendants of the process with the given PID among `processes` (following their
+    /// `parent_pid`), parents coming before their children
+    pub fn descendant_pids<'a, I>(pid: libc::pid_t, processes: I) -> Vec<libc::pid_t>
+    where
+        I: IntoIterator<Item = &'a ProcessData>,
+    {
+        let mut children: HashMap<libc::pid_t, Vec<libc::pid_t>> = HashMap::new();
+        for process in processes {
+            children
+                .entry(process.parent_pid)
+                .or_default()
+                .push(process.pid);
+        }
+
+        let mut descendants = Vec::new();
+        let mut queue = VecDeque::from([pid]);
+        while let Some(parent) = queue.pop_front() {
+            for child in children.remove(&parent).unwrap_or_default() {
+                // a process can't be its own ancestor, but don't get stuck in case of inconsistent data
+                if child != pid {
+                    descendants.push(child);
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        descendants
+    }
+
+    /// Returns the PIDs of all processes without reading any of their files, which is far cheaper than
+    /// `all_process_data()` if only the set of running processes is needed
+    pub fn all_pids() -> Result<Vec<libc::pid_t>> {
+        Ok(glob("/proc/[0-9]*/")
+            .context("unable to glob")?
+            .flatten()
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .and_then(|file_name| file_name.parse().ok())
+            })
+            .collect())
+    }
+
+    pub fn all_process_data() -> Result<Vec<Self>> {
+        Self::all_process_data_with_options(&ScanOptions::default())
+    }
+
+    pub fn all_process_data_with_options(options: &ScanOptions) -> Result<Vec<Self>> {
+        Self::all_process_data_filtered(options, |_| true)
+    }
+
+    /// Like [`ProcessData::all_process_data`], but skips the processes with the given PIDs
+    pub fn all_process_data_excluding(excluded: &[libc::pid_t]) -> Result<Vec<Self>> {
+        Self::all_process_data_filtered(&ScanOptions::default(), |pid| !excluded.contains(&pid))
+    }
+
+    /// Scans only the processes whose PID `filter` returns `true` for. The filter is applied before any of the
+    /// process' files are read, so excluded processes don't cost anything.
+    pub fn all_process_data_filtered<F: FnMut(libc::pid_t) -> bool>(
+        options: &ScanOptions,
+        mut filter: F,
+    ) -> Result<Vec<Self>> {
+        let entries = glob("/proc/[0-9]*/")
+            .context("unable to glob")?
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .and_then(|file_name| file_name.parse().ok())
+                    .is_some_and(&mut filter)
+            });
+
+        Ok(Self::read_entries(entries.collect(), options))
+    }
+
+    /// Reads the processes at the given paths in `/proc`, skipping the ones that vanished or couldn't be read
+    pub(crate) fn read_entries(entries: Vec<PathBuf>, options: &ScanOptions) -> Vec<Self> {
+        Self::read_entries_with_cache(entries, HashMap::new(), options)
+    }
+
+    /// Like `read_entries()`, but the processes in `cache` (i.e. their data of the previous scan) are only refreshed
+    /// through `refresh_from_path()` instead of being read from scratch
+    pub(crate) fn read_entries_with_cache(
+        entries: Vec<PathBuf>,
+        cache: HashMap<libc::pid_t, Self>,
+        options: &ScanOptions,
+    ) -> Vec<Self> {
+        Self::refresh_nvidia_stats();
+
+        if options.network_io {
+            if let Err(error) = net_io::refresh_socket_traffic() {
+                debug!("Unable to query socket traffic: {error:#}");
+            }
+        }
+
+        let mut process_data = if let Some(deadline) = options.deadline {
+            Self::all_process_data_with_deadline(entries, cache, *options, deadline)
+        } else {
+            let read = |entry: &PathBuf| {
+                Self::read_entry(entry, &cache, options)
+                    .inspect_err(Self::log_skipped)
+                    .ok()
+            };
+
+            // every process involves a handful of small reads (plus one per file descriptor for the fdinfo-based GPU
+            // and NPU statistics), which adds up on systems with thousands of processes
+            if let Some(pool) = SCAN_POOL.as_ref() {
+                pool.install(|| entries.par_iter().filter_map(read).collect())
+            } else {
+                entries.iter().filter_map(read).collect()
+            }
+        };
+
+        if options.frozen {
+            Self::read_frozen(&mut process_data);
+        }
+
+        process_data
+    }
+
+    /// Sets `frozen` of all processes, looking up every cgroup only once since most processes share their cgroup with
+    /// others
+    fn read_frozen(process_data: &mut [Self]) {
+        let mut frozen_cgroups: HashMap<String, bool> = HashMap::new();
+
+        for data in process_data {
+            data.frozen = data.cgroup_path.as_ref().is_some_and(|cgroup_path| {
+                *frozen_cgroups
+                    .entry(cgroup_path.clone())
+                    .or_insert_with(|| Self::is_cgroup_frozen(cgroup_path))
+            });
+        }
+    }
+
+    fn all_process_data_with_deadline(
+        entries: Vec<PathBuf>,
+        cache: HashMap<libc::pid_t, Self>,
+        options: ScanOptions,
+        deadline: Duration,
+    ) -> Vec<Self> {
+        Self::read_entries_with_deadline(entries, deadline, move |entry| {
+            Self::read_entry(entry, &cache, &options)
+        })
+    }
+
+    /// Reads the process at `entry`, only refreshing its data if it's in `cache`
+    fn read_entry(
+        entry: &Path,
+        cache: &HashMap<libc::pid_t, Self>,
+        options: &ScanOptions,
+    ) -> Result<Self> {
+        let cached = entry
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.parse().ok())
+            .and_then(|pid: libc::pid_t| cache.get(&pid));
+
+        match cached {
+            Some(cached) => cached.refresh_from_path(entry, options),
+            None => Self::try_from_path_with_options(entry, options),
+        }
+    }
+
+    /// Reads `entries` with `read` on worker threads, skipping (exactly once) every entry that takes longer than
+    /// `deadline`. The worker stuck on such an entry is abandoned and a new one continues with the remaining entries,
+    /// unless `MAX_STUCK_SCAN_WORKERS` are stuck already, in which case the remaining entries are skipped as well.
+    fn read_entries_with_deadline<T, F>(
+        entries: Vec<PathBuf>,
+        deadline: Duration,
+        read: F,
+    ) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(&PathBuf) -> Result<T> + Send + Sync + 'static,
+    {
+        let mut results = Vec::with_capacity(entries.len());
+        let queue = Arc::new(Mutex::new(VecDeque::from(entries)));
+        let read = Arc::new(read);
+
+        loop {
+            if queue.lock().unwrap().is_empty() {
+                return results;
+            }
+
+            if LIVE_SCAN_WORKERS.load(Ordering::Acquire) >= MAX_STUCK_SCAN_WORKERS {
+                let skipped = std::mem::take(&mut *queue.lock().unwrap());
+                debug!(
+                    "Skipping {} processes because {MAX_STUCK_SCAN_WORKERS} scanning threads are stuck",
+                    skipped.len()
+                );
+                return results;
+            }
+
+            let (sender, receiver) = mpsc::channel();
+            // the entry the worker is currently reading, so that it can be logged if the worker gets stuck on it
+            let current = Arc::new(Mutex::new(None));
+
+            let worker_queue = Arc::clone(&queue);
+            let worker_current = Arc::clone(&current);
+            let worker_read = Arc::clone(&read);
+            LIVE_SCAN_WORKERS.fetch_add(1, Ordering::AcqRel);
+            std::thread::spawn(move || {
+                loop {
+                    let Some(entry) = worker_queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    *worker_current.lock().unwrap() = Some(entry.clone());
+
+                    // the receiving end has given up on this entry because it took too long, it's skipped and
+                    // another worker continues with the remaining ones
+                    if sender.send(worker_read(&entry)).is_err() {
+                        break;
+                    }
+                }
+
+                LIVE_SCAN_WORKERS.fetch_sub(1, Ordering::AcqRel);
+            });
+
+            loop {
+                match receiver.recv_timeout(deadline) {
+                    Ok(Ok(data)) => results.push(data),
+                    Ok(Err(error)) => Self::log_skipped(&error),
+                    // skip the entry the worker is stuck on and continue with a fresh worker
+                    Err(RecvTimeoutError::Timeout) => {
+                        let entry = current.lock().unwrap().take();
+                        debug!(
+                            "Skipping {} because it took longer than {deadline:?} to be read",
+                            entry.map_or_else(
+                                || "a process".to_string(),
+                                |entry| entry.to_string_lossy().to_string()
+                            )
+                        );
+                        break;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return results,
+                }
+            }
+        }
+    }
+
+    /// Turns the error of reading a file of the process with the given PID into a `ProcessVanished` if the file
+    /// doesn't exist (anymore)
+    fn vanished_error(error: anyhow::Error, pid: libc::pid_t) -> anyhow::Error {
+        if error
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|error| error.kind() == ErrorKind::NotFound)
+        {
+            ProcessVanished(pid).into()
+        } else {
+            error
+        }
+    }
+
+    fn log_skipped(error: &anyhow::Error) {
+        if error.is::<ProcessVanished>() {
+            trace!("{error}");
+        } else {
+            debug!("Skipping process: {error:#}");
+        }
+    }
+
+    /// Like [`ProcessData::all_process_data`], but stamps every process with the same timestamp
+    /// (which is also returned) so that deltas computed within one scan are consistent.
+    pub fn all_process_data_snapshot() -> Result<(u64, Vec<Self>)> {
+        Self::all_process_data_snapshot_with_options(&ScanOptions::default())
+    }
+
+    /// Like [`ProcessData::all_process_data_snapshot`], but with the given options
+    pub fn all_process_data_snapshot_with_options(
+        options: &ScanOptions,
+    ) -> Result<(u64, Vec<Self>)> {
+        let timestamp = unix_as_millis();
+
+        let mut process_data = Self::all_process_data_with_options(options)?;
+        for data in &mut process_data {
+            data.timestamp = timestamp;
+        }
+
+        Ok((timestamp, process_data))
+    }
+
+    pub fn try_from_path<P: AsRef<Path>>(proc_path: P) -> Result<Self> {
+        Self::try_from_path_with_options(proc_path, &ScanOptions::default())
+    }
+
+    pub fn try_from_path_with_options<P: AsRef<Path>>(
+        proc_path: P,
+        options: &ScanOptions,
+    ) -> Result<Self> {
+        let proc_path = proc_path.as_ref();
+
+        let pid = proc_path
+            .file_name()
+            .context("proc_path terminates in ..")?
+            .to_str()
+            .context("can't turn OsStr to str")?
+            .parse()?;
+
+        // ProcFiles::read() starts with stat, so if the process is already gone, this fails without trying to read the
+        // other files
+        let files = ProcFiles::read(proc_path).map_err(|error| Self::vanished_error(error, pid))?;
+
+        let mut process_data = Self::parse_proc_contents(pid, files, !options.lazy_usernames)?;
+
+        if process_data.containerization == Containerization::None {
+            process_data.containerization = if proc_path.join("root").join(".flatpak-info").exists()
+            {
+                Containerization::Flatpak
+            } else if Self::is_appimage(proc_path) {
+                Containerization::AppImage
+            } else {
+                Containerization::None
+            };
+        }
+
+        process_data.security_context =
+            std::fs::read_to_string(proc_path.join("attr").join("current"))
+                .ok()
+                .map(|context| context.trim_end_matches(['\0', '\n']).to_string())
+                .filter(|context| !context.is_empty());
+
+        process_data.read_volatile_stats(proc_path, options);
+
+        Ok(process_data)
+    }
+
+    /// Rereads a process that has been read before, e.g. by `try_from_path_with_options()`. Its files in procfs are
+    /// read again (the name, command line and cgroup can change at any time, e.g. through `prctl(PR_SET_NAME)`,
+    /// `setproctitle()` or a launcher moving it into a systemd scope), but what can only change when the process
+    /// execs is kept: the Flatpak and AppImage detection and the security context. Keeping track of execs is up to
+    /// the caller (see `proc_events`). The usernames are kept as well unless the UIDs changed.
+    ///
+    /// Falls back to reading the process from scratch if its PID has been reused in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with a `ProcessVanished` if the process doesn't exist anymore
+    pub(crate) fn refresh_from_path<P: AsRef<Path>>(
+        &self,
+        proc_path: P,
+        options: &ScanOptions,
+    ) -> Result<Self> {
+        let proc_path = proc_path.as_ref();
+
+        let files =
+            ProcFiles::read(proc_path).map_err(|error| Self::vanished_error(error, self.pid))?;
+
+        let mut process_data = Self::parse_proc_contents(self.pid, files, false)?;
+
+        if process_data.starttime != self.starttime {
+            return Self::try_from_path_with_options(proc_path, options);
+        }
+
+        if process_data.uid == self.uid && process_data.effective_uid == self.effective_uid {
+            process_data.user.clone_from(&self.user);
+            process_data.effective_user.clone_from(&self.effective_user);
+        } else if !options.lazy_usernames {
+            process_data.user = Self::resolve_username(process_data.uid);
+            process_data.effective_user = Self::resolve_username(process_data.effective_uid);
+        }
+
+        // containers are detected from the cgroup, which has just been read again
+        if process_data.containerization == Containerization::None
+            && matches!(
+                self.containerization,
+                Containerization::Flatpak | Containerization::AppImage
+            )
+        {
+            process_data.containerization = self.containerization;
+        }
+
+        process_data
+            .security_context
+            .clone_from(&self.security_context);
+
+        process_data.read_volatile_stats(proc_path, options);
+
+        Ok(process_data)
+    }
+
+    /// Reads the optional data of the process that changes while it's running, see `ScanOptions`
+    fn read_volatile_stats(&mut self, proc_path: &Path, options: &ScanOptions) {
+        let (gpu_usage_stats, npu_usage_stats) = Self::accelerator_usage_stats(proc_path, self.pid);
+        self.gpu_usage_stats = gpu_usage_stats;
+        self.npu_usage_stats = npu_usage_stats;
+        self.gpu_usage_sampled_at = Self::nvidia_sample_timestamps(self.pid);
+
+        if options.fd_stats {
+            self.open_fds = std::fs::read_dir(proc_path.join("fd"))
+                .ok()
+                .map(|entries| entries.count() as u64);
+            self.fd_soft_limit = std::fs::read_to_string(proc_path.join("limits"))
+                .ok()
+                .and_then(|limits| Self::fd_soft_limit(&limits));
+        }
+
+        if options.schedstat {
+            if let Some((_, cpu_wait_time, timeslices)) =
+                std::fs::read_to_string(proc_path.join("schedstat"))
+                    .ok()
+                    .and_then(|schedstat| Self::parse_schedstat(&schedstat))
+            {
+                self.cpu_wait_time = Some(cpu_wait_time);
+                self.timeslices = Some(timeslices);
+            }
+        }
+
+        if options.smaps_swap {
+            self.smaps_swap_usage = std::fs::read_to_string(proc_path.join("smaps_rollup"))
+                .ok()
+                .and_then(|smaps| Self::smaps_swap_usage(&smaps));
+        }
+
+        if options.io_priority {
+            self.io_priority = Self::io_priority(self.pid);
+        }
+
+        if options.oom_score {
+            self.oom_score = std::fs::read_to_string(proc_path.join("oom_score"))
+                .ok()
+                .and_then(|oom_score| oom_score.trim().parse().ok());
+            self.oom_score_adj = std::fs::read_to_string(proc_path.join("oom_score_adj"))
+                .ok()
+                .and_then(|oom_score_adj| oom_score_adj.trim().parse().ok());
+        }
+
+        if options.network_io {
+            if let Some(traffic) = net_io::process_traffic(proc_path) {
+                self.net_sent_bytes = Some(traffic.sent_bytes);
+                self.net_received_bytes = Some(traffic.received_bytes);
+            }
+        }
+
+        if options.cgroup_memory {
+            self.cgroup_memory_usage = self
+                .cgroup_path
+                .as_deref()
+                .and_then(Self::cgroup_memory_usage);
+        }
+
+        self.timestamp = unix_as_millis();
+    }
+
+    /// Constructs `ProcessData` from already read contents of the process' files in procfs.
+    ///
+    /// Since this doesn't touch the file system, Flatpak and AppImage detection as well as GPU and NPU usage
+    /// statistics are left out, `try_from_path()` takes care of these.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the contents of `stat` are malformed
+    pub fn from_proc_contents(pid: libc::pid_t, files: ProcFiles) -> Result<Self> {
+        Self::parse_proc_contents(pid, files, true)
+    }
+
+    fn parse_proc_contents(
+        pid: libc::pid_t,
+        files: ProcFiles,
+        resolve_usernames: bool,
+    ) -> Result<Self> {
+        let ProcFiles {
+            stat,
+            statm,
+            status,
+            comm,
+            cmdline: commandline,
+            io,
+            io_access,
+            cgroup,
+            wchan,
+        } = files;
+
+        let (uid, effective_uid) = Self::get_uids(&status)?;
+
+        let (user, effective_user) = if resolve_usernames {
+            (
+                Self::resolve_username(uid),
+                Self::resolve_username(effective_uid),
+            )
+        } else {
+            (String::new(), String::new())
+        };
+
+        let stat = stat
+            .split(')') // since we don't care about the pid or the executable name, split after the executable name to make our life easier
+            .last()
+            .context("stat doesn't have ')'")?
+            .split(' ')
+            .skip(1) // the first element would be a space, let's ignore that
+            .collect::<Vec<_>>();
+
+        let statm = statm.split(' ').collect::<Vec<_>>();
+
+        let comm = comm.replace('\n', "");
+
+        // -2 to accommodate for only collecting after the second item (which is the executable name as mentioned above)
+        let state = stat
+            .get(STAT_STATE)
+            .and_then(|x| x.chars().next())
+            .map(ProcessState::from_stat_char)
+            .context("wrong stat file format")?;
+        let parent_pid = stat
+            .get(STAT_PARENT_PID)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let pgrp = stat
+            .get(STAT_PGRP)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let tpgid = stat
+            .get(STAT_TPGID)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let user_cpu_time = stat
+            .get(STAT_USER_CPU_TIME)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let system_cpu_time = stat
+            .get(STAT_SYSTEM_CPU_TIME)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let nice = stat
+            .get(STAT_NICE)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        let starttime = stat
+            .get(STAT_STARTTIME)
+            .context("wrong stat file format")
+            .and_then(|x| x.parse().context("couldn't parse stat file content"))?;
+        // guest_time is only available since Linux 2.6.24
+        let guest_time = stat
+            .get(STAT_GUEST_TIME)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default();
+
+        let last_cpu = stat.get(STAT_PROCESSOR).and_then(|x| x.parse().ok());
+
+        // rt_priority and policy are only available since Linux 2.5.19
+        let realtime_priority = stat
+            .get(STAT_RT_PRIORITY)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default();
+        let scheduling_policy = stat
+            .get(STAT_POLICY)
+            .and_then(|x| x.parse().ok())
+            .map(SchedulingPolicy::from_raw)
+            .unwrap_or_default();
+
+        let minor_faults = stat
+            .get(STAT_MINOR_FAULTS)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default();
+        let major_faults = stat
+            .get(STAT_MAJOR_FAULTS)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default();
+
+        let voluntary_ctxt_switches = RE_VOLUNTARY_CTXT_SWITCHES
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse().ok());
+        let nonvoluntary_ctxt_switches = RE_NONVOLUNTARY_CTXT_SWITCHES
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse().ok());
+
+        let mut affinity = Vec::with_capacity(*NUM_CPUS);
+        RE_AFFINITY
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .map(|capture| capture.as_str())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| c.to_digit(16).unwrap_or_default())
+            .rev()
+            .for_each(|int| {
+                // we want the bits and there are 4 bits in a hex digit
+                (0..4).for_each(|i| {
+                    // this if should prevent wrong size affinity vecs if the thread count is not divisible by 4
+                    if affinity.len() < *NUM_CPUS {
+                        affinity.push((int & (1 << i)) != 0);
+                    }
+                });
+            });
+
+        let swap_usage = RE_SWAP_USAGGE
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .map(|capture| capture.as_str())
+            .unwrap_or_default()
+            .parse::<usize>()
+            .unwrap_or_default() // kworkers don't have swap usage
+            .saturating_mul(1000);
+
+        let peak_memory_usage = RE_PEAK_MEMORY_USAGE
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<usize>().ok())
+            .map(|kib| kib.saturating_mul(1024));
+
+        let peak_resident = RE_PEAK_RESIDENT
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<usize>().ok())
+            .map(|kib| kib.saturating_mul(1024));
+
+        let shared_memory = RE_RSS_SHMEM
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<usize>().ok())
+            .map(|kib| kib.saturating_mul(1024));
+
+        let hugepages_usage = RE_HUGETLB_PAGES
+            .captures(&status)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<usize>().ok())
+            .map(|kib| kib.saturating_mul(1024));
+
+        // a malformed statm shouldn't make us lose the whole process, so treat it like a process without memory usage
+        let resident = statm
+            .get(1)
+            .and_then(|x| x.trim().parse::<usize>().ok())
+            .unwrap_or_default();
+        let shared = statm
+            .get(2)
+            .and_then(|x| x.trim().parse::<usize>().ok())
+            .unwrap_or_default();
+        let memory_usage = resident.saturating_sub(shared).saturating_mul(*PAGESIZE);
+
+        let cgroup_path = cgroup
+            .as_deref()
+            .and_then(Self::cgroup_v2_path)
+            .map(str::to_string);
+        let cgroup = cgroup.and_then(Self::sanitize_cgroup);
+
+        let wchan = wchan
+            .map(|wchan| wchan.trim().to_string())
+            .filter(|wchan| !wchan.is_empty() && wchan != "0");
+
+        let (containerization, container_id) = if commandline.starts_with("/snap/") {
+            (Containerization::Snap, None)
+        } else if let Some((containerization, container_id)) = cgroup_path
+            .as_deref()
+            .and_then(Self::container_from_cgroup_path)
+        {
+            (containerization, Some(container_id))
+        } else {
+            (Containerization::None, None)
+        };
+
+        let read_bytes = io.as_ref().and_then(|io| {
+            RE_IO_READ
+                .captures(io)
+                .and_then(|captures| captures.get(1))
+                .and_then(|capture| capture.as_str().parse::<u64>().ok())
+        });
+
+        let write_bytes = io.as_ref().and_then(|io| {
+            RE_IO_WRITE
+                .captures(io)
+                .and_then(|captures| captures.get(1))
+                .and_then(|capture| capture.as_str().parse::<u64>().ok())
+        });
+
+        let timestamp = unix_as_millis();
+
+        Ok(Self {
+            pid,
+            parent_pid,
+            pgrp,
+            tpgid,
+            uid,
+            user,
+            effective_user,
+            effective_uid,
+            comm,
+            commandline,
+            state,
+            user_cpu_time,
+            system_cpu_time,
+            guest_time,
+            minor_faults,
+            major_faults,
+            voluntary_ctxt_switches,
+            nonvoluntary_ctxt_switches,
+            niceness: nice,
+            scheduling_policy,
+            realtime_priority,
+            io_priority: None,
+            affinity,
+            last_cpu,
+            memory_usage,
+            swap_usage,
+            smaps_swap_usage: None,
+            peak_memory_usage,
+            peak_resident,
+            shared_memory,
+            hugepages_usage,
+            starttime,
+            cgroup,
+            cgroup_path,
+            cgroup_memory_usage: None,
+            frozen: false,
+            wchan,
+            containerization,
+            container_id,
+            read_bytes,
+            write_bytes,
+            io_access,
+            net_sent_bytes: None,
+            net_received_bytes: None,
+            cpu_wait_time: None,
+            timeslices: None,
+            open_fds: None,
+            fd_soft_limit: None,
+            oom_score: None,
+            oom_score_adj: None,
+            security_context: None,
+            timestamp,
+            gpu_usage_stats: BTreeMap::new(),
+            gpu_usage_sampled_at: BTreeMap::new(),
+            npu_usage_stats: BTreeMap::new(),
+        })
+    }
+
+    fn accelerator_usage_stats(
+        proc_path: &Path,
+        pid: i32,
+    ) -> (
+        BTreeMap<GpuIdentifier, GpuUsageStats>,
+        BTreeMap<PciSlot, NpuUsageStats>,
+    ) {
+        let (mut gpu_stats, npu_stats) =
+            Self::fdinfo_usage_stats(proc_path, pid).unwrap_or_default();
+
+        // NVML is only a fallback for NVIDIA GPUs whose driver doesn't export usage stats through fdinfo
+        for (gpu_identifier, stats) in Self::nvidia_gpu_stats_all(pid) {
+            gpu_stats.entry(gpu_identifier).or_insert(stats);
+        }
+
+        (gpu_stats, npu_stats)
+    }
+
+    fn fdinfo_usage_stats(
+        proc_path: &Path,
+        pid: i32,
+    ) -> Result<(
+        BTreeMap<GpuIdentifier, GpuUsageStats>,
+        BTreeMap<PciSlot, NpuUsageStats>,
+    )> {
+        let fdinfo_dir = proc_path.join("fdinfo");
+
+        let mut seen_fds = HashSet::new();
+
+        let mut gpu_map = BTreeMap::new();
+        let mut npu_map = BTreeMap::new();
+        for entry in std::fs::read_dir(fdinfo_dir)? {
+            let entry = entry?;
+            let fdinfo_path = entry.path();
+
+            let _file = std::fs::File::open(&fdinfo_path);
+            if _file.is_err() {
+                continue;
+            }
+            let mut file = _file.unwrap();
+
+            let _metadata = file.metadata();
+            if _metadata.is_err() {
+                continue;
+            }
+            let metadata = _metadata.unwrap();
+
+            // if our fd is 0, 1 or 2 it's probably just a std stream so skip it
+            let fd_num = fdinfo_path
+                .file_name()
+                .and_then(|osstr| osstr.to_str())
+                .unwrap_or("0")
+                .parse::<usize>()
+                .unwrap_or(0);
+            if fd_num <= 2 {
+                continue;
+            }
+
+            if !metadata.is_file() {
+                continue;
+            }
+
+            // Adapted from nvtop's `is_drm_fd()`
+            // https://github.com/Syllo/nvtop/blob/master/src/extract_processinfo_fdinfo.c
+            let mut drm_minor = None;
+            let fd_path = fdinfo_path.to_str().map(|s| s.replace("fdinfo", "fd"));
+            if let Some(fd_path) = fd_path {
+                if let Ok(fd_metadata) = std::fs::metadata(fd_path) {
+                    let major = libc::major(fd_metadata.st_rdev());
+                    if !Self::drm_fdinfo_plausible(fd_metadata.st_mode(), major, *ACCEL_MAJOR) {
+                        continue;
+                    }
+                    if major == DRM_MAJOR {
+                        drm_minor = Some(libc::minor(fd_metadata.st_rdev()));
+                    }
+                }
+            }
+
+            // Adapted from nvtop's `processinfo_sweep_fdinfos()`
+            // https://github.com/Syllo/nvtop/blob/master/src/extract_processinfo_fdinfo.c
+            // if we've already seen the file this fd refers to, skip
+            let not_unique = seen_fds.iter().any(|seen_fd| unsafe {
+                syscalls::syscall!(syscalls::Sysno::kcmp, pid, pid, 0, fd_num, *seen_fd)
+                    .unwrap_or(0)
+                    == 0
+            });
+            if not_unique {
+                continue;
+            }
+
+            seen_fds.insert(fd_num);
+
+            match Self::read_fdinfo(&mut file, metadata.len() as usize) {
+                Ok(FdinfoStats::Gpu(gpu_identifier, stats)) => {
+                    // older kernels don't report drm-pdev, in that case fall back to the PCI slot of the DRM node
+                    // so that e.g. the individual GCDs of multi-die cards aren't merged into one entry
+                    let gpu_identifier = if gpu_identifier == GpuIdentifier::default() {
+                        drm_minor
+                            .and_then(Self::pci_slot_for_drm_minor)
+                            .map(GpuIdentifier::PciSlot)
+                            .unwrap_or(gpu_identifier)
+                    } else {
+                        gpu_identifier
+                    };
+
+                    gpu_map
+                        .entry(gpu_identifier)
+                        .and_modify(|existing_value: &mut GpuUsageStats| {
+                            if stats.gfx > existing_value.gfx {
+                                existing_value.gfx = stats.gfx;
+                            }
+                            if stats.dec > existing_value.dec {
+                                existing_value.dec = stats.dec;
+                            }
+                            if stats.enc > existing_value.enc {
+                                existing_value.enc = stats.enc;
+                            }
+                            if stats.mem > existing_value.mem {
+                                existing_value.mem = stats.mem;
+                            }
+                            if stats.total_cycles > existing_value.total_cycles {
+                                existing_value.total_cycles = stats.total_cycles;
+                            }
+                        })
+                        .or_insert(stats);
+                }
+                Ok(FdinfoStats::Npu(pci_slot, stats)) => {
+                    npu_map
+                        .entry(pci_slot)
+                        .and_modify(|existing_value: &mut NpuUsageStats| {
+                            if stats.usage > existing_value.usage {
+                                existing_value.usage = stats.usage;
+                            }
+                            if stats.mem > existing_value.mem {
+                                existing_value.mem = stats.mem;
+                                existing_value.device_mem = stats.device_mem;
+                                existing_value.shared_mem = stats.shared_mem;
+                            }
+                        })
+                        .or_insert(stats);
+                }
+                Err(error) => trace!(
+                    "Ignoring implausible DRM fdinfo {}: {error:#}",
+                    fdinfo_path.display()
+                ),
+            }
+        }
+
+        Ok((gpu_map, npu_map))
+    }
+
+    /// Whether a file descriptor with the given mode and device major number refers to a DRM or `accel` device, whose
+    /// fdinfo may contain usage statistics
+    fn drm_fdinfo_plausible(st_mode: u32, major: u32, accel_major: Option<u32>) -> bool {
+        (st_mode & libc::S_IFMT) == libc::S_IFCHR
+            && (major == DRM_MAJOR || Some(major) == accel_major)
+    }
+
+    fn pci_slot_for_drm_minor(minor: u32) -> Option<PciSlot> {
+        std::fs::read_link(format!("/sys/dev/char/226:{minor}/device"))
+            .ok()?
+            .file_name()?
+            .to_str()?
+            .parse()
+            .ok()
+    }
+
+    /// Reads and parses an fdinfo file. Some drivers regenerate the contents on every read, which can race with the
+    /// client and occasionally yield a partial read, so a truncated read is retried once before the fd is skipped.
+    /// Other errors (e.g. fds of display-only nodes without `drm-driver`) aren't retried.
+    fn read_fdinfo(fdinfo_file: &mut File, file_size: usize) -> Result<FdinfoStats> {
+        Self::read_fdinfo_once(fdinfo_file, file_size).or_else(|error| {
+            if !error.is::<FdinfoTruncated>() {
+                return Err(error);
+            }
+
+            trace!("Retrying to read truncated fdinfo");
+            fdinfo_file.rewind()?;
+            Self::read_fdinfo_once(fdinfo_file, file_size)
+        })
+    }
+
+    fn read_fdinfo_once(fdinfo_file: &mut File, file_size: usize) -> Result<FdinfoStats> {
+        let mut content = String::with_capacity(file_size);
+        fdinfo_file.read_to_string(&mut content)?;
+        fdinfo_file.flush()?;
+
+        Self::parse_fdinfo(&content)
+    }
+
+    fn parse_fdinfo(content: &str) -> Result<FdinfoStats> {
+        // every line (including the last one) is terminated, anything else has been cut off
+        if !content.ends_with('\n') {
+            return Err(FdinfoTruncated.into());
+        }
+
+        let driver =
+            Self::fdinfo_driver(content).context("unable to find drm-driver in this fdinfo")?;
+
+        if NPU_DRIVERS.contains(&driver) {
+            Self::read_npu_fdinfo(content)
+                .map(|(pci_slot, stats)| FdinfoStats::Npu(pci_slot, stats))
+        } else {
+            Self::read_gpu_fdinfo(content)
+                .map(|(gpu_identifier, stats)| FdinfoStats::Gpu(gpu_identifier, stats))
+        }
+    }
+
+    fn fdinfo_driver(content: &str) -> Option<&str> {
+        RE_DRM_DRIVER
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .map(|capture| capture.as_str().trim())
+    }
+
+    fn read_npu_fdinfo(content: &str) -> Result<(PciSlot, NpuUsageStats)> {
+        let pci_slot = RE_DRM_PDEV
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| PciSlot::from_str(capture.as_str()).ok())
+            .context("unable to find the PCI slot of the NPU in this fdinfo")?;
+
+        let npu = RE_DRM_ENGINE_NPU
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .unwrap_or_default();
+
+        let compute = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_COMPUTE);
+
+        let total_memory = RE_DRM_TOTAL_MEMORY
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .unwrap_or_default()
+            .saturating_mul(1024);
+
+        let shared_memory = RE_DRM_SHARED_MEMORY
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .unwrap_or_default()
+            .saturating_mul(1024);
+
+        let stats = NpuUsageStats {
+            usage: npu.saturating_add(compute),
+            mem: total_memory,
+            device_mem: total_memory.saturating_sub(shared_memory),
+            shared_mem: shared_memory,
+        };
+
+        Ok((pci_slot, stats))
+    }
+
+    /// Sums up the time in ns of all engines in `content` whose key matches `regex`
+    fn fdinfo_engine_time(content: &str, regex: &Regex) -> u64 {
+        regex
+            .captures_iter(content)
+            .filter_map(|captures| captures.get(1)?.as_str().parse::<u64>().ok())
+            .fold(0, u64::saturating_add)
+    }
+
+    fn read_gpu_fdinfo(content: &str) -> Result<(GpuIdentifier, GpuUsageStats)> {
+        let gpu_identifier = RE_DRM_PDEV
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| PciSlot::from_str(capture.as_str()).ok())
+            .map(|pci_slot| GpuIdentifier::PciSlot(pci_slot))
+            .unwrap_or_default();
+
+        match Self::fdinfo_driver(content) {
+            Some(driver) if NVIDIA_DRM_DRIVERS.contains(&driver) => {
+                return Self::read_nvidia_fdinfo(content).map(|stats| (gpu_identifier, stats));
+            }
+            Some("xe") => return Ok((gpu_identifier, Self::read_xe_fdinfo(content))),
+            _ => {}
+        }
+
+        let gfx = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_GFX);
+
+        let render = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_RENDER);
+
+        let compute = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_COMPUTE);
+
+        let enc = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_ENC);
+
+        let video = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_VIDEO);
+
+        let dec = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_DEC);
+
+        // drivers like nouveau only report the standardized memory region keys, amdgpu reports both
+        let vram = RE_DRM_MEMORY_VRAM
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .map_or_else(
+                || Self::fdinfo_total_vram(content),
+                |kib| kib.saturating_mul(1024),
+            );
+
+        let gtt = RE_DRM_MEMORY_GTT
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .unwrap_or_default()
+            .saturating_mul(1024);
+
+        let total_memory = RE_DRM_TOTAL_MEMORY
+            .captures(content)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<u64>().ok())
+            .unwrap_or_default()
+            .saturating_mul(1024);
+
+        let stats = GpuUsageStats {
+            gfx: gfx.saturating_add(render).saturating_add(compute),
+            mem: vram.saturating_add(gtt).saturating_add(total_memory),
+            enc: enc.saturating_add(video),
+            dec,
+            nvidia: false,
+            total_cycles: 0,
+        };
+
+        Ok((gpu_identifier, stats))
+    }
+
+    /// Sums up all `drm-total-vram*` keys in bytes
+    fn fdinfo_total_vram(content: &str) -> u64 {
+        RE_DRM_TOTAL_VRAM
+            .captures_iter(content)
+            .filter_map(|captures| {
+                let value = captures.get(1)?.as_str().parse::<u64>().ok()?;
+                let factor = match captures.get(2).map(|unit| unit.as_str()) {
+                    Some("KiB") => 1024,
+                    Some("MiB") => 1024 * 1024,
+                    _ => 1,
+                };
+                Some(value.saturating_mul(factor))
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Parses the fdinfo of Intel's xe driver, which reports the GPU cycles every engine class has been busy
+    /// (`drm-cycles-<class>`) along with the cycles the GPU has ticked in total (`drm-total-cycles-<class>`). Classes
+    /// with multiple engines (`drm-engine-capacity-<class>`) accumulate the cycles of all of them, so they are
+    /// normalized to a single engine.
+    fn read_xe_fdinfo(content: &str) -> GpuUsageStats {
+        let capacities: HashMap<&str, u64> = RE_DRM_ENGINE_CAPACITY
+            .captures_iter(content)
+            .filter_map(|captures| {
+                let class = captures.get(1)?.as_str();
+                let capacity = captures.get(2)?.as_str().parse::<u64>().ok()?;
+                Some((class, capacity.max(1)))
+            })
+            .collect();
+
+        let cycles = |classes: &[&str]| {
+            RE_DRM_CYCLES
+                .captures_iter(content)
+                .filter_map(|captures| {
+                    let class = captures.get(1)?.as_str();
+                    if !classes.contains(&class) {
+                        return None;
+                    }
+                    let cycles = captures.get(2)?.as_str().parse::<u64>().ok()?;
+                    Some(cycles / capacities.get(class).copied().unwrap_or(1))
+                })
+                .fold(0, u64::saturating_add)
+        };
+
+        // all engine classes of a GT share the same timestamp, so they all report (roughly) the same total
+        let total_cycles = RE_DRM_TOTAL_CYCLES
+            .captures_iter(content)
+            .filter_map(|captures| captures.get(1)?.as_str().parse::<u64>().ok())
+            .max()
+            .unwrap_or_default();
+
+        let gtt = RE_DRM_TOTAL_GTT
+            .captures(content)
+            .and_then(|captures| {
+                let value = captures.get(1)?.as_str().parse::<u64>().ok()?;
+                let factor = match captures.get(2).map(|unit| unit.as_str()) {
+                    Some("KiB") => 1024,
+                    Some("MiB") => 1024 * 1024,
+                    _ => 1,
+                };
+                Some(value.saturating_mul(factor))
+            })
+            .unwrap_or_default();
+
+        GpuUsageStats {
+            gfx: cycles(&["rcs", "ccs"]),
+            mem: Self::fdinfo_total_vram(content).saturating_add(gtt),
+            // like i915, the media engines (video and video enhancement) aren't separated into encode and decode
+            enc: cycles(&["vcs", "vecs"]),
+            dec: 0,
+            nvidia: false,
+            total_cycles,
+        }
+    }
+
+    /// Parses the fdinfo of the NVIDIA kernel driver. Unlike NVML's utilization percentages, its engine times are in
+    /// ns like those of other drivers, so the stats aren't marked as `nvidia`.
+    fn read_nvidia_fdinfo(content: &str) -> Result<GpuUsageStats> {
+        // older drivers only report the client without any usage, NVML has to be used for those
+        if !RE_DRM_ENGINE_ANY.is_match(content) {
+            bail!("NVIDIA fdinfo doesn't contain any engine usage");
+        }
+
+        let gfx = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_GFX)
+            .saturating_add(Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_COMPUTE));
+
+        let enc = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_NVENC)
+            .saturating_add(Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_ENC));
+
+        let dec = Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_NVDEC)
+            .saturating_add(Self::fdinfo_engine_time(content, &RE_DRM_ENGINE_DEC));
+
+        let total_vram = Self::fdinfo_total_vram(content);
+
+        let mem = if total_vram > 0 {
+            total_vram
+        } else {
+            RE_DRM_MEMORY_VRAM
+                .captures(content)
+                .and_then(|captures| captures.get(1))
+                .and_then(|capture| capture.as_str().parse::<u64>().ok())
+                .unwrap_or_default()
+                .saturating_mul(1024)
+        };
+
+        Ok(GpuUsageStats {
+            gfx,
+            mem,
+            enc,
+            dec,
+            nvidia: false,
+            total_cycles: 0,
+        })
+    }
+
+    fn nvidia_gpu_stats_all(pid: i32) -> BTreeMap<GpuIdentifier, GpuUsageStats> {
+        let mut return_map = BTreeMap::new();
+
+        for (pci_slot, _) in NVML_DEVICES.iter() {
+            if let Ok(stats) = Self::nvidia_gpu_stats(pid, *pci_slot) {
+                return_map.insert(GpuIdentifier::PciSlot(pci_slot.to_owned()), stats);
+            }
+        }
+
+        return_map
+    }
+
+    fn nvidia_gpu_stats(pid: i32, pci_slot: PciSlot) -> Result<GpuUsageStats> {
+        let (gfx, enc, dec) = Self::nvidia_average_utilization(
+            NVIDIA_PROCESSES_STATS
+                .read()
+                .unwrap()
+                .get(&pci_slot)
+                .context("couldn't find GPU with this PCI slot")?,
+            pid,
+        );
+
+        let this_process_mem_stats = Self::nvidia_used_memory(
+            NVIDIA_PROCESS_INFOS
+                .read()
+                .unwrap()
+                .get(&pci_slot)
+                .context("couldn't find GPU with this PCI slot")?,
+            pid,
+        );
+
+        let gpu_stats = GpuUsageStats {
+            gfx,
+            mem: this_process_mem_stats,
+            enc,
+            dec,
+            nvidia: true,
+            total_cycles: 0,
+        };
+        Ok(gpu_stats)
+    }
+
+    /// Returns the time (in milliseconds since the Unix epoch) of the newest utilization sample of the process for every
+    /// NVIDIA GPU that has one
+    fn nvidia_sample_timestamps(pid: i32) -> BTreeMap<GpuIdentifier, u64> {
+        NVIDIA_PROCESSES_STATS
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(pci_slot, samples)| {
+                samples
+                    .iter()
+                    .filter(|sample| sample.pid == pid as u32)
+                    .map(|sample| sample.timestamp / 1000)
+                    .max()
+                    .map(|timestamp| (GpuIdentifier::PciSlot(*pci_slot), timestamp))
+            })
+            .collect()
+    }
+
+    /// Returns the used video memory of a process in bytes, which is also what NVML reports
+    fn nvidia_used_memory(process_infos: &[ProcessInfo], pid: i32) -> u64 {
+        process_infos
+            .iter()
+            .filter(|process| process.pid == pid as u32)
+            .map(|stats| match stats.used_gpu_memory {
+                UsedGpuMemory::Unavailable => 0,
+                UsedGpuMemory::Used(bytes) => bytes,
+            })
+            .sum()
+    }
+
+    /// NVML may return several utilization samples for the same process within the sampling window, so they're
+    /// averaged (and clamped to 100) instead of summed up to not report more than 100 % usage
+    fn nvidia_average_utilization(
+        samples: &[ProcessUtilizationSample],
+        pid: i32,
+    ) -> (u64, u64, u64) {
+        let (count, gfx, enc, dec) = samples
+            .iter()
+            .filter(|sample| sample.pid == pid as u32)
+            .fold((0_u64, 0_u64, 0_u64, 0_u64), |acc, sample| {
+                (
+                    acc.0 + 1,
+                    acc.1.saturating_add(sample.sm_util.into()),
+                    acc.2.saturating_add(sample.enc_util.into()),
+                    acc.3.saturating_add(sample.dec_util.into()),
+                )
+            });
+
+        if count == 0 {
+            return (0, 0, 0);
+        }
+
+        (
+            (gfx / count).min(100),
+            (enc / count).min(100),
+            (dec / count).min(100),
+        )
+    }
+
+    fn nvidia_process_infos() -> HashMap<PciSlot, Vec<ProcessInfo>> {
+        let mut return_map = HashMap::new();
+
+        for (pci_slot, gpu) in NVML_DEVICES.iter() {
+            return_map.insert(pci_slot.to_owned(), Self::nvidia_device_process_infos(gpu));
+        }
+
+        return_map
+    }
+
+    fn nvidia_process_stats() -> HashMap<PciSlot, Vec<ProcessUtilizationSample>> {
+        let mut return_map = HashMap::new();
+
+        for (pci_slot, gpu) in NVML_DEVICES.iter() {
+            return_map.insert(pci_slot.to_owned(), Self::nvidia_device_process_stats(gpu));
+        }
+
+        return_map
+    }
+
+    fn nvidia_device_process_infos(gpu: &Device) -> Vec<ProcessInfo> {
+        match *NVIDIA_PROCESS_QUERY.read().unwrap() {
+            NvidiaProcessQuery::Graphics => gpu.running_graphics_processes().unwrap_or_default(),
+            NvidiaProcessQuery::Compute => gpu.running_compute_processes().unwrap_or_default(),
+            NvidiaProcessQuery::Both => Self::merge_nvidia_process_infos(
+                gpu.running_graphics_processes().unwrap_or_default(),
+                gpu.running_compute_processes().unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// A process with both a graphics and a compute context shows up in both lists with the memory of its
+    /// (shared) context, so only keep one entry per process and GPU/compute instance to not count it twice
+    fn merge_nvidia_process_infos(
+        graphics: Vec<ProcessInfo>,
+        compute: Vec<ProcessInfo>,
+    ) -> Vec<ProcessInfo> {
+        let mut merged = graphics;
+
+        for compute_info in compute {
+            let existing = merged.iter_mut().find(|info| {
+                info.pid == compute_info.pid
+                    && info.gpu_instance_id == compute_info.gpu_instance_id
+                    && info.compute_instance_id == compute_info.compute_instance_id
+            });
+
+            match existing {
+                Some(existing) => {
+                    if let UsedGpuMemory::Used(compute_bytes) = compute_info.used_gpu_memory {
+                        existing.used_gpu_memory = match existing.used_gpu_memory {
+                            UsedGpuMemory::Used(bytes) => {
+                                UsedGpuMemory::Used(bytes.max(compute_bytes))
+                            }
+                            UsedGpuMemory::Unavailable => UsedGpuMemory::Used(compute_bytes),
+                        };
+                    }
+                }
+                None => merged.push(compute_info),
+            }
+        }
+
+        merged
+    }
+
+    fn nvidia_device_process_stats(gpu: &Device) -> Vec<ProcessUtilizationSample> {
+        let window = NVIDIA_SAMPLE_WINDOW.read().unwrap().as_micros() as u64;
+
+        gpu.process_utilization_stats(unix_as_millis().saturating_mul(1000).saturating_sub(window))
+            .unwrap_or_default()
+    }
+}
+
+/// Returns the major number of the char device driver called `name` from the contents of `/proc/devices`
+fn char_device_major(devices: &str, name: &str) -> Option<u32> {
+    devices
+        .lines()
+        .skip_while(|line| *line != "Character devices:")
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| {
+            let (major, driver) = line.trim().split_once(' ')?;
+            (driver == name).then(|| major.parse().ok())?
+        })
+}
+
+/// Checks whether a process with the given PID exists without gathering any data about it. Zombies count as existing.
+pub fn process_exists(pid: libc::pid_t) -> bool {
+    // 0 and negative PIDs would address process groups
+    if pid <= 0 {
+        return false;
+    }
+
+    // signal 0 only performs the existence and permission checks, EPERM still means that the process exists
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Like `process_exists()`, but also makes sure that it's still the same process (identified by its start time in
+/// clock ticks since boot, see `ProcessData::starttime`) and not another one that got the PID after it was freed
+pub fn process_exists_with_starttime(pid: libc::pid_t, starttime: u64) -> bool {
+    pid > 0
+        && std::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .and_then(|stat| starttime_from_stat(&stat))
+            == Some(starttime)
+}
+
+fn starttime_from_stat(stat: &str) -> Option<u64> {
+    stat.rsplit_once(')')?
+        .1
+        .split(' ')
+        .skip(1)
+        .nth(STAT_STARTTIME)?
+        .parse()
+        .ok()
+}
+
+/// Returns all GPUs and NPUs found in sysfs (DRM cards and compute accelerators) and through NVML, each tagged with
+/// its kind. Devices that aren't attached through PCI are not included.
+pub fn accelerator_slots() -> Vec<(PciSlot, AcceleratorKind)> {
+    let mut slots = BTreeMap::new();
+
+    for (pattern, default_kind) in [
+        ("/sys/class/drm/card[0-9]*/device", AcceleratorKind::Gpu),
+        ("/sys/class/accel/accel[0-9]*/device", AcceleratorKind::Npu),
+    ] {
+        for device_path in glob(pattern).into_iter().flatten().flatten() {
+            let Some(pci_slot) = std::fs::canonicalize(&device_path)
+                .ok()
+                .and_then(|path| path.file_name()?.to_str()?.parse::<PciSlot>().ok())
+            else {
+                continue;
+            };
+
+            let driver = std::fs::read_link(device_path.join("driver"))
+                .ok()
+                .and_then(|driver| Some(driver.file_name()?.to_string_lossy().to_string()));
+
+            let kind = if driver.is_some_and(|driver| NPU_DRIVERS.contains(&driver.as_str())) {
+                AcceleratorKind::Npu
+            } else {
+                default_kind
+            };
+
+            slots.insert(pci_slot, kind);
+        }
+    }
+
+    for (pci_slot, _) in NVML_DEVICES.iter() {
+        slots.entry(*pci_slot).or_insert(AcceleratorKind::Gpu);
+    }
+
+    slots.into_iter().collect()
+}
+
+/// Reads the system's total, available and swap memory from `/proc/meminfo`, e.g. to put the memory usage of a
+/// process in relation to the installed RAM
+pub fn system_memory() -> Result<MemoryInfo> {
+    let meminfo =
+        std::fs::read_to_string("/proc/meminfo").context("unable to read /proc/meminfo")?;
+    MemoryInfo::parse(&meminfo)
+}
+
+pub fn unix_as_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::struct_wrappers::device::{ProcessInfo, ProcessUtilizationSample};
+    use pretty_assertions::assert_eq;
+
+    use super::pci_slot::PciSlot;
+    use super::{
+        AcceleratorKind, AffinityReport, Containerization, DisplayProtocol, FdinfoTruncated,
+        GpuEngine, GpuIdentifier, GpuUsagePercent, GpuUsageSmoother, GpuUsageStats, IoPriority,
+        IoPriorityClass, IoStatsAccess, MemoryInfo, Niceness, NpuUsageStats, ProcFiles,
+        ProcessData, ProcessState, ProcessVanished, ScanOptions, SchedulingPolicy,
+    };
+
+    fn utilization_sample(pid: u32, sm_util: u32, enc_util: u32) -> ProcessUtilizationSample {
+        ProcessUtilizationSample {
+            pid,
+            timestamp: 0,
+            sm_util,
+            mem_util: 0,
+            enc_util,
+            dec_util: 0,
+        }
+    }
+
+    #[test]
+    fn nvidia_utilization_multiple_samples() {
+        let samples = [
+            utilization_sample(1, 80, 100),
+            utilization_sample(1, 90, 100),
+            utilization_sample(1, 100, 100),
+            utilization_sample(2, 100, 100),
+        ];
+
+        let (gfx, enc, dec) = ProcessData::nvidia_average_utilization(&samples, 1);
+
+        assert!((0..=100).contains(&gfx));
+        assert_eq!((90, 100, 0), (gfx, enc, dec));
+    }
+
+    #[test]
+    fn nvidia_utilization_clamped() {
+        let samples = [utilization_sample(1, 250, 0)];
+
+        let (gfx, _, _) = ProcessData::nvidia_average_utilization(&samples, 1);
+
+        assert_eq!(100, gfx);
+    }
+
+    #[test]
+    fn nvidia_utilization_no_samples() {
+        let samples = [utilization_sample(2, 50, 50)];
+
+        assert_eq!(
+            (0, 0, 0),
+            ProcessData::nvidia_average_utilization(&samples, 1)
+        );
+    }
+
+    #[test]
+    fn accelerator_stats_merged() {
+        let gpu_slot = PciSlot::new(0, 0x3, 0, 0);
+        let npu_slot = PciSlot::new(0, 0xc3, 0, 1);
+
+        let process_data = ProcessData {
+            gpu_usage_stats: BTreeMap::from([(
+                GpuIdentifier::PciSlot(gpu_slot),
+                GpuUsageStats {
+                    gfx: 1000,
+                    mem: 2048,
+                    enc: 0,
+                    dec: 0,
+                    nvidia: false,
+                    total_cycles: 0,
+                },
+            )]),
+            npu_usage_stats: BTreeMap::from([(
+                npu_slot,
+                NpuUsageStats {
+                    usage: 500,
+                    mem: 4096,
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let accelerator_stats = process_data.accelerator_stats();
+
+        assert_eq!(2, accelerator_stats.len());
+        assert_eq!(
+            AcceleratorKind::Gpu,
+            accelerator_stats[&GpuIdentifier::PciSlot(gpu_slot)].kind
+        );
+        assert_eq!(
+            AcceleratorKind::Npu,
+            accelerator_stats[&GpuIdentifier::PciSlot(npu_slot)].kind
+        );
+        assert_eq!(
+            4096,
+            accelerator_stats[&GpuIdentifier::PciSlot(npu_slot)].mem
+        );
+    }
+
+    fn process_info(pid: u32, used_gpu_memory: UsedGpuMemory) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            used_gpu_memory,
+            gpu_instance_id: None,
+            compute_instance_id: None,
+        }
+    }
+
+    #[test]
+    fn nvidia_memory_in_bytes() {
+        let infos = [
+            process_info(1, UsedGpuMemory::Used(256 * 1024 * 1024)),
+            process_info(2, UsedGpuMemory::Used(1024)),
+            process_info(3, UsedGpuMemory::Unavailable),
+        ];
+
+        assert_eq!(268_435_456, ProcessData::nvidia_used_memory(&infos, 1));
+        assert_eq!(1024, ProcessData::nvidia_used_memory(&infos, 2));
+        assert_eq!(0, ProcessData::nvidia_used_memory(&infos, 3));
+    }
+
+    #[test]
+    fn nvidia_graphics_and_compute_contexts() {
+        let graphics = vec![
+            process_info(1, UsedGpuMemory::Used(512 * 1024 * 1024)),
+            process_info(2, UsedGpuMemory::Unavailable),
+        ];
+        let compute = vec![
+            process_info(1, UsedGpuMemory::Used(512 * 1024 * 1024)),
+            process_info(2, UsedGpuMemory::Used(2048)),
+            process_info(3, UsedGpuMemory::Used(1024)),
+        ];
+
+        let merged = ProcessData::merge_nvidia_process_infos(graphics, compute);
+
+        assert_eq!(3, merged.len());
+        assert_eq!(536_870_912, ProcessData::nvidia_used_memory(&merged, 1));
+        assert_eq!(2048, ProcessData::nvidia_used_memory(&merged, 2));
+        assert_eq!(1024, ProcessData::nvidia_used_memory(&merged, 3));
+    }
+
+    #[test]
+    fn gpu_usage_percent_since_counters() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
+
+        let process = |timestamp, gfx, enc| ProcessData {
+            pid: 42,
+            starttime: 1000,
+            timestamp,
+            gpu_usage_stats: BTreeMap::from([(
+                gpu,
+                GpuUsageStats {
+                    gfx,
+                    mem: 0,
+                    enc,
+                    dec: 0,
+                    nvidia: false,
+                    total_cycles: 0,
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let previous = process(10_000, 1_000_000_000, 0);
+        let current = process(12_000, 1_500_000_000, 100_000_000);
+
+        let expected = GpuUsagePercent {
+            gfx: 25.0,
+            enc: 5.0,
+            dec: 0.0,
+        };
+
+        assert_eq!(
+            BTreeMap::from([(gpu, expected)]),
+            current.gpu_usage_percent_since(&previous)
+        );
+
+        // PID has been reused by another process in the meantime
+        let reused = ProcessData {
+            starttime: 2000,
+            ..process(12_000, 1_500_000_000, 100_000_000)
+        };
+        assert!(reused.gpu_usage_percent_since(&previous).is_empty());
+    }
+
+    #[test]
+    fn gpu_usage_percent_since_nvidia() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x1, 0, 0));
+
+        let process = |timestamp| ProcessData {
+            pid: 42,
+            timestamp,
+            gpu_usage_stats: BTreeMap::from([(
+                gpu,
+                GpuUsageStats {
+                    gfx: 37,
+                    mem: 0,
+                    enc: 4,
+                    dec: 0,
+                    nvidia: true,
+                    total_cycles: 0,
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let expected = GpuUsagePercent {
+            gfx: 37.0,
+            enc: 4.0,
+            dec: 0.0,
+        };
+
+        assert_eq!(
+            BTreeMap::from([(gpu, expected)]),
+            process(2000).gpu_usage_percent_since(&process(1000))
+        );
+    }
+
+    #[test]
+    fn client_aggregate_usage() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
+
+        let nvidia_gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x1, 0, 0));
+
+        let process = |pid, timestamp, gpu, gfx, nvidia| ProcessData {
+            pid,
+            timestamp,
+            gpu_usage_stats: BTreeMap::from([(
+                gpu,
+                GpuUsageStats {
+                    gfx,
+                    mem: 0,
+                    enc: 0,
+                    dec: 0,
+                    nvidia,
+                    total_cycles: 0,
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let previous = [
+            process(1, 1000, gpu, 0, false),
+            process(2, 1000, gpu, 5_000_000_000, false),
+        ];
+        // 250 ms and 500 ms of graphics engine time within one second, PID 3 is new and thus ignored while PID 4 is
+        // new as well but counts since its NVIDIA percentage doesn't need a previous sample
+        let current = [
+            process(1, 2000, gpu, 250_000_000, false),
+            process(2, 2000, gpu, 5_500_000_000, false),
+            process(3, 2000, gpu, 900_000_000, false),
+            process(4, 2000, nvidia_gpu, 30, true),
+        ];
+
+        let usages = ProcessData::client_aggregate_usage(&previous, &current);
+
+        assert_eq!(2, usages.len());
+        assert!((usages[&gpu] - 0.75).abs() < f64::EPSILON);
+        assert!((usages[&nvidia_gpu] - 0.3).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn sandboxed() {
+        let native = ProcessData::default();
+        let flatpak = ProcessData {
+            containerization: Containerization::Flatpak,
+            ..Default::default()
+        };
+
+        assert!(!native.is_sandboxed());
+        assert!(flatpak.is_sandboxed());
+        assert_eq!(Containerization::Flatpak, flatpak.sandbox_kind());
+    }
+
+    #[test]
+    fn amdxdna_fdinfo() {
+        let fdinfo = concat!(
+            "pos:\t0\n",
+            "flags:\t02100002\n",
+            "mnt_id:\t26\n",
+            "ino:\t1036\n",
+            "drm-driver:\tamdxdna_accel_driver\n",
+            "drm-client-id:\t7\n",
+            "drm-pdev:\t0000:c4:00.1\n",
+            "drm-total-memory:\t2048 KiB\n",
+            "drm-shared-memory:\t512 KiB\n",
+            "drm-active-memory:\t0\n",
+            "drm-resident-memory:\t2048 KiB\n",
+            "drm-purgeable-memory:\t0\n",
+        );
+
+        let (pci_slot, stats) = ProcessData::read_npu_fdinfo(fdinfo).unwrap();
+
+        assert_eq!(PciSlot::new(0, 0xc4, 0, 1), pci_slot);
+        assert_eq!(2048 * 1024, stats.mem);
+        assert_eq!(1536 * 1024, stats.device_mem);
+        assert_eq!(512 * 1024, stats.shared_mem);
+    }
+
+    const STAT: &str = "1234 (bash) S 1000 1234 1234 34816 1240 4194304 2000 10 1 0 150 30 0 0 20 0 1 0 54321 12345678 1500 18446744073709551615 1 1 0 0 0 0 65536 3686404 1266761467 0 0 0 17 3 0 0 0 25 0 0 0 0 0 0 0 0 0\n";
+
+    const STATUS: &str = concat!(
+        "Name:\tbash\n",
+        "State:\tS (sleeping)\n",
+        "Pid:\t1234\n",
+        "PPid:\t1000\n",
+        "Uid:\t1000\t0\t0\t0\n",
+        "Gid:\t0\t0\t0\t0\n",
+        "VmPeak:\t   12060 kB\n",
+        "VmSize:\t   12056 kB\n",
+        "VmHWM:\t    6000 kB\n",
+        "VmRSS:\t    5920 kB\n",
+        "RssShmem:\t     128 kB\n",
+        "VmSwap:\t       0 kB\n",
+        "HugetlbPages:\t    2048 kB\n",
+        "Cpus_allowed:\tf\n",
+        "voluntary_ctxt_switches:\t150\n",
+        "nonvoluntary_ctxt_switches:\t12\n",
+    );
+
+    fn proc_files() -> ProcFiles {
+        ProcFiles {
+            stat: STAT.into(),
+            statm: "3014 1480 900 238 0 400 0\n".into(),
+            status: STATUS.into(),
+            comm: "bash\n".into(),
+            cmdline: "/usr/bin/bash\0--login\0".into(),
+            io: Some("rchar: 100\nwchar: 50\nread_bytes: 4096\nwrite_bytes: 8192\n".into()),
+            io_access: IoStatsAccess::Readable,
+            cgroup: Some("0::/user.slice/user-1000.slice/session-2.scope\n".into()),
+            wchan: Some("do_wait".into()),
+        }
+    }
+
+    #[test]
+    fn from_proc_contents() {
+        let process_data = ProcessData::from_proc_contents(1234, proc_files()).unwrap();
+
+        assert_eq!(1234, process_data.pid);
+        assert_eq!(1000, process_data.parent_pid);
+        assert_eq!(1234, process_data.pgrp);
+        assert_eq!(1240, process_data.tpgid);
+        assert_eq!(Some(false), process_data.is_foreground());
+        assert_eq!("bash", process_data.comm);
+        assert_eq!(ProcessState::Sleeping, process_data.state);
+        assert_eq!(150, process_data.user_cpu_time);
+        assert_eq!(30, process_data.system_cpu_time);
+        assert_eq!(25, process_data.guest_time);
+        assert_eq!(Some(3), process_data.last_cpu);
+        assert_eq!(2000, process_data.minor_faults);
+        assert_eq!(1, process_data.major_faults);
+        assert_eq!(Some(150), process_data.voluntary_ctxt_switches);
+        assert_eq!(Some(12), process_data.nonvoluntary_ctxt_switches);
+        assert_eq!(54321, process_data.starttime);
+        assert_eq!(Some(6000 * 1024), process_data.peak_resident);
+        assert_eq!(Some(2048 * 1024), process_data.hugepages_usage);
+        assert_eq!(Some(128 * 1024), process_data.shared_memory);
+        assert_eq!(Some(12060 * 1024), process_data.peak_memory_usage);
+        assert_eq!(
+            Some("/user.slice/user-1000.slice/session-2.scope".to_string()),
+            process_data.cgroup_path
+        );
+        assert_eq!(Some(4096), process_data.read_bytes);
+        assert_eq!(Some(8192), process_data.write_bytes);
+        assert_eq!(Some("do_wait".into()), process_data.wchan);
+        assert_eq!(Containerization::None, process_data.containerization);
+        assert_eq!(0, process_data.effective_uid);
+        assert_eq!(1000, process_data.uid);
+    }
+
+    #[test]
+    fn display_protocol() {
+        assert_eq!(
+            ProcessData::display_protocol_from_environ(
+                b"HOME=/home/user\0WAYLAND_DISPLAY=wayland-0\0DISPLAY=:0\0"
+            ),
+            Some(DisplayProtocol::Wayland)
+        );
+        assert_eq!(
+            ProcessData::display_protocol_from_environ(
+                b"WAYLAND_DISPLAY=wayland-0\0DISPLAY=:0\0GDK_BACKEND=x11\0"
+            ),
+            Some(DisplayProtocol::X11)
+        );
+        assert_eq!(
+            ProcessData::display_protocol_from_environ(b"DISPLAY=:1\0"),
+            Some(DisplayProtocol::X11)
+        );
+        assert_eq!(
+            ProcessData::display_protocol_from_environ(b"HOME=/root\0"),
+            None
+        );
+    }
+
+    #[test]
+    fn schedstat() {
+        assert_eq!(
+            Some((2_459_123_004, 87_410_223, 5123)),
+            ProcessData::parse_schedstat("2459123004 87410223 5123\n")
+        );
+        assert_eq!(None, ProcessData::parse_schedstat("2459123004 87410223\n"));
+        assert_eq!(None, ProcessData::parse_schedstat(""));
+    }
+
+    #[test]
+    fn fd_soft_limit() {
+        const LIMITS: &str = concat!(
+            "Limit                     Soft Limit           Hard Limit           Units     \n",
+            "Max cpu time              unlimited            unlimited            seconds   \n",
+            "Max processes             62383                62383                processes \n",
+            "Max open files            1024                 524288               files     \n",
+            "Max locked memory         8388608              8388608              bytes     \n",
+        );
+
+        assert_eq!(Some(1024), ProcessData::fd_soft_limit(LIMITS));
+        assert_eq!(
+            None,
+            ProcessData::fd_soft_limit(
+                "Max open files            unlimited            unlimited            files\n"
+            )
+        );
+        assert_eq!(None, ProcessData::fd_soft_limit(""));
+    }
+
+    #[test]
+    fn untruncated_comm() {
+        assert_eq!(
+            "chrome_crashpad_handler",
+            ProcessData::untruncated_comm(
+                "chrome_crashpad",
+                Some("chrome_crashpad_handler"),
+                "/opt/google/chrome/chrome_crashpad_handler\0--monitor-self\0"
+            )
+        );
+        assert_eq!(
+            "gnome-shell-calendar-server",
+            ProcessData::untruncated_comm(
+                "gnome-shell-cal",
+                None,
+                "/usr/libexec/gnome-shell-calendar-server\0"
+            )
+        );
+        assert_eq!(
+            "bash",
+            ProcessData::untruncated_comm("bash", Some("bash-5.2"), "/usr/bin/bash\0")
+        );
+        assert_eq!(
+            "pool-spawner-ab",
+            ProcessData::untruncated_comm("pool-spawner-ab", Some("gnome-shell"), "")
+        );
+    }
+
+    #[test]
+    fn cgroup_v2_path() {
+        assert_eq!(
+            Some("/user.slice/user-1000.slice/session-2.scope"),
+            ProcessData::cgroup_v2_path(proc_files().cgroup.as_deref().unwrap())
+        );
+        assert_eq!(
+            Some("/system.slice/docker.service"),
+            ProcessData::cgroup_v2_path("1:name=systemd:/\n0::/system.slice/docker.service\n")
+        );
+        assert_eq!(None, ProcessData::cgroup_v2_path("1:cpu,cpuacct:/\n"));
+    }
+
+    #[test]
+    fn systemd_slice() {
+        let slice = |cgroup_path: &str| {
+            ProcessData {
+                cgroup_path: Some(cgroup_path.to_string()),
+                ..Default::default()
+            }
+            .systemd_slice()
+        };
+
+        assert_eq!(
+            Some("user-1000.slice".to_string()),
+            slice("/user.slice/user-1000.slice/session-2.scope")
+        );
+        assert_eq!(
+            Some("app.slice".to_string()),
+            slice("/user.slice/user-1000.slice/user@1000.service/app.slice/app-gnome-firefox-2261.scope")
+        );
+        assert_eq!(
+            Some("system.slice".to_string()),
+            slice("/system.slice/NetworkManager.service")
+        );
+        assert_eq!(
+            Some("machine.slice".to_string()),
+            slice("/machine.slice/machine-qemu\\x2d1\\x2dfedora.scope")
+        );
+        assert_eq!(None, slice("/init.scope"));
+        assert_eq!(None, ProcessData::default().systemd_slice());
+    }
+
+    #[test]
+    fn login_session() {
+        let session = |cgroup_path: &str| {
+            ProcessData {
+                cgroup_path: Some(cgroup_path.to_string()),
+                ..Default::default()
+            }
+            .login_session()
+        };
+
+        assert_eq!(
+            Some("2".to_string()),
+            session("/user.slice/user-1000.slice/session-2.scope")
+        );
+        assert_eq!(
+            Some("c1".to_string()),
+            session("/user.slice/user-120.slice/session-c1.scope")
+        );
+        assert_eq!(
+            None,
+            session("/user.slice/user-1000.slice/user@1000.service/app.slice/app-gnome-firefox-2261.scope")
+        );
+        assert_eq!(None, session("/system.slice/sshd.service"));
+    }
+
+    #[test]
+    fn memory_fraction() {
+        assert_eq!(
+            Some(0.25),
+            ProcessData::memory_fraction("134217728\n", "536870912\n")
+        );
+        assert_eq!(None, ProcessData::memory_fraction("134217728\n", "max\n"));
+    }
+
+    #[test]
+    fn container_from_cgroup_path() {
+        const ID: &str = "3f4e5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f";
+
+        assert_eq!(
+            Some((Containerization::Docker, ID.to_string())),
+            ProcessData::container_from_cgroup_path(&format!("/system.slice/docker-{ID}.scope"))
+        );
+        assert_eq!(
+            Some((Containerization::Docker, ID.to_string())),
+            ProcessData::container_from_cgroup_path(&format!("/docker/{ID}"))
+        );
+        assert_eq!(
+            Some((Containerization::Podman, ID.to_string())),
+            ProcessData::container_from_cgroup_path(&format!(
+                "/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{ID}.scope/container"
+            ))
+        );
+        assert_eq!(
+            Some((Containerization::Lxc, "ubuntu".to_string())),
+            ProcessData::container_from_cgroup_path(
+                "/lxc.payload.ubuntu/system.slice/cron.service"
+            )
+        );
+        assert_eq!(
+            None,
+            ProcessData::container_from_cgroup_path(&format!(
+                "/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-conmon-{ID}.scope"
+            ))
+        );
+        assert_eq!(
+            None,
+            ProcessData::container_from_cgroup_path("/system.slice/docker.service")
+        );
+        assert_eq!(
+            None,
+            ProcessData::container_from_cgroup_path("/user.slice/user-1000.slice/session-2.scope")
+        );
+    }
+
+    #[test]
+    fn cgroup2_mount_point() {
+        const MOUNTINFO: &str = concat!(
+            "22 1 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw\n",
+            "25 23 0:24 / /sys/fs/cgroup/unified rw,nosuid,nodev,noexec,relatime shared:4 - cgroup2 cgroup2 rw\n",
+        );
+
+        assert_eq!(
+            Some(PathBuf::from("/sys/fs/cgroup/unified")),
+            ProcessData::cgroup2_mount_point(MOUNTINFO)
+        );
+        assert_eq!(None, ProcessData::cgroup2_mount_point(""));
+    }
+
+    #[test]
+    fn starttime_from_stat() {
+        assert_eq!(Some(54321), super::starttime_from_stat(STAT));
+        assert_eq!(
+            Some(54321),
+            super::starttime_from_stat(&STAT.replace("(bash)", "(evil) S 1 2 3 (name)"))
+        );
+        assert_eq!(None, super::starttime_from_stat("1234 (bash) S 1000"));
+    }
+
+    #[test]
+    fn process_exists() {
+        let pid = std::process::id() as libc::pid_t;
+
+        assert!(super::process_exists(pid));
+        assert!(!super::process_exists(0));
+        assert!(!super::process_exists(-1));
+    }
+
+    #[test]
+    fn try_from_path_vanished() {
+        let error = ProcessData::try_from_path("/proc/999999999").unwrap_err();
+
+        assert_eq!(
+            Some(&ProcessVanished(999_999_999)),
+            error.downcast_ref::<ProcessVanished>()
+        );
+    }
+
+    #[test]
+    fn refresh_from_path() {
+        let pid = std::process::id() as libc::pid_t;
+        let proc_path = format!("/proc/{pid}");
+        let options = ScanOptions::default();
+
+        let current = ProcessData::try_from_path_with_options(&proc_path, &options).unwrap();
+
+        let mut cached = current.clone();
+        cached.comm = String::from("cached");
+        cached.commandline = String::from("cached");
+        cached.cgroup = Some(String::from("cached"));
+        cached.cgroup_path = Some(String::from("/cached.scope"));
+        cached.security_context = Some(String::from("cached"));
+
+        // the name, command line and cgroup can change without an exec and are read again
+        let refreshed = cached.refresh_from_path(&proc_path, &options).unwrap();
+        assert_eq!(current.comm, refreshed.comm);
+        assert_eq!(current.commandline, refreshed.commandline);
+        assert_eq!(current.cgroup, refreshed.cgroup);
+        assert_eq!(current.cgroup_path, refreshed.cgroup_path);
+        assert_eq!(current.starttime, refreshed.starttime);
+        // the security context only changes on exec and is kept
+        assert_eq!(Some("cached"), refreshed.security_context.as_deref());
+
+        // a different start time means the PID has been reused, so the process is read from scratch
+        cached.starttime += 1;
+        let refreshed = cached.refresh_from_path(&proc_path, &options).unwrap();
+        assert_eq!(current.security_context, refreshed.security_context);
+    }
+
+    #[test]
+    fn frozen_from_cgroup_events() {
+        assert!(ProcessData::frozen_from_cgroup_events(
+            "populated 1\nfrozen 1\n"
+        ));
+        assert!(!ProcessData::frozen_from_cgroup_events(
+            "populated 1\nfrozen 0\n"
+        ));
+        assert!(!ProcessData::frozen_from_cgroup_events("populated 0\n"));
+    }
+
+    #[test]
+    fn cgroup_pids_of_descendants() {
+        let cgroup_dir =
+            std::env::temp_dir().join(format!("resources-cgroup-{}", std::process::id()));
+        let child_dir = cgroup_dir.join("app.scope").join("child");
+        std::fs::create_dir_all(&child_dir).unwrap();
+
+        std::fs::write(cgroup_dir.join("cgroup.procs"), "10\n11\n").unwrap();
+        std::fs::write(cgroup_dir.join("app.scope").join("cgroup.procs"), "").unwrap();
+        std::fs::write(child_dir.join("cgroup.procs"), "12\n").unwrap();
+
+        let mut pids = Vec::new();
+        assert_eq!(
+            Some(()),
+            ProcessData::collect_cgroup_pids(&cgroup_dir, &mut pids)
+        );
+        pids.sort_unstable();
+        assert_eq!(vec![10, 11, 12], pids);
+
+        std::fs::remove_dir_all(&cgroup_dir).unwrap();
+
+        assert_eq!(
+            None,
+            ProcessData::collect_cgroup_pids(&cgroup_dir, &mut Vec::new())
+        );
+    }
+
+    #[test]
+    fn uids() {
+        assert_eq!((1000, 0), ProcessData::get_uids(STATUS).unwrap());
+        assert_eq!((0, 0), ProcessData::get_uids("Name:\tkthreadd\n").unwrap());
+    }
+
+    #[test]
+    fn from_proc_contents_malformed_stat() {
+        let files = ProcFiles {
+            stat: "1234 (bash) S 1000".into(),
+            ..proc_files()
+        };
+
+        assert!(ProcessData::from_proc_contents(1234, files).is_err());
+    }
+
+    #[test]
+    fn from_proc_contents_truncated_statm() {
+        let files = ProcFiles {
+            statm: "3014".into(),
+            ..proc_files()
+        };
+
+        let process_data = ProcessData::from_proc_contents(1234, files).unwrap();
+
+        assert_eq!(0, process_data.memory_usage);
+        assert_eq!(150, process_data.user_cpu_time);
+    }
+
+    #[test]
+    fn from_proc_contents_io_denied() {
+        let files = ProcFiles {
+            io: None,
+            io_access: IoStatsAccess::Denied,
+            ..proc_files()
+        };
+
+        let process_data = ProcessData::from_proc_contents(1234, files).unwrap();
 
-        let memory_usage = statm
-            .get(1)
-            .context("wrong statm file format")
-            .and_then(|x| {
-                x.parse::<usize>()
-                    .context("couldn't parse statm file content")
-            })?
-            .saturating_sub(
-                statm
-                    .get(2)
-                    .context("wrong statm file format")
-                    .and_then(|x| {
-                        x.parse::<usize>()
-                            .context("couldn't parse statm file content")
-                    })?,
-            )
-            .saturating_mul(*PAGESIZE);
+        assert_eq!(None, process_data.read_bytes);
+        assert_eq!(IoStatsAccess::Denied, process_data.io_access);
+    }
 
-        let cgroup = std::fs::read_to_string(proc_path.join("cgroup"))
-            .ok()
-            .and_then(Self::sanitize_cgroup);
+    #[test]
+    fn from_proc_contents_short_stat() {
+        let files = ProcFiles {
+            stat: STAT.split(' ').take(30).collect::<Vec<_>>().join(" "),
+            ..proc_files()
+        };
 
-        let containerization = if commandline.starts_with("/snap/") {
-            Containerization::Snap
-        } else if proc_path.join("root").join(".flatpak-info").exists() {
-            Containerization::Flatpak
-        } else {
-            Containerization::None
+        let process_data = ProcessData::from_proc_contents(1234, files).unwrap();
+
+        assert_eq!(0, process_data.guest_time);
+        assert_eq!(54321, process_data.starttime);
+    }
+
+    #[test]
+    fn gpu_usage_smoother_ema() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
+        let usage = |gfx| GpuUsagePercent {
+            gfx,
+            enc: 0.0,
+            dec: 0.0,
         };
 
-        let read_bytes = io.as_ref().and_then(|io| {
-            RE_IO_READ
-                .captures(io)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-        });
+        let mut smoother = GpuUsageSmoother::new(0.5);
 
-        let write_bytes = io.as_ref().and_then(|io| {
-            RE_IO_WRITE
-                .captures(io)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-        });
+        assert_eq!(usage(40.0), smoother.smooth(1, gpu, usage(40.0)));
+        assert_eq!(usage(20.0), smoother.smooth(1, gpu, usage(0.0)));
+        assert_eq!(usage(60.0), smoother.smooth(1, gpu, usage(100.0)));
 
-        let gpu_usage_stats = Self::gpu_usage_stats(proc_path, pid);
+        smoother.retain_pids(&[2].into());
+        assert_eq!(usage(10.0), smoother.smooth(1, gpu, usage(10.0)));
+    }
 
-        let timestamp = unix_as_millis();
+    #[test]
+    fn parse_namespace_link() {
+        assert_eq!(
+            Some(4026531841),
+            ProcessData::parse_namespace_link("mnt:[4026531841]", "mnt")
+        );
+        assert_eq!(
+            None,
+            ProcessData::parse_namespace_link("pid:[4026531836]", "mnt")
+        );
+        assert_eq!(None, ProcessData::parse_namespace_link("mnt:[]", "mnt"));
+    }
 
-        Ok(Self {
+    #[test]
+    fn smaps_swap_usage() {
+        let smaps_rollup = concat!(
+            "55d6f3a6c000-7ffd8a1f1000 ---p 00000000 00:00 0                          [rollup]\n",
+            "Rss:              204352 kB\n",
+            "Anonymous:         98304 kB\n",
+            "Swap:               5120 kB\n",
+            "SwapPss:            2048 kB\n",
+            "Locked:                0 kB\n",
+        );
+
+        assert_eq!(
+            Some(5120 * 1024),
+            ProcessData::smaps_swap_usage(smaps_rollup)
+        );
+    }
+
+    #[test]
+    fn subtree_gpu_memory() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
+
+        let process = |pid, parent_pid, mem| ProcessData {
             pid,
             parent_pid,
-            user,
-            comm,
-            commandline,
-            user_cpu_time,
-            system_cpu_time,
-            niceness: nice,
-            affinity,
-            memory_usage,
-            swap_usage,
+            gpu_usage_stats: BTreeMap::from([(
+                gpu,
+                GpuUsageStats {
+                    gfx: 0,
+                    mem,
+                    enc: 0,
+                    dec: 0,
+                    nvidia: false,
+                    total_cycles: 0,
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let processes = [
+            process(1, 0, 1),
+            process(10, 1, 100),
+            process(11, 10, 20),
+            process(12, 11, 3),
+            process(13, 1, 4000),
+        ];
+
+        assert_eq!(
+            BTreeMap::from([(gpu, 123)]),
+            ProcessData::subtree_gpu_memory(10, &processes)
+        );
+        assert_eq!(
+            BTreeMap::new(),
+            ProcessData::subtree_gpu_memory(42, &processes)
+        );
+    }
+
+    #[test]
+    fn dominant_engine() {
+        let stats = |gfx, enc, dec, nvidia| GpuUsageStats {
+            gfx,
+            mem: 0,
+            enc,
+            dec,
+            nvidia,
+            total_cycles: 0,
+        };
+
+        // e.g. OBS recording the screen
+        assert_eq!(
+            Some(GpuEngine::Encode),
+            stats(1_200_000, 9_500_000, 0, false).dominant_engine()
+        );
+        assert_eq!(
+            Some(GpuEngine::Graphics),
+            stats(85, 3, 10, true).dominant_engine()
+        );
+        assert_eq!(
+            Some(GpuEngine::Graphics),
+            stats(20, 20, 20, true).dominant_engine()
+        );
+        assert_eq!(None, stats(0, 0, 0, false).dominant_engine());
+    }
+
+    #[test]
+    fn gpu_usage_diff() {
+        let gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
+
+        let process = |pid, starttime, timestamp, gfx, dec| ProcessData {
+            pid,
             starttime,
-            cgroup,
-            containerization,
-            read_bytes,
-            write_bytes,
             timestamp,
-            gpu_usage_stats,
-        })
-    }
+            gpu_usage_stats: BTreeMap::from([(
+                gpu,
+                GpuUsageStats {
+                    gfx,
+                    mem: 0,
+                    enc: 0,
+                    dec,
+                    nvidia: false,
+                    total_cycles: 0,
+                },
+            )]),
+            ..Default::default()
+        };
 
-    fn gpu_usage_stats(proc_path: &Path, pid: i32) -> BTreeMap<GpuIdentifier, GpuUsageStats> {
-        let nvidia_stats = Self::nvidia_gpu_stats_all(pid);
-        let mut other_stats = Self::other_gpu_usage_stats(proc_path, pid).unwrap_or_default();
-        other_stats.extend(nvidia_stats);
-        other_stats
+        // PID 2 exits between both scans and a new process reuses its PID, which must not be compared to the old one
+        let previous = [
+            process(1, 100, 1000, 0, 0),
+            process(2, 200, 1000, 3_000_000_000, 0),
+        ];
+        let current = [
+            process(1, 100, 2000, 250_000_000, 100_000_000),
+            process(2, 900, 2000, 400_000_000, 0),
+        ];
+
+        let expected = BTreeMap::from([(
+            gpu,
+            GpuUsagePercent {
+                gfx: 25.0,
+                enc: 0.0,
+                dec: 10.0,
+            },
+        )]);
+
+        assert_eq!(expected, ProcessData::gpu_usage_diff(&previous, &current));
     }
 
-    fn other_gpu_usage_stats(
-        proc_path: &Path,
-        pid: i32,
-    ) -> Result<BTreeMap<GpuIdentifier, GpuUsageStats>> {
-        let fdinfo_dir = proc_path.join("fdinfo");
+    #[test]
+    fn drm_fdinfo_plausible() {
+        let devices = concat!(
+            "Character devices:\n",
+            "  1 mem\n",
+            "226 drm\n",
+            "261 accel\n",
+            "\n",
+            "Block devices:\n",
+            "259 blkext\n",
+        );
+        let accel_major = super::char_device_major(devices, "accel");
 
-        let mut seen_fds = HashSet::new();
+        assert_eq!(Some(261), accel_major);
+        assert_eq!(None, super::char_device_major(devices, "blkext"));
 
-        let mut return_map = BTreeMap::new();
-        for entry in std::fs::read_dir(fdinfo_dir)? {
-            let entry = entry?;
-            let fdinfo_path = entry.path();
+        assert!(ProcessData::drm_fdinfo_plausible(
+            libc::S_IFCHR | 0o666,
+            226,
+            accel_major
+        ));
+        assert!(ProcessData::drm_fdinfo_plausible(
+            libc::S_IFCHR | 0o666,
+            261,
+            accel_major
+        ));
+        assert!(!ProcessData::drm_fdinfo_plausible(
+            libc::S_IFCHR | 0o666,
+            261,
+            None
+        ));
+        assert!(!ProcessData::drm_fdinfo_plausible(
+            libc::S_IFCHR | 0o666,
+            1,
+            accel_major
+        ));
+        assert!(!ProcessData::drm_fdinfo_plausible(
+            libc::S_IFREG | 0o644,
+            226,
+            accel_major
+        ));
+    }
 
-            let _file = std::fs::File::open(&fdinfo_path);
-            if _file.is_err() {
-                continue;
-            }
-            let mut file = _file.unwrap();
+    #[test]
+    fn active_gpu_count() {
+        let stats = |gfx, mem, nvidia| GpuUsageStats {
+            gfx,
+            mem,
+            enc: 0,
+            dec: 0,
+            nvidia,
+            total_cycles: 0,
+        };
 
-            let _metadata = file.metadata();
-            if _metadata.is_err() {
-                continue;
-            }
-            let metadata = _metadata.unwrap();
+        let process = ProcessData {
+            gpu_usage_stats: BTreeMap::from([
+                (GpuIdentifier::Enumerator(0), stats(1_000_000, 0, false)),
+                (GpuIdentifier::Enumerator(1), stats(0, 4096, false)),
+                (GpuIdentifier::Enumerator(2), stats(12, 0, true)),
+            ]),
+            npu_usage_stats: BTreeMap::from([(
+                PciSlot::new(0, 0xc4, 0, 1),
+                NpuUsageStats::default(),
+            )]),
+            ..Default::default()
+        };
 
-            // if our fd is 0, 1 or 2 it's probably just a std stream so skip it
-            let fd_num = fdinfo_path
-                .file_name()
-                .and_then(|osstr| osstr.to_str())
-                .unwrap_or("0")
-                .parse::<usize>()
-                .unwrap_or(0);
-            if fd_num <= 2 {
-                continue;
-            }
+        assert_eq!(2, process.active_gpu_count());
+        assert_eq!(0, process.active_npu_count());
+    }
 
-            if !metadata.is_file() {
-                continue;
-            }
+    #[test]
+    fn top_processes_by() {
+        let processes: Vec<ProcessData> = [300, 100, 500, 200, 400]
+            .into_iter()
+            .enumerate()
+            .map(|(pid, memory_usage)| ProcessData {
+                pid: pid as libc::pid_t,
+                memory_usage,
+                ..Default::default()
+            })
+            .collect();
 
-            // Adapted from nvtop's `is_drm_fd()`
-            // https://github.com/Syllo/nvtop/blob/master/src/extract_processinfo_fdinfo.c
-            let fd_path = fdinfo_path.to_str().map(|s| s.replace("fdinfo", "fd"));
-            if let Some(fd_path) = fd_path {
-                if let Ok(fd_metadata) = std::fs::metadata(fd_path) {
-                    let major = unsafe { libc::major(fd_metadata.st_rdev()) };
-                    if (fd_metadata.st_mode() & libc::S_IFMT) != libc::S_IFCHR || major != 226 {
-                        continue;
-                    }
-                }
-            }
+        let top =
+            ProcessData::top_processes_by(&processes, 3, |process| process.memory_usage as u64);
+        assert_eq!(
+            vec![2, 4, 0],
+            top.iter().map(|process| process.pid).collect::<Vec<_>>()
+        );
 
-            // Adapted from nvtop's `processinfo_sweep_fdinfos()`
-            // https://github.com/Syllo/nvtop/blob/master/src/extract_processinfo_fdinfo.c
-            // if we've already seen the file this fd refers to, skip
-            let not_unique = seen_fds.iter().any(|seen_fd| unsafe {
-                syscalls::syscall!(syscalls::Sysno::kcmp, pid, pid, 0, fd_num, *seen_fd)
-                    .unwrap_or(0)
-                    == 0
-            });
-            if not_unique {
-                continue;
-            }
+        assert_eq!(
+            5,
+            ProcessData::top_processes_by(&processes, 10, |process| process.user_cpu_time).len()
+        );
+        assert!(
+            ProcessData::top_processes_by(&processes, 0, |process| process.user_cpu_time)
+                .is_empty()
+        );
+    }
 
-            seen_fds.insert(fd_num);
+    #[test]
+    fn per_instance_engine_fdinfo() {
+        // as reported by amdgpu on a Navi 21 card
+        let fdinfo = concat!(
+            "pos:\t0\n",
+            "flags:\t02100002\n",
+            "mnt_id:\t24\n",
+            "ino:\t1159\n",
+            "drm-driver:\tamdgpu\n",
+            "drm-client-id:\t42\n",
+            "drm-pdev:\t0000:03:00.0\n",
+            "pasid:\t32771\n",
+            "drm-memory-vram:\t65536 KiB\n",
+            "drm-memory-gtt: \t0 KiB\n",
+            "drm-memory-cpu: \t0 KiB\n",
+            "amd-memory-visible-vram:\t0 KiB\n",
+            "amd-evicted-vram:\t0 KiB\n",
+            "amd-evicted-visible-vram:\t0 KiB\n",
+            "amd-requested-vram:\t65536 KiB\n",
+            "amd-requested-visible-vram:\t0 KiB\n",
+            "amd-requested-gtt:\t0 KiB\n",
+            "drm-engine-gfx:\t1500 ns\n",
+            "drm-engine-compute:\t0 ns\n",
+            "drm-engine-dma:\t0 ns\n",
+            "drm-engine-dec:\t100 ns\n",
+            "drm-engine-enc:\t150 ns\n",
+            "drm-engine-enc_1:\t50 ns\n",
+            "drm-engine-dec_1:\t200 ns\n",
+            "drm-engine-jpeg:\t0 ns\n",
+        );
 
-            if let Ok(stats) = Self::read_fdinfo(&mut file, metadata.len() as usize) {
-                return_map
-                    .entry(stats.0)
-                    .and_modify(|existing_value: &mut GpuUsageStats| {
-                        if stats.1.gfx > existing_value.gfx {
-                            existing_value.gfx = stats.1.gfx;
-                        }
-                        if stats.1.dec > existing_value.dec {
-                            existing_value.dec = stats.1.dec;
-                        }
-                        if stats.1.enc > existing_value.enc {
-                            existing_value.enc = stats.1.enc;
-                        }
-                        if stats.1.mem > existing_value.mem {
-                            existing_value.mem = stats.1.mem;
-                        }
-                    })
-                    .or_insert(stats.1);
-            }
-        }
+        let (gpu_identifier, stats) = ProcessData::read_gpu_fdinfo(fdinfo).unwrap();
 
-        Ok(return_map)
+        assert_eq!(
+            GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0)),
+            gpu_identifier
+        );
+        assert_eq!(
+            GpuUsageStats {
+                gfx: 1500,
+                mem: 65536 * 1024,
+                enc: 200,
+                dec: 300,
+                nvidia: false,
+                total_cycles: 0,
+            },
+            stats
+        );
     }
 
-    fn read_fdinfo(
-        fdinfo_file: &mut File,
-        file_size: usize,
-    ) -> Result<(GpuIdentifier, GpuUsageStats)> {
-        let mut content = String::with_capacity(file_size);
-        fdinfo_file.read_to_string(&mut content)?;
-        fdinfo_file.flush()?;
+    #[test]
+    fn desktop_id_hint() {
+        let process = |cgroup_path: Option<&str>, commandline: &str| ProcessData {
+            cgroup_path: cgroup_path.map(str::to_string),
+            comm: "bash".into(),
+            commandline: commandline.into(),
+            ..Default::default()
+        };
 
-        let driver = RE_DRM_DRIVER
-            .captures(&content)
-            .and_then(|captures| captures.get(1))
-            .map(|capture| capture.as_str());
+        assert_eq!(
+            Some("org.mozilla.firefox".to_string()),
+            process(
+                Some("/user.slice/user-1000.slice/user@1000.service/app.slice/app-flatpak-org.mozilla.firefox-2261.scope"),
+                "/app/lib/firefox/firefox\0"
+            )
+            .desktop_id_hint()
+        );
+        assert_eq!(
+            Some("org.gnome.Terminal".to_string()),
+            process(
+                Some("/user.slice/user-1000.slice/user@1000.service/app.slice/app-gnome-org.gnome.Terminal-4020.scope"),
+                "/usr/bin/gnome-terminal\0"
+            )
+            .desktop_id_hint()
+        );
+        assert_eq!(
+            Some("org.gnome.Nautilus".to_string()),
+            process(
+                Some("/user.slice/user-1000.slice/user@1000.service/app.slice/app-gnome-org.gnome.Nautilus@73e5.service"),
+                "/usr/bin/nautilus\0--gapplication-service\0"
+            )
+            .desktop_id_hint()
+        );
+        assert_eq!(
+            Some("htop".to_string()),
+            process(
+                Some("/user.slice/user-1000.slice/session-2.scope"),
+                "/usr/bin/htop\0-d\0"
+            )
+            .desktop_id_hint()
+        );
+        assert_eq!(
+            Some("bash".to_string()),
+            process(None, "").desktop_id_hint()
+        );
+    }
 
-        if driver.is_some() {
-            let gpu_identifier = RE_DRM_PDEV
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| PciSlot::from_str(capture.as_str()).ok())
-                .map(|pci_slot| GpuIdentifier::PciSlot(pci_slot))
-                .unwrap_or_default();
+    #[test]
+    fn affinity_report() {
+        // pinned to CPUs 2 and 3 of 8
+        let process = |last_cpu| ProcessData {
+            affinity: (0..8).map(|cpu| cpu == 2 || cpu == 3).collect(),
+            last_cpu,
+            ..Default::default()
+        };
 
-            let gfx = RE_DRM_ENGINE_GFX
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+        assert_eq!(
+            AffinityReport {
+                allowed_cpus: 2,
+                total_cpus: 8,
+                restricted: true,
+                last_cpu_allowed: Some(true),
+            },
+            process(Some(3)).affinity_report()
+        );
+        assert!(!process(Some(3)).affinity_violation());
+        assert!(process(Some(5)).affinity_violation());
+        assert!(!process(None).affinity_violation());
+    }
 
-            let render = RE_DRM_ENGINE_RENDER
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+    #[test]
+    fn truncated_fdinfo() {
+        let fdinfo = concat!(
+            "pos:\t0\n",
+            "flags:\t02100002\n",
+            "drm-driver:\tamdgpu\n",
+            "drm-pdev:\t0000:03:00.0\n",
+            "drm-memory-vram:\t65536 KiB\n",
+            "drm-engine-gfx:\t1000 ns\n",
+        );
 
-            let compute = RE_DRM_ENGINE_COMPUTE
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+        assert!(ProcessData::parse_fdinfo(fdinfo).is_ok());
+        assert!(ProcessData::parse_fdinfo(&fdinfo[..fdinfo.len() - 4])
+            .is_err_and(|error| error.is::<FdinfoTruncated>()));
+        assert!(ProcessData::parse_fdinfo(&fdinfo[..12])
+            .is_err_and(|error| error.is::<FdinfoTruncated>()));
+        // complete but lacking drm-driver, which isn't worth a retry
+        assert!(ProcessData::parse_fdinfo("pos:\t0\nflags:\t02100002\n")
+            .is_err_and(|error| !error.is::<FdinfoTruncated>()));
+    }
 
-            let enc = RE_DRM_ENGINE_ENC
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+    #[test]
+    fn gpu_usage_age() {
+        let nvidia_gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x1, 0, 0));
+        let amd_gpu = GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0));
 
-            let video = RE_DRM_ENGINE_VIDEO
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+        let stats = |nvidia| GpuUsageStats {
+            gfx: 0,
+            mem: 0,
+            enc: 0,
+            dec: 0,
+            nvidia,
+            total_cycles: 0,
+        };
 
-            let dec = RE_DRM_ENGINE_DEC
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default();
+        let mut process = ProcessData {
+            timestamp: 10_000,
+            gpu_usage_stats: BTreeMap::from([(nvidia_gpu, stats(true)), (amd_gpu, stats(false))]),
+            ..Default::default()
+        };
 
-            let vram = RE_DRM_MEMORY_VRAM
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default()
-                .saturating_mul(1024);
+        assert_eq!(None, process.gpu_usage_age(&nvidia_gpu));
+        assert_eq!(Some(Duration::ZERO), process.gpu_usage_age(&amd_gpu));
 
-            let gtt = RE_DRM_MEMORY_GTT
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default()
-                .saturating_mul(1024);
+        process.gpu_usage_sampled_at.insert(nvidia_gpu, 7_500);
+        assert_eq!(
+            Some(Duration::from_millis(2_500)),
+            process.gpu_usage_age(&nvidia_gpu)
+        );
+    }
 
-            let total_memory = RE_DRM_TOTAL_MEMORY
-                .captures(&content)
-                .and_then(|captures| captures.get(1))
-                .and_then(|capture| capture.as_str().parse::<u64>().ok())
-                .unwrap_or_default()
-                .saturating_mul(1024);
+    #[test]
+    fn all_pids() {
+        let pids = ProcessData::all_pids().unwrap();
 
-            let stats = GpuUsageStats {
-                gfx: gfx.saturating_add(render).saturating_add(compute),
-                mem: vram.saturating_add(gtt).saturating_add(total_memory),
-                enc: enc.saturating_add(video),
-                dec,
-                nvidia: false,
-            };
+        assert!(pids.contains(&(std::process::id() as libc::pid_t)));
+    }
 
-            return Ok((gpu_identifier, stats));
+    #[test]
+    fn deserialize_old_gpu_usage_stats() {
+        use serde::Serialize;
+
+        // a payload from before the version was introduced, lacking `nvidia` and containing a field we don't know,
+        // encoded like `resources-processes` does
+        #[derive(Serialize)]
+        struct LegacyGpuUsageStats {
+            gfx: u64,
+            mem: u64,
+            enc: u64,
+            dec: u64,
+            copy: u64,
         }
 
-        bail!("unable to find gpu information in this fdinfo");
+        let payload = rmp_serde::to_vec_named(&LegacyGpuUsageStats {
+            gfx: 1000,
+            mem: 4096,
+            enc: 20,
+            dec: 30,
+            copy: 5,
+        })
+        .unwrap();
+        let stats: GpuUsageStats = rmp_serde::from_slice(&payload).unwrap();
+
+        let expected = GpuUsageStats {
+            gfx: 1000,
+            mem: 4096,
+            enc: 20,
+            dec: 30,
+            nvidia: false,
+            total_cycles: 0,
+        };
+        assert_eq!(expected, stats);
+
+        let current = GpuUsageStats {
+            nvidia: true,
+            total_cycles: 500,
+            ..expected
+        };
+        let round_tripped: GpuUsageStats =
+            rmp_serde::from_slice(&rmp_serde::to_vec_named(&current).unwrap()).unwrap();
+        assert_eq!(current, round_tripped);
     }
 
-    fn nvidia_gpu_stats_all(pid: i32) -> BTreeMap<GpuIdentifier, GpuUsageStats> {
-        let mut return_map = BTreeMap::new();
+    #[test]
+    fn parse_meminfo() {
+        let meminfo = concat!(
+            "MemTotal:       32546872 kB\n",
+            "MemFree:         9718836 kB\n",
+            "MemAvailable:   21470228 kB\n",
+            "Buffers:          512340 kB\n",
+            "Cached:         11223220 kB\n",
+            "SwapCached:            0 kB\n",
+            "Shmem:           1034520 kB\n",
+            "SwapTotal:       8388604 kB\n",
+            "SwapFree:        8388604 kB\n",
+            "HugePages_Total:       0\n",
+            "Hugepagesize:       2048 kB\n",
+        );
 
-        for (pci_slot, _) in NVML_DEVICES.iter() {
-            if let Ok(stats) = Self::nvidia_gpu_stats(pid, *pci_slot) {
-                return_map.insert(GpuIdentifier::PciSlot(pci_slot.to_owned()), stats);
-            }
+        let memory_info = MemoryInfo::parse(meminfo).unwrap();
+
+        assert_eq!(
+            MemoryInfo {
+                total: 32546872 * 1024,
+                free: 9718836 * 1024,
+                available: 21470228 * 1024,
+                buffers: 512340 * 1024,
+                cached: 11223220 * 1024,
+                shared: 1034520 * 1024,
+                swap_total: 8388604 * 1024,
+                swap_free: 8388604 * 1024,
+            },
+            memory_info
+        );
+        assert_eq!((32546872 - 21470228) * 1024, memory_info.used());
+        assert_eq!(0, memory_info.swap_used());
+    }
+
+    #[test]
+    fn parse_meminfo_missing_field() {
+        assert!(
+            MemoryInfo::parse("MemTotal:       32546872 kB\nMemFree:         9718836 kB\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_environ() {
+        assert_eq!(
+            vec![
+                ("HOME".to_string(), "/home/user".to_string()),
+                ("LS_COLORS".to_string(), "rs=0:di=01;34".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("MALFORMED".to_string(), String::new()),
+            ],
+            ProcessData::parse_environ(
+                b"HOME=/home/user\0LS_COLORS=rs=0:di=01;34\0EMPTY=\0MALFORMED\0"
+            )
+        );
+        assert!(ProcessData::parse_environ(b"").is_empty());
+    }
+
+    #[test]
+    fn process_state_round_trip() {
+        for state in ['R', 'S', 'D', 'T', 't', 'Z', 'X', 'I', 'P'] {
+            assert_eq!(state, ProcessState::from_stat_char(state).as_char());
         }
+        assert_eq!(ProcessState::Unknown, ProcessState::from_stat_char('W'));
+    }
 
-        return_map
+    #[test]
+    fn security_context_confinement() {
+        for (security_context, confined) in [
+            (
+                "unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023",
+                false,
+            ),
+            ("system_u:system_r:kernel_t:s0", false),
+            ("system_u:system_r:httpd_t:s0", true),
+            ("unconfined", false),
+            ("/usr/sbin/cupsd (enforce)", true),
+            ("/usr/bin/man (complain)", false),
+            ("firefox (unconfined)", false),
+        ] {
+            let process_data = ProcessData {
+                security_context: Some(security_context.to_string()),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                Some(confined),
+                process_data.is_confined(),
+                "{security_context}"
+            );
+        }
     }
 
-    fn nvidia_gpu_stats(pid: i32, pci_slot: PciSlot) -> Result<GpuUsageStats> {
-        let this_process_stats = NVIDIA_PROCESSES_STATS
-            .read()
-            .unwrap()
-            .get(&pci_slot)
-            .context("couldn't find GPU with this PCI slot")?
-            .iter()
-            .filter(|process| process.pid == pid as u32)
-            .map(|stats| (stats.sm_util, stats.enc_util, stats.dec_util))
-            .reduce(|acc, curr| (acc.0 + curr.0, acc.1 + curr.1, acc.2 + curr.2));
+    #[test]
+    fn scheduling_policy() {
+        let process_data = ProcessData::from_proc_contents(1234, proc_files()).unwrap();
+        assert_eq!(SchedulingPolicy::Other, process_data.scheduling_policy);
+        assert_eq!(0, process_data.realtime_priority);
 
-        let this_process_mem_stats: u64 = NVIDIA_PROCESS_INFOS
-            .read()
-            .unwrap()
-            .get(&pci_slot)
-            .context("couldn't find GPU with this PCI slot")?
-            .iter()
-            .filter(|process| process.pid == pid as u32)
-            .map(|stats| match stats.used_gpu_memory {
-                UsedGpuMemory::Unavailable => 0,
-                UsedGpuMemory::Used(bytes) => bytes,
-            })
-            .sum();
+        let files = ProcFiles {
+            stat: STAT.replace(" 17 3 0 0 0 25 ", " 17 3 50 1 0 25 "),
+            ..proc_files()
+        };
+        let process_data = ProcessData::from_proc_contents(1234, files).unwrap();
+        assert_eq!(SchedulingPolicy::Fifo, process_data.scheduling_policy);
+        assert_eq!(50, process_data.realtime_priority);
+        assert!(process_data.scheduling_policy.is_realtime());
+    }
 
-        let gpu_stats = GpuUsageStats {
-            gfx: this_process_stats.unwrap_or_default().0 as u64,
-            mem: this_process_mem_stats,
-            enc: this_process_stats.unwrap_or_default().1 as u64,
-            dec: this_process_stats.unwrap_or_default().2 as u64,
-            nvidia: true,
+    #[test]
+    fn io_priority_raw() {
+        let io_priority = IoPriority {
+            class: IoPriorityClass::BestEffort,
+            level: 7,
         };
-        Ok(gpu_stats)
+        assert_eq!((2 << 13) | 7, io_priority.as_raw());
+        assert_eq!(io_priority, IoPriority::from_raw(io_priority.as_raw()));
+
+        assert_eq!(IoPriority::default(), IoPriority::from_raw(0));
+        assert_eq!(IoPriorityClass::Idle, IoPriority::from_raw(3 << 13).class);
     }
 
-    fn nvidia_process_infos() -> HashMap<PciSlot, Vec<ProcessInfo>> {
-        let mut return_map = HashMap::new();
+    #[test]
+    fn io_priority_effective() {
+        let effective = IoPriority::default().effective(Niceness::try_new(19).unwrap());
+        assert_eq!(
+            IoPriority {
+                class: IoPriorityClass::BestEffort,
+                level: 7
+            },
+            effective
+        );
+        assert_eq!(
+            4,
+            IoPriority::default().effective(Niceness::default()).level
+        );
 
-        for (pci_slot, gpu) in NVML_DEVICES.iter() {
-            let mut comp_gfx_stats = gpu.running_graphics_processes().unwrap_or_default();
-            comp_gfx_stats.extend(gpu.running_compute_processes().unwrap_or_default());
+        let idle = IoPriority {
+            class: IoPriorityClass::Idle,
+            level: 0,
+        };
+        assert_eq!(idle, idle.effective(Niceness::default()));
+    }
 
-            return_map.insert(pci_slot.to_owned(), comp_gfx_stats);
-        }
+    #[test]
+    fn descendant_pids() {
+        let processes: Vec<_> = [
+            (1, 0),
+            (100, 1),
+            (101, 100),
+            (102, 100),
+            (103, 101),
+            (200, 1),
+        ]
+        .into_iter()
+        .map(|(pid, parent_pid)| ProcessData {
+            pid,
+            parent_pid,
+            ..Default::default()
+        })
+        .collect();
 
-        return_map
+        assert_eq!(
+            vec![101, 102, 103],
+            ProcessData::descendant_pids(100, &processes)
+        );
+        assert_eq!(
+            Vec::<libc::pid_t>::new(),
+            ProcessData::descendant_pids(200, &processes)
+        );
     }
 
-    fn nvidia_process_stats() -> HashMap<PciSlot, Vec<ProcessUtilizationSample>> {
-        let mut return_map = HashMap::new();
+    #[test]
+    fn nvidia_fdinfo() {
+        let fdinfo = concat!(
+            "pos:\t0\n",
+            "flags:\t02100002\n",
+            "drm-driver:\tnvidia-drm\n",
+            "drm-pdev:\t0000:01:00.0\n",
+            "drm-client-id:\t7\n",
+            "drm-engine-gfx:\t4000 ns\n",
+            "drm-engine-compute:\t1000 ns\n",
+            "drm-engine-nvenc0:\t300 ns\n",
+            "drm-engine-nvenc1:\t200 ns\n",
+            "drm-engine-nvdec0:\t600 ns\n",
+            "drm-total-vram0:\t256 MiB\n",
+            "drm-resident-vram0:\t128 MiB\n",
+        );
 
-        for (pci_slot, gpu) in NVML_DEVICES.iter() {
-            return_map.insert(
-                pci_slot.to_owned(),
-                gpu.process_utilization_stats(
-                    unix_as_millis()
-                        .saturating_mul(1000)
-                        .saturating_sub(5_000_000),
-                )
-                .unwrap_or_default(),
-            );
-        }
+        let (gpu_identifier, stats) = ProcessData::read_gpu_fdinfo(fdinfo).unwrap();
 
-        return_map
+        assert_eq!(
+            GpuIdentifier::PciSlot(PciSlot::new(0, 0x1, 0, 0)),
+            gpu_identifier
+        );
+        assert_eq!(
+            GpuUsageStats {
+                gfx: 5000,
+                mem: 256 * 1024 * 1024,
+                enc: 500,
+                dec: 600,
+                nvidia: false,
+                total_cycles: 0,
+            },
+            stats
+        );
+
+        // without any engine usage, the fdinfo is ignored so that NVML is used instead
+        let fdinfo_without_usage = concat!(
+            "pos:\t0\n",
+            "drm-driver:\tnvidia-drm\n",
+            "drm-pdev:\t0000:01:00.0\n",
+            "drm-client-id:\t7\n",
+        );
+        assert!(ProcessData::read_gpu_fdinfo(fdinfo_without_usage).is_err());
     }
-}
 
-pub fn unix_as_millis() -> u64 {
-    SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
+    #[test]
+    fn nouveau_fdinfo() {
+        let fdinfo = concat!(
+            "pos:\t0\n",
+            "drm-driver:\tnouveau\n",
+            "drm-pdev:\t0000:01:00.0\n",
+            "drm-client-id:\t3\n",
+            "drm-total-vram:\t2048 KiB\n",
+            "drm-shared-vram:\t0\n",
+            "drm-resident-vram:\t2048 KiB\n",
+        );
+
+        let (_, stats) = ProcessData::read_gpu_fdinfo(fdinfo).unwrap();
+
+        assert_eq!(2048 * 1024, stats.mem);
+        assert!(!stats.nvidia);
+    }
+
+    #[test]
+    fn xe_fdinfo() {
+        let fdinfo = |cycles_rcs, cycles_vcs, total_cycles| {
+            format!(
+                concat!(
+                    "drm-driver:\txe\n",
+                    "drm-client-id:\t10\n",
+                    "drm-pdev:\t0000:03:00.0\n",
+                    "drm-total-gtt:\t80 KiB\n",
+                    "drm-total-vram0:\t2 MiB\n",
+                    "drm-cycles-rcs:\t{}\n",
+                    "drm-total-cycles-rcs:\t{}\n",
+                    "drm-cycles-bcs:\t0\n",
+                    "drm-total-cycles-bcs:\t{}\n",
+                    "drm-cycles-vcs:\t{}\n",
+                    "drm-total-cycles-vcs:\t{}\n",
+                    "drm-engine-capacity-vcs:\t2\n",
+                ),
+                cycles_rcs, total_cycles, total_cycles, cycles_vcs, total_cycles
+            )
+        };
+
+        let (gpu_identifier, previous) =
+            ProcessData::read_gpu_fdinfo(&fdinfo(1_000, 0, 10_000)).unwrap();
+        let (_, current) = ProcessData::read_gpu_fdinfo(&fdinfo(6_000, 4_000, 20_000)).unwrap();
+
+        assert_eq!(
+            GpuIdentifier::PciSlot(PciSlot::new(0, 0x3, 0, 0)),
+            gpu_identifier
+        );
+        assert_eq!(80 * 1024 + 2 * 1024 * 1024, current.mem);
+        assert_eq!(20_000, current.total_cycles);
+        assert_eq!(
+            Some(0.5),
+            current.cycles_fraction_since(&previous, GpuEngine::Graphics)
+        );
+        // the cycles of both video engines are accumulated
+        assert_eq!(
+            Some(0.2),
+            current.cycles_fraction_since(&previous, GpuEngine::Encode)
+        );
+        assert_eq!(
+            None,
+            GpuUsageStats::default().cycles_fraction_since(&previous, GpuEngine::Graphics)
+        );
+    }
+
+    #[test]
+    fn slow_entry_skipped_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let slow_reads = Arc::new(AtomicUsize::new(0));
+
+        let entries = ["/proc/1", "/proc/2", "/proc/3"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let counter = Arc::clone(&slow_reads);
+        let mut read = ProcessData::read_entries_with_deadline(
+            entries,
+            Duration::from_millis(50),
+            move |entry| {
+                if entry.ends_with("2") {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(300));
+                }
+                Ok(entry.clone())
+            },
+        );
+        read.sort();
+
+        assert_eq!(
+            vec![PathBuf::from("/proc/1"), PathBuf::from("/proc/3")],
+            read
+        );
+
+        // give the abandoned worker time to finish, it must not have picked up the slow entry again
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(1, slow_reads.load(Ordering::SeqCst));
+    }
 }
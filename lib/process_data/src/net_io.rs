@@ -0,0 +1,374 @@
+//! Per-process network traffic accounting.
+//!
+//! The kernel doesn't keep track of how many bytes a process has sent or received over the network, only of how many
+//! bytes went through each socket. The sockets' counters are queried through the `sock_diag` netlink interface once
+//! per scan and then attributed to processes by resolving the `socket:[<inode>]` links in `/proc/<pid>/fd`.
+//!
+//! Limitations:
+//! - only TCP sockets have byte counters, UDP and other protocols aren't accounted for
+//! - only sockets in the network namespace of the scanning process are found, so processes in containers with their
+//!   own network namespace show no traffic
+//! - the counters only cover the sockets that are currently open, so the sum for a process drops once it closes a
+//!   socket. Rates should therefore be computed using saturating differences.
+//! - sockets shared between processes (e.g. inherited across `fork()`) are accounted to each of them
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+use anyhow::{bail, Context, Result};
+
+const NETLINK_SOCK_DIAG: libc::c_int = 4;
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+/// Length of `struct inet_diag_req_v2`
+const INET_DIAG_REQ_LEN: usize = 56;
+/// Length of `struct inet_diag_msg`
+const INET_DIAG_MSG_LEN: usize = 72;
+/// Offset of `idiag_inode` within `struct inet_diag_msg`
+const INET_DIAG_MSG_INODE: usize = 68;
+/// Attribute carrying a `struct tcp_info`
+const INET_DIAG_INFO: u16 = 2;
+
+/// Offset of `tcpi_bytes_acked` within `struct tcp_info` (Linux 4.1+)
+const TCP_INFO_BYTES_ACKED: usize = 120;
+/// Offset of `tcpi_bytes_received` within `struct tcp_info` (Linux 4.1+)
+const TCP_INFO_BYTES_RECEIVED: usize = 128;
+
+const RECV_BUFFER_LEN: usize = 32 * 1024;
+
+static SOCKET_TRAFFIC: LazyLock<RwLock<HashMap<u64, SocketTraffic>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Bytes that went through a socket (or all sockets of a process) since it was opened
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct SocketTraffic {
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+}
+
+/// Queries the traffic of all TCP sockets and caches it for `process_traffic()`. This should be called once per scan
+/// before the processes are read.
+///
+/// # Errors
+///
+/// Will return `Err` if the `sock_diag` netlink interface is unavailable, the cache is cleared in that case
+pub fn refresh_socket_traffic() -> Result<()> {
+    let traffic = query_tcp_traffic();
+
+    let mut cache = SOCKET_TRAFFIC.write().unwrap();
+    cache.clear();
+    cache.extend(traffic.as_ref().into_iter().flatten());
+
+    traffic.map(|_| ())
+}
+
+/// Sums up the traffic of all sockets the process at `proc_path` has open, using the socket counters queried by the
+/// last `refresh_socket_traffic()` call. `None` if the process' file descriptors can't be read (e.g. due to missing
+/// privileges) or no socket counters are available.
+pub fn process_traffic<P: AsRef<Path>>(proc_path: P) -> Option<SocketTraffic> {
+    let cache = SOCKET_TRAFFIC.read().unwrap();
+    if cache.is_empty() {
+        return None;
+    }
+
+    let mut traffic = SocketTraffic::default();
+    for entry in std::fs::read_dir(proc_path.as_ref().join("fd"))
+        .ok()?
+        .flatten()
+    {
+        let Some(inode) = std::fs::read_link(entry.path())
+            .ok()
+            .and_then(|target| socket_inode(target.to_str()?))
+        else {
+            continue;
+        };
+
+        if let Some(socket_traffic) = cache.get(&inode) {
+            traffic.sent_bytes = traffic.sent_bytes.saturating_add(socket_traffic.sent_bytes);
+            traffic.received_bytes = traffic
+                .received_bytes
+                .saturating_add(socket_traffic.received_bytes);
+        }
+    }
+
+    Some(traffic)
+}
+
+/// Extracts the inode from the target of a file descriptor link to a socket, e.g. `socket:[123456]`
+fn socket_inode(link_target: &str) -> Option<u64> {
+    link_target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+fn query_tcp_traffic() -> Result<HashMap<u64, SocketTraffic>> {
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            NETLINK_SOCK_DIAG,
+        )
+    };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error()).context("unable to open sock_diag socket");
+    }
+
+    let result = [libc::AF_INET, libc::AF_INET6]
+        .into_iter()
+        .try_fold(HashMap::new(), |mut traffic, family| {
+            dump_tcp_sockets(fd, family as u8, &mut traffic).map(|_| traffic)
+        });
+
+    unsafe { libc::close(fd) };
+
+    result
+}
+
+fn dump_tcp_sockets(
+    fd: libc::c_int,
+    family: u8,
+    traffic: &mut HashMap<u64, SocketTraffic>,
+) -> Result<()> {
+    let request = dump_request(family);
+    let sent = unsafe { libc::send(fd, request.as_ptr().cast(), request.len(), 0) };
+    if sent < 0 {
+        return Err(std::io::Error::last_os_error()).context("unable to send sock_diag request");
+    }
+
+    let mut buffer = vec![0_u8; RECV_BUFFER_LEN];
+    loop {
+        let received = unsafe { libc::recv(fd, buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+        if received < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("unable to receive sock_diag response");
+        }
+
+        if parse_dump_response(&buffer[..received as usize], traffic)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Builds a `SOCK_DIAG_BY_FAMILY` dump request for all TCP sockets of the given address family that asks for their
+/// `struct tcp_info`
+fn dump_request(family: u8) -> Vec<u8> {
+    let len = NLMSG_HEADER_LEN + INET_DIAG_REQ_LEN;
+    let mut request = Vec::with_capacity(len);
+
+    // struct nlmsghdr
+    request.extend_from_slice(&(len as u32).to_ne_bytes());
+    request.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    request.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+    request.extend_from_slice(&1_u32.to_ne_bytes()); // sequence number
+    request.extend_from_slice(&0_u32.to_ne_bytes()); // port ID, 0 is the kernel
+
+    // struct inet_diag_req_v2
+    request.push(family);
+    request.push(libc::IPPROTO_TCP as u8);
+    request.push(1 << (INET_DIAG_INFO - 1)); // extensions to include
+    request.push(0); // padding
+    request.extend_from_slice(&u32::MAX.to_ne_bytes()); // all TCP states
+    request.resize(len, 0); // struct inet_diag_sockid, zeroed to match every socket
+
+    request
+}
+
+/// Parses one datagram of a dump response into `traffic`, returning whether the dump is complete
+fn parse_dump_response(
+    mut buffer: &[u8],
+    traffic: &mut HashMap<u64, SocketTraffic>,
+) -> Result<bool> {
+    while buffer.len() >= NLMSG_HEADER_LEN {
+        let len = read_u32(buffer, 0).context("truncated netlink header")? as usize;
+        let message_type = read_u16(buffer, 4).context("truncated netlink header")?;
+
+        if len < NLMSG_HEADER_LEN || len > buffer.len() {
+            bail!("invalid netlink message length {len}");
+        }
+
+        match message_type {
+            NLMSG_DONE => return Ok(true),
+            NLMSG_ERROR => {
+                let errno = read_u32(buffer, NLMSG_HEADER_LEN).unwrap_or_default() as i32;
+                return Err(std::io::Error::from_raw_os_error(-errno))
+                    .context("sock_diag request failed");
+            }
+            SOCK_DIAG_BY_FAMILY => {
+                if let Some((inode, socket_traffic)) =
+                    parse_diag_message(&buffer[NLMSG_HEADER_LEN..len])
+                {
+                    traffic.insert(inode, socket_traffic);
+                }
+            }
+            _ => (),
+        }
+
+        buffer = buffer.get(align(len)..).unwrap_or_default();
+    }
+
+    Ok(false)
+}
+
+/// Parses a `struct inet_diag_msg` and its attributes, `None` if it doesn't contain a `struct tcp_info` recent enough
+/// to have byte counters
+fn parse_diag_message(message: &[u8]) -> Option<(u64, SocketTraffic)> {
+    let inode = u64::from(read_u32(message, INET_DIAG_MSG_INODE)?);
+
+    let mut attributes = message.get(INET_DIAG_MSG_LEN..)?;
+    while attributes.len() >= 4 {
+        let len = read_u16(attributes, 0)? as usize;
+        let attribute_type = read_u16(attributes, 2)?;
+        if len < 4 {
+            return None;
+        }
+
+        if attribute_type == INET_DIAG_INFO {
+            let tcp_info = attributes.get(4..len)?;
+            return Some((
+                inode,
+                SocketTraffic {
+                    sent_bytes: read_u64(tcp_info, TCP_INFO_BYTES_ACKED)?,
+                    received_bytes: read_u64(tcp_info, TCP_INFO_BYTES_RECEIVED)?,
+                },
+            ));
+        }
+
+        attributes = attributes.get(align(len)..)?;
+    }
+
+    None
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u16(buffer: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        buffer.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        buffer.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(buffer: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        buffer.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        dump_request, parse_dump_response, socket_inode, SocketTraffic, INET_DIAG_INFO,
+        INET_DIAG_MSG_INODE, INET_DIAG_MSG_LEN, NLMSG_DONE, SOCK_DIAG_BY_FAMILY,
+        TCP_INFO_BYTES_ACKED, TCP_INFO_BYTES_RECEIVED,
+    };
+
+    fn netlink_message(message_type: u16, payload: &[u8]) -> Vec<u8> {
+        let mut message = Vec::new();
+        message.extend_from_slice(&((16 + payload.len()) as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(&[0; 10]);
+        message.extend_from_slice(payload);
+        message
+    }
+
+    fn diag_message(inode: u32, sent_bytes: u64, received_bytes: u64) -> Vec<u8> {
+        let mut tcp_info = vec![0; 232];
+        tcp_info[TCP_INFO_BYTES_ACKED..TCP_INFO_BYTES_ACKED + 8]
+            .copy_from_slice(&sent_bytes.to_ne_bytes());
+        tcp_info[TCP_INFO_BYTES_RECEIVED..TCP_INFO_BYTES_RECEIVED + 8]
+            .copy_from_slice(&received_bytes.to_ne_bytes());
+
+        let mut message = vec![0; INET_DIAG_MSG_LEN];
+        message[INET_DIAG_MSG_INODE..INET_DIAG_MSG_INODE + 4].copy_from_slice(&inode.to_ne_bytes());
+        message.extend_from_slice(&((4 + tcp_info.len()) as u16).to_ne_bytes());
+        message.extend_from_slice(&INET_DIAG_INFO.to_ne_bytes());
+        message.extend_from_slice(&tcp_info);
+        message
+    }
+
+    #[test]
+    fn valid_socket_inode() {
+        assert_eq!(Some(123456), socket_inode("socket:[123456]"));
+        assert_eq!(None, socket_inode("pipe:[123456]"));
+        assert_eq!(None, socket_inode("/dev/null"));
+    }
+
+    #[test]
+    fn dump_request_length() {
+        let request = dump_request(libc::AF_INET6 as u8);
+
+        assert_eq!(72, request.len());
+        assert_eq!(72, u32::from_ne_bytes(request[0..4].try_into().unwrap()));
+        assert_eq!(libc::AF_INET6 as u8, request[16]);
+    }
+
+    #[test]
+    fn parse_dump() {
+        let mut buffer = netlink_message(SOCK_DIAG_BY_FAMILY, &diag_message(1234, 1_000, 2_000));
+        buffer.extend(netlink_message(
+            SOCK_DIAG_BY_FAMILY,
+            &diag_message(5678, 3_000, 4_000),
+        ));
+
+        let mut traffic = HashMap::new();
+        assert!(!parse_dump_response(&buffer, &mut traffic).unwrap());
+        assert!(parse_dump_response(&netlink_message(NLMSG_DONE, &[0; 4]), &mut traffic).unwrap());
+
+        assert_eq!(
+            HashMap::from([
+                (
+                    1234,
+                    SocketTraffic {
+                        sent_bytes: 1_000,
+                        received_bytes: 2_000
+                    }
+                ),
+                (
+                    5678,
+                    SocketTraffic {
+                        sent_bytes: 3_000,
+                        received_bytes: 4_000
+                    }
+                ),
+            ]),
+            traffic
+        );
+    }
+
+    #[test]
+    fn parse_dump_old_tcp_info() {
+        // tcp_info of kernels older than 4.1 doesn't have byte counters yet
+        let mut message = diag_message(1234, 1_000, 2_000);
+        message.truncate(INET_DIAG_MSG_LEN + 4 + 104);
+        let attribute_len = (4 + 104) as u16;
+        message[INET_DIAG_MSG_LEN..INET_DIAG_MSG_LEN + 2]
+            .copy_from_slice(&attribute_len.to_ne_bytes());
+
+        let mut traffic = HashMap::new();
+        parse_dump_response(
+            &netlink_message(SOCK_DIAG_BY_FAMILY, &message),
+            &mut traffic,
+        )
+        .unwrap();
+
+        assert!(traffic.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Resolves the open file descriptors of a process, e.g. to find leaked descriptors or the process keeping a file or
+//! mount point busy.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Socket tables in `/proc/<pid>/net` along with the protocol name and the index of their inode column
+const SOCKET_TABLES: &[(&str, &str, usize)] = &[
+    ("tcp", "TCP", 9),
+    ("tcp6", "TCP", 9),
+    ("udp", "UDP", 9),
+    ("udp6", "UDP", 9),
+    ("raw", "Raw", 9),
+    ("raw6", "Raw", 9),
+    ("unix", "Unix", 6),
+    ("netlink", "Netlink", 9),
+    ("packet", "Packet", 8),
+];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpenFileKind {
+    /// A file system path, i.e. a regular file, directory or device node
+    Path,
+    /// A socket, along with its protocol (e.g. `TCP` or `Unix`) if it could be determined
+    Socket(Option<String>),
+    Pipe,
+    /// A file without an inode of its own, e.g. an eventfd or inotify instance, along with its type
+    AnonInode(String),
+    Other,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenFile {
+    pub fd: u32,
+    /// What the file descriptor's link in `/proc/<pid>/fd` points to, e.g. `/home/user/file.txt` or `socket:[1234]`
+    pub target: String,
+    pub kind: OpenFileKind,
+    /// Whether the file has been deleted while it's still held open
+    pub deleted: bool,
+}
+
+/// Returns the open file descriptors of the process with the given PID, sorted by their number.
+///
+/// # Errors
+///
+/// Will return `Err` if the process doesn't exist (anymore) or the file descriptors of the process can't be read
+/// due to missing privileges
+pub fn open_files(pid: libc::pid_t) -> Result<Vec<OpenFile>> {
+    let proc_path = Path::new("/proc").join(pid.to_string());
+
+    let entries = std::fs::read_dir(proc_path.join("fd"))
+        .with_context(|| format!("unable to read open file descriptors of {pid}"))?;
+
+    let socket_protocols = socket_protocols(&proc_path);
+
+    let mut open_files: Vec<OpenFile> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_str()?.parse().ok()?;
+            // the file descriptor might have been closed in the meantime
+            let target = std::fs::read_link(entry.path()).ok()?;
+            Some(OpenFile::new(
+                fd,
+                target.to_string_lossy().to_string(),
+                &socket_protocols,
+            ))
+        })
+        .collect();
+
+    open_files.sort_by_key(|open_file| open_file.fd);
+
+    Ok(open_files)
+}
+
+impl OpenFile {
+    fn new(fd: u32, target: String, socket_protocols: &HashMap<u64, &str>) -> Self {
+        let (kind, deleted) = if target.starts_with('/') {
+            (OpenFileKind::Path, target.ends_with(" (deleted)"))
+        } else if let Some(inode) = bracketed_inode(&target, "socket:") {
+            let protocol = socket_protocols.get(&inode).map(ToString::to_string);
+            (OpenFileKind::Socket(protocol), false)
+        } else if bracketed_inode(&target, "pipe:").is_some() {
+            (OpenFileKind::Pipe, false)
+        } else if let Some(anon_type) = target.strip_prefix("anon_inode:") {
+            let anon_type = anon_type.trim_start_matches('[').trim_end_matches(']');
+            (OpenFileKind::AnonInode(anon_type.to_string()), false)
+        } else {
+            (OpenFileKind::Other, false)
+        };
+
+        Self {
+            fd,
+            target,
+            kind,
+            deleted,
+        }
+    }
+}
+
+fn bracketed_inode(target: &str, prefix: &str) -> Option<u64> {
+    target
+        .strip_prefix(prefix)?
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Maps the inodes of the sockets in the network namespace of the process at `proc_path` to their protocol
+fn socket_protocols(proc_path: &Path) -> HashMap<u64, &'static str> {
+    let mut protocols = HashMap::new();
+
+    for (file, protocol, inode_column) in SOCKET_TABLES {
+        if let Ok(table) = std::fs::read_to_string(proc_path.join("net").join(file)) {
+            for inode in socket_table_inodes(&table, *inode_column) {
+                protocols.insert(inode, *protocol);
+            }
+        }
+    }
+
+    protocols
+}
+
+fn socket_table_inodes(table: &str, inode_column: usize) -> impl Iterator<Item = u64> + '_ {
+    table
+        .lines()
+        .skip(1) // header
+        .filter_map(move |line| line.split_whitespace().nth(inode_column)?.parse().ok())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{socket_table_inodes, OpenFile, OpenFileKind};
+
+    #[test]
+    fn open_file_kinds() {
+        let socket_protocols = HashMap::from([(1234, "TCP")]);
+
+        let kinds: Vec<_> = [
+            "/home/user/file.txt",
+            "/tmp/gone (deleted)",
+            "socket:[1234]",
+            "socket:[5678]",
+            "pipe:[91011]",
+            "anon_inode:[eventfd]",
+            "anon_inode:inotify",
+            "/dev/null",
+            "net:[4026531840]",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(fd, target)| {
+            let open_file = OpenFile::new(fd as u32, target.to_string(), &socket_protocols);
+            (open_file.kind, open_file.deleted)
+        })
+        .collect();
+
+        assert_eq!(
+            vec![
+                (OpenFileKind::Path, false),
+                (OpenFileKind::Path, true),
+                (OpenFileKind::Socket(Some("TCP".into())), false),
+                (OpenFileKind::Socket(None), false),
+                (OpenFileKind::Pipe, false),
+                (OpenFileKind::AnonInode("eventfd".into()), false),
+                (OpenFileKind::AnonInode("inotify".into()), false),
+                (OpenFileKind::Path, false),
+                (OpenFileKind::Other, false),
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn tcp_table_inodes() {
+        let table = concat!(
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n",
+            "   0: 3500007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000   991        0 19573 1 0000000000000000 100 0 0 10 5\n",
+            "   1: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 27613 1 0000000000000000 100 0 0 10 0\n",
+        );
+
+        assert_eq!(
+            vec![19573, 27613],
+            socket_table_inodes(table, 9).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unix_table_inodes() {
+        let table = concat!(
+            "Num       RefCount Protocol Flags    Type St Inode Path\n",
+            "0000000000000000: 00000002 00000000 00010000 0001 01 21548 /run/systemd/private\n",
+            "0000000000000000: 00000003 00000000 00000000 0001 03 33297\n",
+        );
+
+        assert_eq!(
+            vec![21548, 33297],
+            socket_table_inodes(table, 6).collect::<Vec<_>>()
+        );
+    }
+}
@@ -6,21 +6,51 @@ use serde::{Deserialize, Serialize};
     Debug, Clone, Copy, Serialize, Deserialize, Default, Hash, PartialEq, Eq, PartialOrd, Ord,
 )]
 pub struct PciSlot {
-    pub domain: u16,
+    // domains above 0xffff are used e.g. by Intel's Volume Management Device
+    pub domain: u32,
     pub bus: u8,
     pub number: u8,
     pub function: u8,
 }
 
 impl PciSlot {
-    pub fn new(domain: u16, bus: u8, number: u8, function: u8) -> Self {
+    pub fn new(domain: u32, bus: u8, device: u8, function: u8) -> Self {
         Self {
             domain,
             bus,
-            number,
+            number: device,
             function,
         }
     }
+
+    /// Like `new()`, but makes sure that `device` and `function` fit into the 5 and 3 bits PCI provides for them
+    pub fn try_new(domain: u32, bus: u8, device: u8, function: u8) -> Result<Self, ParseError> {
+        if device > 0x1f {
+            return Err(ParseError("device number out of range".into()));
+        }
+
+        if function > 0x7 {
+            return Err(ParseError("function number out of range".into()));
+        }
+
+        Ok(Self::new(domain, bus, device, function))
+    }
+
+    pub fn domain(&self) -> u32 {
+        self.domain
+    }
+
+    pub fn bus(&self) -> u8 {
+        self.bus
+    }
+
+    pub fn device(&self) -> u8 {
+        self.number
+    }
+
+    pub fn function(&self) -> u8 {
+        self.function
+    }
 }
 
 #[derive(Debug)]
@@ -63,7 +93,7 @@ impl FromStr for PciSlot {
             return Err(ParseError("amount of ':' ≠ 2".into()));
         }
 
-        let domain = u16::from_str_radix(colon_split[0], 16)
+        let domain = u32::from_str_radix(colon_split[0], 16)
             .or(Err(ParseError("unable to parse domain".into())))?;
 
         let bus = u8::from_str_radix(colon_split[1], 16)
@@ -75,12 +105,9 @@ impl FromStr for PciSlot {
         let function = u8::from_str_radix(dot_split[1], 16)
             .or(Err(ParseError("unable to parse function".into())))?;
 
-        Ok(PciSlot {
-            domain,
-            bus,
-            number,
-            function,
-        })
+        // like `new()`, this doesn't check the ranges of device and function so that everything that's displayed can
+        // be parsed again, use `try_new()` for that
+        Ok(PciSlot::new(domain, bus, number, function))
     }
 }
 
@@ -115,4 +142,27 @@ mod test {
         let pci_id_str = "0000:01:fe.3";
         assert_eq!(pci_id_str, pci_id.to_string());
     }
+
+    #[test]
+    fn pci_id_round_trip() {
+        let from_new = PciSlot::new(0x10000, 0xe1, 0x1f, 0x7);
+        let from_try_new = PciSlot::try_new(0x10000, 0xe1, 0x1f, 0x7).unwrap();
+        let from_str = PciSlot::from_str("10000:e1:1f.7").unwrap();
+
+        assert_eq!(from_new, from_try_new);
+        assert_eq!(from_new, from_str);
+        assert_eq!(from_new, PciSlot::from_str(&from_new.to_string()).unwrap());
+
+        assert_eq!(0x10000, from_str.domain());
+        assert_eq!(0xe1, from_str.bus());
+        assert_eq!(0x1f, from_str.device());
+        assert_eq!(0x7, from_str.function());
+    }
+
+    #[test]
+    fn pci_id_out_of_range() {
+        assert!(PciSlot::try_new(0x0, 0x1, 0x20, 0x0).is_err());
+        assert!(PciSlot::try_new(0x0, 0x1, 0x0, 0x8).is_err());
+        assert!(PciSlot::from_str("0000:01:fe.3").is_ok());
+    }
 }
@@ -0,0 +1,526 @@
+//! Incremental process tracking through the netlink process events connector.
+//!
+//! Instead of listing `/proc` on every scan, `ProcessTracker` subscribes to the fork, exec and exit events the kernel
+//! broadcasts and keeps its set of PIDs up to date from them. The statistics of every live process still have to be
+//! read on every scan since CPU time, memory usage and I/O change continuously, but the directory walk is gone.
+//! Processes that haven't been forked off or executed anything since the last scan keep what can only change on
+//! exec (their Flatpak and AppImage detection and security context) from the previous scan instead of looking it up
+//! again.
+//!
+//! Limitations:
+//! - subscribing requires `CAP_NET_ADMIN` on kernels older than 6.6, without it `ProcessTracker` falls back to
+//!   listing `/proc` on every scan
+//! - events are dropped if they arrive faster than they're read (e.g. during fork bombs), the tracker notices this
+//!   and lists `/proc` once to resynchronize
+
+use std::collections::{BTreeSet, HashMap};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::{unix_as_millis, ProcessData, ScanOptions};
+
+#[cfg(feature = "log")]
+use log::debug;
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+const NETLINK_CONNECTOR: libc::c_int = 11;
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const NLMSG_DONE: u16 = 3;
+/// Length of `struct cn_msg` without its payload
+const CN_MSG_LEN: usize = 20;
+/// Offset of `event_data` within `struct proc_event`
+const PROC_EVENT_DATA: usize = 16;
+
+const PROC_EVENT_FORK: u32 = 0x0000_0001;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+const RECV_BUFFER_LEN: usize = 32 * 1024;
+/// Requested receive buffer size of the socket, so that bursts of events don't overflow it between two scans
+const SOCKET_BUFFER_LEN: libc::c_int = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ProcEvent {
+    /// A new process has been created, events about new threads are left out
+    Fork {
+        parent: libc::pid_t,
+        child: libc::pid_t,
+    },
+    /// A process has replaced its executable
+    Exec(libc::pid_t),
+    /// A process has exited, events about exiting threads are left out
+    Exit(libc::pid_t),
+}
+
+/// Subscription to the process events of the kernel
+#[derive(Debug)]
+pub struct ProcEventListener {
+    fd: OwnedFd,
+}
+
+impl ProcEventListener {
+    /// Subscribes to the process events of the kernel.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the connector is unavailable or we lack the privileges to subscribe to it
+    pub fn new() -> Result<Self> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                NETLINK_CONNECTOR,
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("unable to open process events connector socket");
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let buffer_len = SOCKET_BUFFER_LEN;
+        unsafe {
+            // best-effort, the default buffer is just a bit more likely to overflow
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVBUF,
+                std::ptr::addr_of!(buffer_len).cast(),
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            );
+        }
+
+        let mut address: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        address.nl_pid = 0; // let the kernel pick a port ID
+        address.nl_groups = CN_IDX_PROC;
+        let bound = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                std::ptr::addr_of!(address).cast(),
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("unable to bind process events connector socket");
+        }
+
+        let request = listen_request();
+        let sent = unsafe { libc::send(fd.as_raw_fd(), request.as_ptr().cast(), request.len(), 0) };
+        if sent < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("unable to subscribe to process events");
+        }
+
+        Ok(Self { fd })
+    }
+
+    /// Returns the events that arrived since the last call without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading from the socket failed, most notably with `ENOBUFS` if events have been dropped
+    /// because they weren't read quickly enough
+    pub fn drain(&self) -> Result<Vec<ProcEvent>> {
+        let mut events = Vec::new();
+        let mut buffer = vec![0_u8; RECV_BUFFER_LEN];
+
+        loop {
+            let received = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                let error = std::io::Error::last_os_error();
+                if error.kind() == std::io::ErrorKind::WouldBlock {
+                    return Ok(events);
+                }
+                return Err(error).context("unable to receive process events");
+            }
+
+            parse_events(&buffer[..received as usize], &mut events);
+        }
+    }
+}
+
+/// Keeps track of the running processes using a `ProcEventListener` if possible and reads their data
+#[derive(Debug)]
+pub struct ProcessTracker {
+    listener: Option<ProcEventListener>,
+    pids: BTreeSet<libc::pid_t>,
+    /// Whether `pids` has to be rebuilt by listing `/proc`, e.g. because events have been lost
+    resync: bool,
+    /// The data of the previous scan of the processes that haven't been forked off, executed anything or exited since,
+    /// see `ProcessData::refresh_from_path()`
+    cache: HashMap<libc::pid_t, ProcessData>,
+    options: ScanOptions,
+}
+
+impl ProcessTracker {
+    pub fn new(options: ScanOptions) -> Self {
+        let listener = ProcEventListener::new()
+            .inspect_err(|error| {
+                debug!("Process events are unavailable, falling back to listing /proc: {error:#}");
+            })
+            .ok();
+
+        Self {
+            listener,
+            pids: BTreeSet::new(),
+            resync: true,
+            cache: HashMap::new(),
+            options,
+        }
+    }
+
+    /// Returns whether processes are tracked through events rather than by listing `/proc` on every scan
+    pub fn is_event_driven(&self) -> bool {
+        self.listener.is_some()
+    }
+
+    /// Reads the data of all running processes, stamping every process with the same timestamp (which is also
+    /// returned) like `ProcessData::all_process_data_snapshot()` does.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `/proc` has to be listed and that fails
+    pub fn snapshot(&mut self) -> Result<(u64, Vec<ProcessData>)> {
+        self.update_pids()?;
+
+        let timestamp = unix_as_millis();
+
+        let entries: Vec<PathBuf> = self
+            .pids
+            .iter()
+            .map(|pid| PathBuf::from(format!("/proc/{pid}")))
+            .collect();
+
+        let mut process_data = ProcessData::read_entries_with_cache(
+            entries,
+            std::mem::take(&mut self.cache),
+            &self.options,
+        );
+        for data in &mut process_data {
+            data.timestamp = timestamp;
+        }
+
+        // without events we can't tell whether a process has executed something else in the meantime
+        if self.listener.is_some() {
+            self.cache = process_data
+                .iter()
+                .map(|data| (data.pid, data.clone()))
+                .collect();
+        }
+
+        // forget about the processes that have failed to be read because they're gone, their exit events will arrive
+        // later or have been lost
+        if process_data.len() != self.pids.len() {
+            let read_pids: BTreeSet<_> = process_data.iter().map(|data| data.pid).collect();
+            self.pids.retain(|pid| {
+                read_pids.contains(pid) || Path::new("/proc").join(pid.to_string()).exists()
+            });
+        }
+
+        Ok((timestamp, process_data))
+    }
+
+    fn update_pids(&mut self) -> Result<()> {
+        if let Some(listener) = &self.listener {
+            match listener.drain() {
+                Ok(events) => {
+                    apply_events(&mut self.pids, &mut self.cache, &events, process_has_exited);
+                }
+                Err(error) => {
+                    debug!("Lost process events, listing /proc again: {error:#}");
+                    self.resync = true;
+                }
+            }
+        }
+
+        if self.resync || self.listener.is_none() {
+            self.pids = ProcessData::all_pids()?.into_iter().collect();
+            // the lost events might have included execs
+            self.cache.clear();
+            // events that arrived while listing /proc are already reflected in it, applying them next time is
+            // harmless since forks and exits are idempotent for a set
+            self.resync = false;
+        }
+
+        Ok(())
+    }
+}
+
+fn apply_events(
+    pids: &mut BTreeSet<libc::pid_t>,
+    cache: &mut HashMap<libc::pid_t, ProcessData>,
+    events: &[ProcEvent],
+    has_exited: impl Fn(libc::pid_t) -> bool,
+) {
+    for event in events {
+        match event {
+            ProcEvent::Fork { child, .. } => {
+                pids.insert(*child);
+                // the PID might have been reused
+                cache.remove(child);
+            }
+            ProcEvent::Exit(pid) => {
+                if has_exited(*pid) {
+                    pids.remove(pid);
+                }
+                cache.remove(pid);
+            }
+            // the process may have become a Flatpak or AppImage or changed its security context, so it has to be
+            // read from scratch
+            ProcEvent::Exec(pid) => {
+                cache.remove(pid);
+            }
+        }
+    }
+}
+
+/// Returns whether the process whose thread group leader has exited is gone. The leader might have only called
+/// `pthread_exit()` while the other threads keep running, so the process is only gone if `/proc/<pid>` doesn't exist
+/// anymore or it's a zombie without any other threads that hasn't been reaped yet.
+fn process_has_exited(pid: libc::pid_t) -> bool {
+    match std::fs::read_to_string(format!("/proc/{pid}/status")) {
+        Ok(status) => is_exited_status(&status),
+        Err(_) => true,
+    }
+}
+
+fn is_exited_status(status: &str) -> bool {
+    let value = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(str::trim)
+    };
+
+    value("State:").is_some_and(|state| state.starts_with('Z')) && value("Threads:") == Some("1")
+}
+
+/// Builds the `PROC_CN_MCAST_LISTEN` request that starts the delivery of process events
+fn listen_request() -> Vec<u8> {
+    let len = NLMSG_HEADER_LEN + CN_MSG_LEN + 4;
+    let mut request = Vec::with_capacity(len);
+
+    // struct nlmsghdr
+    request.extend_from_slice(&(len as u32).to_ne_bytes());
+    request.extend_from_slice(&NLMSG_DONE.to_ne_bytes());
+    request.extend_from_slice(&0_u16.to_ne_bytes()); // flags
+    request.extend_from_slice(&0_u32.to_ne_bytes()); // sequence number
+    request.extend_from_slice(&std::process::id().to_ne_bytes());
+
+    // struct cn_msg
+    request.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+    request.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+    request.extend_from_slice(&0_u32.to_ne_bytes()); // sequence number
+    request.extend_from_slice(&0_u32.to_ne_bytes()); // acknowledgement
+    request.extend_from_slice(&4_u16.to_ne_bytes()); // payload length
+    request.extend_from_slice(&0_u16.to_ne_bytes()); // flags
+
+    // enum proc_cn_mcast_op
+    request.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+
+    request
+}
+
+/// Parses the `struct proc_event`s in one datagram into `events`, ignoring the ones about threads
+fn parse_events(mut buffer: &[u8], events: &mut Vec<ProcEvent>) {
+    while buffer.len() >= NLMSG_HEADER_LEN {
+        let Some(len) = read_u32(buffer, 0).map(|len| len as usize) else {
+            return;
+        };
+        if len < NLMSG_HEADER_LEN || len > buffer.len() {
+            return;
+        }
+
+        if let Some(event) = buffer
+            .get(NLMSG_HEADER_LEN + CN_MSG_LEN..len)
+            .and_then(parse_event)
+        {
+            events.push(event);
+        }
+
+        buffer = buffer.get(align(len)..).unwrap_or_default();
+    }
+}
+
+fn parse_event(proc_event: &[u8]) -> Option<ProcEvent> {
+    let what = read_u32(proc_event, 0)?;
+    let data = proc_event.get(PROC_EVENT_DATA..)?;
+
+    // every event starts with the PID (i.e. thread ID) and TGID (i.e. process ID) it's about
+    let pid = read_i32(data, 0)?;
+    let tgid = read_i32(data, 4)?;
+
+    match what {
+        PROC_EVENT_FORK => {
+            let child_pid = read_i32(data, 8)?;
+            let child_tgid = read_i32(data, 12)?;
+            (child_pid == child_tgid).then_some(ProcEvent::Fork {
+                parent: tgid,
+                child: child_tgid,
+            })
+        }
+        PROC_EVENT_EXEC => Some(ProcEvent::Exec(tgid)),
+        PROC_EVENT_EXIT => (pid == tgid).then_some(ProcEvent::Exit(tgid)),
+        _ => None,
+    }
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        buffer.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_i32(buffer: &[u8], offset: usize) -> Option<i32> {
+    Some(i32::from_ne_bytes(
+        buffer.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashMap};
+
+    use pretty_assertions::assert_eq;
+
+    use crate::ProcessData;
+
+    use super::{
+        apply_events, is_exited_status, listen_request, parse_events, ProcEvent, CN_MSG_LEN,
+        NLMSG_HEADER_LEN, PROC_EVENT_DATA, PROC_EVENT_EXEC, PROC_EVENT_EXIT, PROC_EVENT_FORK,
+    };
+
+    fn event_message(what: u32, data: &[i32]) -> Vec<u8> {
+        let mut proc_event = vec![0; PROC_EVENT_DATA];
+        proc_event[0..4].copy_from_slice(&what.to_ne_bytes());
+        for value in data {
+            proc_event.extend_from_slice(&value.to_ne_bytes());
+        }
+
+        let len = NLMSG_HEADER_LEN + CN_MSG_LEN + proc_event.len();
+        let mut message = Vec::new();
+        message.extend_from_slice(&(len as u32).to_ne_bytes());
+        message.extend_from_slice(&[0; NLMSG_HEADER_LEN - 4 + CN_MSG_LEN]);
+        message.extend_from_slice(&proc_event);
+        message
+    }
+
+    #[test]
+    fn listen_request_length() {
+        let request = listen_request();
+
+        assert_eq!(40, request.len());
+        assert_eq!(40, u32::from_ne_bytes(request[0..4].try_into().unwrap()));
+    }
+
+    #[test]
+    fn parse_proc_events() {
+        let mut buffer = event_message(PROC_EVENT_FORK, &[100, 100, 200, 200]);
+        // new thread of process 200
+        buffer.extend(event_message(PROC_EVENT_FORK, &[200, 200, 201, 200]));
+        buffer.extend(event_message(PROC_EVENT_EXEC, &[200, 200]));
+        // exiting thread of process 200
+        buffer.extend(event_message(PROC_EVENT_EXIT, &[201, 200, 0, 0, 0, 0]));
+        buffer.extend(event_message(PROC_EVENT_EXIT, &[200, 200, 0, 0, 100, 100]));
+
+        let mut events = Vec::new();
+        parse_events(&buffer, &mut events);
+
+        assert_eq!(
+            vec![
+                ProcEvent::Fork {
+                    parent: 100,
+                    child: 200
+                },
+                ProcEvent::Exec(200),
+                ProcEvent::Exit(200),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn exited_status() {
+        assert!(is_exited_status(
+            "Name:\tbash\nState:\tZ (zombie)\nThreads:\t1\n"
+        ));
+        // the thread group leader has called pthread_exit()
+        assert!(!is_exited_status(
+            "Name:\tbash\nState:\tZ (zombie)\nThreads:\t4\n"
+        ));
+        assert!(!is_exited_status(
+            "Name:\tbash\nState:\tS (sleeping)\nThreads:\t1\n"
+        ));
+    }
+
+    #[test]
+    fn apply_proc_events() {
+        let mut pids = BTreeSet::from([1, 100, 400, 500]);
+        let mut cache: HashMap<_, _> = [1, 100, 200, 400]
+            .into_iter()
+            .map(|pid| {
+                (
+                    pid,
+                    ProcessData {
+                        pid,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+
+        apply_events(
+            &mut pids,
+            &mut cache,
+            &[
+                ProcEvent::Fork {
+                    parent: 100,
+                    child: 200,
+                },
+                ProcEvent::Exec(200),
+                ProcEvent::Fork {
+                    parent: 1,
+                    child: 300,
+                },
+                ProcEvent::Exit(100),
+                ProcEvent::Exec(400),
+                // only the thread group leader has exited
+                ProcEvent::Exit(500),
+            ],
+            |pid| pid != 500,
+        );
+
+        assert_eq!(BTreeSet::from([1, 200, 300, 400, 500]), pids);
+        assert_eq!(
+            BTreeSet::from([1]),
+            cache.keys().copied().collect::<BTreeSet<_>>()
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Fixed-capacity ring buffer of samples (e.g. CPU or GPU usage, power draw) taken once per refresh. Once it's full,
+/// pushing a new sample drops the oldest one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageHistory {
+    capacity: usize,
+    samples: VecDeque<f64>,
+}
+
+impl UsageHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, sample: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Iterates over the samples from the oldest to the newest one
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = f64> + ExactSizeIterator + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Average of all samples, `None` if there are none
+    pub fn average(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            None
+        } else {
+            Some(self.samples.iter().sum::<f64>() / self.samples.len() as f64)
+        }
+    }
+
+    /// Averages every `bucket_size` consecutive samples (from the oldest to the newest one) into one value, e.g. to
+    /// fit a long history into a narrow sparkline. The last bucket may contain fewer samples.
+    pub fn averaged(&self, bucket_size: usize) -> Vec<f64> {
+        let bucket_size = bucket_size.max(1);
+
+        self.samples
+            .iter()
+            .copied()
+            .collect::<Vec<_>>()
+            .chunks(bucket_size)
+            .map(|bucket| bucket.iter().sum::<f64>() / bucket.len() as f64)
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::UsageHistory;
+
+    #[test]
+    fn push_drops_oldest() {
+        let mut history = UsageHistory::new(3);
+        for sample in [1.0, 2.0, 3.0, 4.0] {
+            history.push(sample);
+        }
+
+        assert_eq!(vec![2.0, 3.0, 4.0], history.iter().collect::<Vec<_>>());
+        assert_eq!(Some(4.0), history.latest());
+        assert_eq!(Some(3.0), history.average());
+    }
+
+    #[test]
+    fn averaged() {
+        let mut history = UsageHistory::new(8);
+        for sample in [0.0, 2.0, 4.0, 6.0, 8.0] {
+            history.push(sample);
+        }
+
+        assert_eq!(vec![1.0, 5.0, 8.0], history.averaged(2));
+        assert_eq!(None, UsageHistory::new(8).average());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut history = UsageHistory::new(0);
+        history.push(1.0);
+
+        assert!(history.is_empty());
+    }
+}
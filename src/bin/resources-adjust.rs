@@ -5,7 +5,21 @@ use nix::{
     unistd::Pid,
 };
 
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+
 fn main() {
+    // resources-adjust --io-priority <PID> <IOPRIO>
+    if env::args().nth(1).as_deref() == Some("--io-priority") {
+        if let (Some(pid), Some(ioprio)) = (
+            env::args().nth(2).and_then(|s| s.trim().parse().ok()),
+            env::args().nth(3).and_then(|s| s.trim().parse().ok()),
+        ) {
+            adjust_io_priority(pid, ioprio);
+            std::process::exit(0)
+        }
+        std::process::exit(255);
+    }
+
     if let Some(pid) = env::args().nth(1).and_then(|s| s.trim().parse().ok()) {
         if let Some(nice) = env::args().nth(2).and_then(|s| s.trim().parse().ok()) {
             if let Some(mask) = env::args().nth(3) {
@@ -17,6 +31,21 @@ fn main() {
                     }
                 }
 
+                // the OOM score adjustment is shared by all threads of a process
+                if let Some(oom_score_adj) = env::args().nth(4).and_then(|s| s.trim().parse().ok())
+                {
+                    adjust_oom_score(pid, oom_score_adj);
+                }
+
+                // unlike the niceness, the scheduling policy is only applied to the main thread since other threads
+                // (e.g. the realtime threads of audio servers) often deliberately use a policy of their own
+                if let (Some(policy), Some(realtime_priority)) = (
+                    env::args().nth(5).and_then(|s| s.trim().parse().ok()),
+                    env::args().nth(6).and_then(|s| s.trim().parse().ok()),
+                ) {
+                    adjust_scheduling_policy(pid, policy, realtime_priority);
+                }
+
                 adjust(pid, nice, &cpu_set);
 
                 // find tasks that belong to this process
@@ -34,6 +63,81 @@ fn main() {
     std::process::exit(255);
 }
 
+fn adjust_oom_score(pid: i32, oom_score_adj: i16) {
+    let path = PathBuf::from("/proc/")
+        .join(pid.to_string())
+        .join("oom_score_adj");
+
+    // lowering the adjustment requires privileges, so don't touch it if it's unchanged
+    let current = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|current| current.trim().parse::<i16>().ok());
+    if current == Some(oom_score_adj) {
+        return;
+    }
+
+    if let Err(error) = std::fs::write(&path, oom_score_adj.to_string()) {
+        std::process::exit(error.raw_os_error().unwrap_or(255))
+    }
+}
+
+/// Sets the I/O priority of every thread of the process, since like the niceness it's a per-thread attribute
+fn adjust_io_priority(pid: i32, ioprio: libc::c_int) {
+    let tasks_path = PathBuf::from("/proc/").join(pid.to_string()).join("task");
+    let thread_ids = std::fs::read_dir(tasks_path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_string_lossy().parse::<i32>().ok());
+
+    for id in std::iter::once(pid).chain(thread_ids) {
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, id, ioprio) } != 0 {
+            std::process::exit(
+                std::io::Error::last_os_error()
+                    .raw_os_error()
+                    .unwrap_or(255),
+            )
+        }
+    }
+}
+
+fn adjust_scheduling_policy(pid: i32, policy: i32, realtime_priority: i32) {
+    let mut current_param = libc::sched_param { sched_priority: 0 };
+    let current_policy = unsafe { libc::sched_getscheduler(pid) };
+    unsafe { libc::sched_getparam(pid, &mut current_param) };
+
+    // the policy passed to us comes from /proc/<pid>/stat, which doesn't include the reset-on-fork flag (that e.g.
+    // rtkit always sets), so compare without it and keep it when setting the policy
+    let reset_on_fork = current_policy >= 0 && current_policy & libc::SCHED_RESET_ON_FORK != 0;
+    let current_policy = if reset_on_fork {
+        current_policy & !libc::SCHED_RESET_ON_FORK
+    } else {
+        current_policy
+    };
+
+    // switching to a realtime policy requires privileges and SCHED_DEADLINE can't be set this way at all, so don't
+    // touch it if it's unchanged
+    if current_policy == policy && current_param.sched_priority == realtime_priority {
+        return;
+    }
+
+    let policy = if reset_on_fork {
+        policy | libc::SCHED_RESET_ON_FORK
+    } else {
+        policy
+    };
+    let param = libc::sched_param {
+        sched_priority: realtime_priority,
+    };
+    if unsafe { libc::sched_setscheduler(pid, policy, &param) } != 0 {
+        std::process::exit(
+            std::io::Error::last_os_error()
+                .raw_os_error()
+                .unwrap_or(255),
+        )
+    }
+}
+
 fn adjust(id: i32, nice: i32, cpu_set: &CpuSet) {
     unsafe {
         libc::setpriority(libc::PRIO_PROCESS, id as u32, nice);
@@ -47,5 +151,10 @@ fn adjust(id: i32, nice: i32, cpu_set: &CpuSet) {
         std::process::exit(error)
     }
 
-    let _ = sched_setaffinity(Pid::from_raw(id), cpu_set);
+    // threads may end in the meantime, that's not a failure
+    if let Err(errno) = sched_setaffinity(Pid::from_raw(id), cpu_set) {
+        if errno != nix::errno::Errno::ESRCH {
+            std::process::exit(errno as i32)
+        }
+    }
 }
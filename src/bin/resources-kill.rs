@@ -10,7 +10,8 @@ fn main() {
                 "CONT" => signal::Signal::SIGCONT,
                 "TERM" => signal::Signal::SIGTERM,
                 "KILL" => signal::Signal::SIGKILL,
-                _ => std::process::exit(254),
+                // any other signal by its full name, e.g. "SIGHUP"
+                other => other.parse().unwrap_or_else(|_| std::process::exit(254)),
             };
             let result = signal::kill(Pid::from_raw(pid), Some(signal));
             if let Err(errno) = result {
@@ -1,6 +1,9 @@
 use anyhow::Result;
-use process_data::ProcessData;
+use process_data::open_files::open_files;
+use process_data::proc_events::ProcessTracker;
+use process_data::{ProcessData, ScanOptions};
 use ron::ser::PrettyConfig;
+use serde::Serialize;
 use std::io::{Read, Write};
 
 use clap::Parser;
@@ -15,35 +18,67 @@ struct Args {
     /// Use Rusty Object Notation (use this only for debugging this binary on its own, Resources won't be able to decode RON)
     #[arg(short, long, default_value_t = false)]
     ron: bool,
+
+    /// Output the open file descriptors of the process with the given PID once and then exit
+    #[arg(long, value_name = "PID")]
+    open_files: Option<libc::pid_t>,
+
+    /// Output the environment variables of the process with the given PID once and then exit
+    #[arg(long, value_name = "PID")]
+    environ: Option<libc::pid_t>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    if let Some(pid) = args.open_files {
+        match open_files(pid) {
+            Ok(open_files) => write_encoded(&encode(&open_files, args.ron)?)?,
+            Err(error) => exit_with_error(&error),
+        }
+        return Ok(());
+    }
+
+    if let Some(pid) = args.environ {
+        match ProcessData::environ(pid) {
+            Ok(environ) => write_encoded(&encode(&environ, args.ron)?)?,
+            Err(error) => exit_with_error(&error),
+        }
+        return Ok(());
+    }
+
     if args.once {
-        output(args.ron)?;
+        let (_, data) = ProcessData::all_process_data_snapshot_with_options(&scan_options())?;
+        output(&data, args.ron)?;
         return Ok(());
     }
 
+    // we're asked for new data periodically, so follow the processes coming and going in the meantime instead of
+    // listing /proc every time
+    let mut tracker = ProcessTracker::new(scan_options());
+
     loop {
         let mut buffer = [0; 1];
 
         std::io::stdin().read_exact(&mut buffer)?;
 
-        output(args.ron)?;
+        let (_, data) = tracker.snapshot()?;
+        output(&data, args.ron)?;
     }
 }
 
-fn output(ron: bool) -> Result<()> {
-    let data = ProcessData::all_process_data()?;
+/// The optional data Resources displays
+fn scan_options() -> ScanOptions {
+    ScanOptions {
+        oom_score: true,
+        io_priority: true,
+        frozen: true,
+        ..Default::default()
+    }
+}
 
-    let encoded = if ron {
-        ron::ser::to_string_pretty(&data, PrettyConfig::default())?
-            .as_bytes()
-            .to_vec()
-    } else {
-        rmp_serde::to_vec(&data)?
-    };
+fn output(data: &[ProcessData], ron: bool) -> Result<()> {
+    let encoded = encode(&data, ron)?;
 
     let len_byte_array = encoded.len().to_le_bytes();
 
@@ -57,3 +92,34 @@ fn output(ron: bool) -> Result<()> {
     handle.flush()?;
     Ok(())
 }
+
+fn encode<T: Serialize>(data: &T, ron: bool) -> Result<Vec<u8>> {
+    if ron {
+        Ok(ron::ser::to_string_pretty(data, PrettyConfig::default())?
+            .as_bytes()
+            .to_vec())
+    } else {
+        // serialize structs as maps rather than positionally, so that Resources can decode our output even if either
+        // side knows about fields the other one doesn't
+        Ok(rmp_serde::to_vec_named(data)?)
+    }
+}
+
+/// Writes a one-off response without the length prefix, the reader simply reads until we exit
+fn write_encoded(encoded: &[u8]) -> Result<()> {
+    let mut handle = std::io::stdout().lock();
+    handle.write_all(encoded)?;
+    handle.flush()?;
+    Ok(())
+}
+
+/// Exits with the errno of the underlying I/O error (if any), so that Resources can tell whether it should retry
+/// with elevated privileges
+fn exit_with_error(error: &anyhow::Error) -> ! {
+    eprintln!("{error:#}");
+    let code = error
+        .downcast_ref::<std::io::Error>()
+        .and_then(std::io::Error::raw_os_error)
+        .unwrap_or(1);
+    std::process::exit(code)
+}
@@ -1,4 +1,5 @@
 pub mod app_dialog;
+pub mod open_files_dialog;
 pub mod process_dialog;
 pub mod process_options_dialog;
 pub mod settings_dialog;
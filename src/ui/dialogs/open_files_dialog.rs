@@ -0,0 +1,159 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::gio;
+use gtk::glib::{self, MainContext, clone};
+use log::{trace, warn};
+use process_data::open_files::{OpenFile, OpenFileKind};
+
+use crate::config::PROFILE;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::pages::processes::process_entry::ProcessEntry;
+use crate::utils::process::Process;
+
+mod imp {
+
+    use super::*;
+
+    use gtk::CompositeTemplate;
+
+    #[derive(Debug, CompositeTemplate, Default)]
+    #[template(resource = "/net/nokyan/Resources/ui/dialogs/open_files_dialog.ui")]
+    pub struct ResOpenFilesDialog {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub error_status_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub name: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub files_group: TemplateChild<adw::PreferencesGroup>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResOpenFilesDialog {
+        const NAME: &'static str = "ResOpenFilesDialog";
+        type Type = super::ResOpenFilesDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        // You must call `Widget`'s `init_template()` within `instance_init()`.
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ResOpenFilesDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Devel Profile
+            if PROFILE == "Devel" {
+                obj.add_css_class("devel");
+            }
+        }
+    }
+
+    impl WidgetImpl for ResOpenFilesDialog {}
+    impl WindowImpl for ResOpenFilesDialog {}
+    impl AdwDialogImpl for ResOpenFilesDialog {}
+}
+
+glib::wrapper! {
+    pub struct ResOpenFilesDialog(ObjectSubclass<imp::ResOpenFilesDialog>)
+        @extends gtk::Widget, adw::Dialog;
+}
+
+impl Default for ResOpenFilesDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResOpenFilesDialog {
+    pub fn new() -> Self {
+        trace!("Creating ResOpenFilesDialog GObject…");
+        glib::Object::new::<Self>()
+    }
+
+    /// Shows the file descriptors the process had open at the time this is called, they're not refreshed afterwards
+    pub fn init(&self, process: &ProcessEntry) {
+        trace!("Setting up ResOpenFilesDialog widgets…");
+
+        let imp = self.imp();
+
+        imp.name.set_label(&process.name());
+        imp.stack.set_visible_child_name("loading");
+
+        let pid = process.pid();
+
+        // this may ask for elevated privileges, so don't block the UI in the meantime
+        MainContext::default().spawn_local(clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                match gio::spawn_blocking(move || Process::open_files(pid)).await {
+                    Ok(Ok(open_files)) => this.show_open_files(&open_files),
+                    Ok(Err(error)) => {
+                        warn!("Unable to get open files of {pid}: {error}");
+                        this.show_error();
+                    }
+                    Err(_) => this.show_error(),
+                }
+            }
+        ));
+    }
+
+    fn show_open_files(&self, open_files: &[OpenFile]) {
+        let imp = self.imp();
+
+        imp.files_group.set_description(Some(&ni18n_f(
+            "{} open file descriptor",
+            "{} open file descriptors",
+            open_files.len() as u32,
+            &[&open_files.len().to_string()],
+        )));
+
+        for open_file in open_files {
+            let row = adw::ActionRow::builder()
+                .title(&open_file.target)
+                .title_selectable(true)
+                .use_markup(false)
+                .subtitle(i18n_f(
+                    "File descriptor {} · {}",
+                    &[
+                        &open_file.fd.to_string(),
+                        &kind_description(&open_file.kind),
+                    ],
+                ))
+                .build();
+            row.set_tooltip_text(Some(&open_file.target));
+
+            imp.files_group.add(&row);
+        }
+
+        imp.stack.set_visible_child_name("files");
+    }
+
+    fn show_error(&self) {
+        let imp = self.imp();
+
+        imp.error_status_page.set_description(Some(&i18n(
+            "The process may have ended or the required privileges were not granted.",
+        )));
+        imp.stack.set_visible_child_name("error");
+    }
+}
+
+fn kind_description(kind: &OpenFileKind) -> String {
+    match kind {
+        OpenFileKind::Path => i18n("File"),
+        OpenFileKind::Socket(Some(protocol)) => i18n_f("{} socket", &[protocol]),
+        OpenFileKind::Socket(None) => i18n("Socket"),
+        OpenFileKind::Pipe => i18n("Pipe"),
+        OpenFileKind::AnonInode(anon_type) => i18n_f("Anonymous inode ({})", &[anon_type]),
+        OpenFileKind::Other => i18n("Other"),
+    }
+}
@@ -1,14 +1,20 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib::{self, GString};
-use log::trace;
+use gtk::gio;
+use gtk::glib::{self, GString, MainContext, clone};
+use log::{trace, warn};
+use process_data::{IoPriority, Niceness};
 
 use crate::config::PROFILE;
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f};
+use crate::ui::pages::io_priority_description;
 use crate::ui::pages::processes::process_entry::ProcessEntry;
+use crate::utils::process::Process;
 use crate::utils::units::{convert_speed, convert_storage, format_time};
 
 mod imp {
 
+    use std::cell::{Cell, RefCell};
+
     use super::*;
 
     use gtk::CompositeTemplate;
@@ -58,6 +64,25 @@ mod imp {
         pub cgroup: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub containerized: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub oom_score: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub io_priority: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub security_context: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub confinement_badge: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub environment_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub show_environment_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub environment_search_entry: TemplateChild<gtk::SearchEntry>,
+
+        /// Rows of the environment variables along with their lowercase `KEY=value` for searching
+        pub environment_rows: RefCell<Vec<(adw::ActionRow, String)>>,
+
+        pub pid: Cell<libc::pid_t>,
     }
 
     #[glib::object_subclass]
@@ -112,6 +137,7 @@ impl ResProcessDialog {
 
     pub fn init<S: AsRef<str>>(&self, process: &ProcessEntry, user: S) {
         self.setup_widgets(process, user.as_ref());
+        self.setup_signals();
     }
 
     pub fn setup_widgets(&self, process: &ProcessEntry, user: &str) {
@@ -148,9 +174,108 @@ impl ResProcessDialog {
 
         imp.containerized.set_subtitle(&process.containerization());
 
+        if let Some(security_context) = process.security_context() {
+            imp.security_context.set_subtitle(&security_context);
+            imp.security_context
+                .set_tooltip_text(Some(&security_context));
+
+            if process.confined() {
+                imp.confinement_badge.set_label(&i18n("Confined"));
+                imp.confinement_badge.add_css_class("success");
+            } else {
+                imp.confinement_badge.set_label(&i18n("Unconfined"));
+                imp.confinement_badge.add_css_class("warning");
+            }
+            imp.confinement_badge.set_visible(true);
+        } else {
+            imp.security_context.set_subtitle(&i18n("N/A"));
+        }
+
+        imp.pid.set(process.pid());
+
         self.update(process);
     }
 
+    pub fn setup_signals(&self) {
+        trace!("Setting up ResProcessDialog signals…");
+
+        let imp = self.imp();
+
+        // reading the environment may ask for elevated privileges, so it's only done on request
+        imp.show_environment_button.connect_clicked(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |button| {
+                button.set_sensitive(false);
+
+                let pid = this.imp().pid.get();
+
+                MainContext::default().spawn_local(clone!(
+                    #[weak]
+                    this,
+                    async move {
+                        match gio::spawn_blocking(move || Process::environ(pid)).await {
+                            Ok(Ok(environ)) => this.show_environment(&environ),
+                            Ok(Err(error)) => {
+                                warn!("Unable to get environment variables of {pid}: {error}");
+                                this.show_environment_error();
+                            }
+                            Err(_) => this.show_environment_error(),
+                        }
+                    }
+                ));
+            }
+        ));
+
+        imp.environment_search_entry.connect_search_changed(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |search_entry| {
+                let search_string = search_entry.text().to_lowercase();
+
+                for (row, variable) in this.imp().environment_rows.borrow().iter() {
+                    row.set_visible(variable.contains(&search_string));
+                }
+            }
+        ));
+    }
+
+    fn show_environment(&self, environ: &[(String, String)]) {
+        let imp = self.imp();
+
+        imp.show_environment_button.set_visible(false);
+        imp.environment_search_entry.set_visible(true);
+
+        if environ.is_empty() {
+            imp.environment_group
+                .set_description(Some(&i18n("This process has no environment variables")));
+            return;
+        }
+
+        let mut environment_rows = imp.environment_rows.borrow_mut();
+        for (key, value) in environ {
+            let row = adw::ActionRow::builder()
+                .title(key)
+                .subtitle(value)
+                .subtitle_selectable(true)
+                .use_markup(false)
+                .build();
+            row.add_css_class("property");
+
+            imp.environment_group.add(&row);
+            environment_rows.push((row, format!("{key}={value}").to_lowercase()));
+        }
+    }
+
+    fn show_environment_error(&self) {
+        let imp = self.imp();
+
+        imp.show_environment_button.set_sensitive(true);
+        imp.environment_group.set_description(Some(&i18n(
+            "Unable to read the environment variables. The process may have ended or the required privileges were not granted.",
+        )));
+    }
+
     pub fn update(&self, process: &ProcessEntry) {
         trace!("Refreshing ResProcessDialog…");
 
@@ -213,5 +338,26 @@ impl ResProcessDialog {
 
         imp.system_cpu_time
             .set_subtitle(&format_time(process.system_cpu_time()));
+
+        if process.oom_score() == -1 {
+            imp.oom_score.set_subtitle(&i18n("N/A"));
+        } else {
+            imp.oom_score.set_subtitle(&i18n_f(
+                "{} (adjustment: {})",
+                &[
+                    &process.oom_score().to_string(),
+                    &process.oom_score_adj().to_string(),
+                ],
+            ));
+        }
+
+        if process.io_priority() == -1 {
+            imp.io_priority.set_subtitle(&i18n("N/A"));
+        } else {
+            imp.io_priority.set_subtitle(&io_priority_description(
+                IoPriority::from_raw(process.io_priority()),
+                Niceness::try_from(process.niceness()).unwrap_or_default(),
+            ));
+        }
     }
 }
@@ -1,17 +1,23 @@
 use crate::{
     config::PROFILE,
-    i18n::i18n_f,
+    i18n::{i18n, i18n_f},
     ui::{
-        pages::{NICE_TO_LABEL, processes::process_entry::ProcessEntry},
+        pages::{
+            NICE_TO_LABEL, SETTABLE_SCHEDULING_POLICIES, processes::process_entry::ProcessEntry,
+            scheduling_policy_name,
+        },
         window::Action,
     },
-    utils::settings::SETTINGS,
+    utils::{
+        cpu::{CoreType, get_core_types},
+        settings::SETTINGS,
+    },
 };
 use adw::{ToastOverlay, prelude::*, subclass::prelude::*};
 use async_channel::Sender;
 use gtk::glib::{self, MainContext, clone};
 use log::trace;
-use process_data::Niceness;
+use process_data::{Niceness, SchedulingPolicy};
 
 mod imp {
 
@@ -35,11 +41,19 @@ mod imp {
         #[template_child]
         pub affinity_row: TemplateChild<adw::ExpanderRow>,
         #[template_child]
+        pub oom_score_adj_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub scheduling_policy_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub realtime_priority_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
         pub select_all_button: TemplateChild<gtk::Button>,
 
         pub cpu_rows: RefCell<Vec<adw::SwitchRow>>,
 
         pub pid: Cell<libc::pid_t>,
+        pub scheduling_policy: Cell<SchedulingPolicy>,
+        pub realtime_priority: Cell<u32>,
     }
 
     #[glib::object_subclass]
@@ -119,6 +133,27 @@ impl ResProcessOptionsDialog {
         }
     }
 
+    /// Returns the scheduling policy and realtime priority to apply, which are the current ones if the process uses a
+    /// policy that can't be chosen here (i.e. `SCHED_DEADLINE`)
+    fn get_current_scheduling_policy(&self) -> (SchedulingPolicy, u32) {
+        let imp = self.imp();
+
+        if !imp.scheduling_policy_row.is_sensitive() {
+            return (imp.scheduling_policy.get(), imp.realtime_priority.get());
+        }
+
+        let scheduling_policy = SETTABLE_SCHEDULING_POLICIES
+            .get(imp.scheduling_policy_row.selected() as usize)
+            .copied()
+            .unwrap_or_default();
+
+        if scheduling_policy.is_realtime() {
+            (scheduling_policy, imp.realtime_priority_row.value() as u32)
+        } else {
+            (scheduling_policy, 0)
+        }
+    }
+
     pub fn setup_widgets(&self, process: &ProcessEntry) {
         trace!("Setting up ResProcessOptionsDialog widgets…");
 
@@ -134,15 +169,90 @@ impl ResProcessOptionsDialog {
                 .map_or(2, |(_, i)| *i),
         );
 
+        imp.oom_score_adj_row
+            .set_value(f64::from(process.oom_score_adj()));
+
+        let scheduling_policy = SchedulingPolicy::from_raw(process.scheduling_policy());
+        imp.scheduling_policy.set(scheduling_policy);
+        imp.realtime_priority.set(process.realtime_priority());
+
+        let policy_names = gtk::StringList::new(&[]);
+        for settable_policy in SETTABLE_SCHEDULING_POLICIES {
+            policy_names.append(&scheduling_policy_name(settable_policy));
+        }
+        imp.scheduling_policy_row.set_model(Some(&policy_names));
+
+        if let Some(position) = SETTABLE_SCHEDULING_POLICIES
+            .iter()
+            .position(|settable_policy| *settable_policy == scheduling_policy)
+        {
+            imp.scheduling_policy_row.set_selected(position as u32);
+        } else {
+            imp.scheduling_policy_row.set_sensitive(false);
+            imp.scheduling_policy_row.set_subtitle(&i18n_f(
+                "This process uses the {} policy, which can't be changed here",
+                &[&scheduling_policy_name(scheduling_policy)],
+            ));
+        }
+
+        imp.realtime_priority_row
+            .set_value(f64::from(process.realtime_priority().max(1)));
+        imp.realtime_priority_row.set_sensitive(
+            imp.scheduling_policy_row.is_sensitive() && scheduling_policy.is_realtime(),
+        );
+
         if SETTINGS.detailed_priority() {
             imp.priority_row.set_visible(false);
         } else {
             imp.nice_row.set_visible(false);
         }
 
+        let core_types = get_core_types();
+
+        // on hybrid CPUs, allow restricting the process to one kind of cores at once, e.g. pinning a game to the
+        // performance cores
+        if !core_types.is_empty() {
+            let core_type_row = adw::ActionRow::builder().title(i18n("Core Type")).build();
+
+            for (label, core_type) in [
+                (i18n("Performance"), CoreType::Performance),
+                (i18n("Efficiency"), CoreType::Efficiency),
+            ] {
+                let button = gtk::Button::builder()
+                    .label(label)
+                    .valign(gtk::Align::Center)
+                    .build();
+
+                button.connect_clicked(clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    #[strong]
+                    core_types,
+                    move |_| {
+                        for (i, switch_row) in this.imp().cpu_rows.borrow().iter().enumerate() {
+                            switch_row.set_active(
+                                core_types.get(i).copied().flatten() == Some(core_type),
+                            );
+                        }
+                    }
+                ));
+
+                core_type_row.add_suffix(&button);
+            }
+
+            imp.affinity_row.add_row(&core_type_row);
+        }
+
         for (i, affinity) in process.affinity().iter().enumerate() {
+            let subtitle = match core_types.get(i).copied().flatten() {
+                Some(CoreType::Performance) => i18n("Performance core"),
+                Some(CoreType::Efficiency) => i18n("Efficiency core"),
+                None => String::new(),
+            };
+
             let switch_row = adw::SwitchRow::builder()
                 .title(i18n_f("CPU {}", &[&(i + 1).to_string()]))
+                .subtitle(subtitle)
                 .active(*affinity)
                 .build();
 
@@ -176,6 +286,18 @@ impl ResProcessOptionsDialog {
 
         let imp = self.imp();
 
+        imp.scheduling_policy_row.connect_selected_notify(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_| {
+                let imp = this.imp();
+                let (scheduling_policy, _) = this.get_current_scheduling_policy();
+                // the realtime priority only applies to realtime scheduling policies
+                imp.realtime_priority_row
+                    .set_sensitive(scheduling_policy.is_realtime());
+            }
+        ));
+
         imp.select_all_button.connect_clicked(clone!(
             #[weak(rename_to = this)]
             self,
@@ -216,11 +338,16 @@ impl ResProcessOptionsDialog {
                             .map(adw::SwitchRow::is_active)
                             .collect();
 
+                        let (scheduling_policy, realtime_priority) =
+                            this.get_current_scheduling_policy();
                         let _ = sender
                             .send(Action::AdjustProcess(
                                 process.pid(),
                                 this.get_current_niceness(),
                                 affinity,
+                                imp.oom_score_adj_row.value() as i16,
+                                scheduling_policy,
+                                realtime_priority,
                                 process.name().to_string(),
                                 toast_overlay.clone(),
                             ))
@@ -1,9 +1,11 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib;
 use log::trace;
+use process_data::energy::RaplSampler;
 
 use crate::{
     config::PROFILE,
+    i18n::i18n,
     utils::settings::{Base, RefreshSpeed, SETTINGS, SidebarMeterType, TemperatureUnit},
 };
 
@@ -97,6 +99,16 @@ mod imp {
         pub processes_show_priority_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
         pub processes_show_swap_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub processes_show_major_faults_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub processes_show_involuntary_switches_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub processes_show_scheduling_policy_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub processes_show_realtime_priority_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub processes_show_power_usage_row: TemplateChild<adw::SwitchRow>,
 
         #[template_child]
         pub show_virtual_drives_row: TemplateChild<adw::SwitchRow>,
@@ -245,6 +257,28 @@ impl ResSettingsDialog {
             .set_active(SETTINGS.processes_show_system_cpu_time());
         imp.processes_show_swap_row
             .set_active(SETTINGS.processes_show_swap());
+        imp.processes_show_major_faults_row
+            .set_active(SETTINGS.processes_show_major_faults());
+        imp.processes_show_involuntary_switches_row
+            .set_active(SETTINGS.processes_show_involuntary_switches());
+        imp.processes_show_scheduling_policy_row
+            .set_active(SETTINGS.processes_show_scheduling_policy());
+        imp.processes_show_realtime_priority_row
+            .set_active(SETTINGS.processes_show_realtime_priority());
+        imp.processes_show_power_usage_row
+            .set_active(SETTINGS.processes_show_power_usage());
+
+        // the power usage is estimated from the energy counters of the CPUs, which only root can read on most systems
+        let rapl = RaplSampler::new();
+        if !rapl.is_readable() {
+            imp.processes_show_power_usage_row.set_sensitive(false);
+            imp.processes_show_power_usage_row
+                .set_subtitle(&if rapl.is_available() {
+                    i18n("Unavailable because the energy counters of the CPUs can only be read by root")
+                } else {
+                    i18n("Unavailable because the CPUs don't expose energy counters")
+                });
+        }
 
         imp.show_virtual_drives_row
             .set_active(SETTINGS.show_virtual_drives());
@@ -455,6 +489,31 @@ impl ResSettingsDialog {
                 let _ = SETTINGS.set_processes_show_swap(switch_row.is_active());
             });
 
+        imp.processes_show_major_faults_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_major_faults(switch_row.is_active());
+            });
+
+        imp.processes_show_involuntary_switches_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_involuntary_switches(switch_row.is_active());
+            });
+
+        imp.processes_show_scheduling_policy_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_scheduling_policy(switch_row.is_active());
+            });
+
+        imp.processes_show_realtime_priority_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_realtime_priority(switch_row.is_active());
+            });
+
+        imp.processes_show_power_usage_row
+            .connect_active_notify(|switch_row| {
+                let _ = SETTINGS.set_processes_show_power_usage(switch_row.is_active());
+            });
+
         imp.show_virtual_drives_row
             .connect_active_notify(|switch_row| {
                 let _ = SETTINGS.set_show_virtual_drives(switch_row.is_active());
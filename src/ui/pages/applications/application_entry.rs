@@ -186,6 +186,10 @@ impl ApplicationEntry {
             Containerization::None => i18n("No"),
             Containerization::Flatpak => i18n("Yes (Flatpak)"),
             Containerization::Snap => i18n("Yes (Snap)"),
+            Containerization::AppImage => i18n("Yes (AppImage)"),
+            Containerization::Docker => i18n("Yes (Docker)"),
+            Containerization::Podman => i18n("Yes (Podman)"),
+            Containerization::Lxc => i18n("Yes (LXC)"),
         };
 
         let this: Self = glib::Object::builder()
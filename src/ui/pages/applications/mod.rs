@@ -601,10 +601,9 @@ impl ResApplications {
                 let app_id = object.id().map(|gs| gs.to_string());
                 // filter out apps that have run before but don't anymore
                 if app_id.is_some() // don't try to filter out "System Processes"
-                    && !apps_context
+                    && apps_context
                         .get_app(&app_id)
-                        .unwrap()
-                        .is_running()
+                        .is_none_or(|app| !app.is_running())
                 {
                     if let Some((dialog_id, dialog)) = dialog_opt {
                         if dialog_id.as_deref() == app_id.as_deref() {
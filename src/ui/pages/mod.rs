@@ -1,8 +1,8 @@
 use std::{collections::HashMap, sync::LazyLock};
 
-use process_data::Niceness;
+use process_data::{IoPriority, IoPriorityClass, Niceness, SchedulingPolicy};
 
-use crate::i18n::pi18n;
+use crate::i18n::{i18n, i18n_f, pi18n};
 
 pub mod applications;
 pub mod battery;
@@ -64,3 +64,58 @@ pub static NICE_TO_LABEL: LazyLock<HashMap<Niceness, (String, u32)>> = LazyLock:
 
     hash_map
 });
+
+/// Scheduling policies that can be chosen in the process options, in the order they're listed in
+pub const SETTABLE_SCHEDULING_POLICIES: [SchedulingPolicy; 5] = [
+    SchedulingPolicy::Other,
+    SchedulingPolicy::Batch,
+    SchedulingPolicy::Idle,
+    SchedulingPolicy::Fifo,
+    SchedulingPolicy::RoundRobin,
+];
+
+pub fn scheduling_policy_name(scheduling_policy: SchedulingPolicy) -> String {
+    match scheduling_policy {
+        SchedulingPolicy::Other => i18n("Normal"),
+        SchedulingPolicy::Fifo => i18n("Realtime (FIFO)"),
+        SchedulingPolicy::RoundRobin => i18n("Realtime (Round Robin)"),
+        SchedulingPolicy::Batch => i18n("Batch"),
+        SchedulingPolicy::Idle => i18n("Idle"),
+        SchedulingPolicy::Deadline => i18n("Deadline"),
+        SchedulingPolicy::Unknown => i18n("Unknown"),
+    }
+}
+
+/// I/O priority classes that can be chosen for a process, in the order they're listed in
+pub const SETTABLE_IO_PRIORITY_CLASSES: [IoPriorityClass; 3] = [
+    IoPriorityClass::Realtime,
+    IoPriorityClass::BestEffort,
+    IoPriorityClass::Idle,
+];
+
+pub fn io_priority_class_name(class: IoPriorityClass) -> String {
+    match class {
+        IoPriorityClass::None => i18n("None"),
+        IoPriorityClass::Realtime => i18n("Realtime"),
+        IoPriorityClass::BestEffort => i18n("Best Effort"),
+        IoPriorityClass::Idle => i18n("Idle"),
+    }
+}
+
+/// Describes the I/O priority the kernel actually applies to a process with the given niceness
+pub fn io_priority_description(io_priority: IoPriority, niceness: Niceness) -> String {
+    let effective = io_priority.effective(niceness);
+    let class_name = io_priority_class_name(effective.class);
+
+    match io_priority.class {
+        IoPriorityClass::None => i18n_f(
+            "{} (level {}, derived from niceness)",
+            &[&class_name, &effective.level.to_string()],
+        ),
+        IoPriorityClass::Idle => class_name,
+        _ => i18n_f(
+            "{} (level {})",
+            &[&class_name, &effective.level.to_string()],
+        ),
+    }
+}
@@ -12,19 +12,24 @@ use gtk::{
     BitsetIter, ColumnView, ColumnViewColumn, EventControllerKey, FilterChange, ListItem,
     NumericSorter, SortType, StringSorter, Widget, gio,
 };
-use process_data::Niceness;
+use nix::sys::signal::Signal;
+use process_data::energy::RaplSampler;
+use process_data::{IoPriority, IoPriorityClass, Niceness, SchedulingPolicy};
 
 use crate::config::PROFILE;
 use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::ui::dialogs::open_files_dialog::ResOpenFilesDialog;
 use crate::ui::dialogs::process_dialog::ResProcessDialog;
 use crate::ui::dialogs::process_options_dialog::ResProcessOptionsDialog;
-use crate::ui::pages::NICE_TO_LABEL;
+use crate::ui::pages::{
+    NICE_TO_LABEL, SETTABLE_IO_PRIORITY_CLASSES, io_priority_class_name, scheduling_policy_name,
+};
 use crate::ui::window::{Action, MainWindow};
 use crate::utils::NUM_CPUS;
 use crate::utils::app::AppsContext;
 use crate::utils::process::ProcessAction;
 use crate::utils::settings::SETTINGS;
-use crate::utils::units::{convert_speed, convert_storage, format_time};
+use crate::utils::units::{convert_power, convert_speed, convert_storage, format_time};
 
 use self::process_entry::ProcessEntry;
 use self::process_name_cell::ResProcessNameCell;
@@ -252,6 +257,44 @@ mod imp {
                 },
             );
 
+            klass.install_action(
+                "processes.context-end-process-tree",
+                None,
+                move |res_processes, _, _| {
+                    if let Some(process_entry) =
+                        res_processes.imp().popped_over_process.borrow().as_ref()
+                    {
+                        res_processes
+                            .open_process_tree_action_dialog(process_entry, ProcessAction::TERM);
+                    }
+                },
+            );
+
+            klass.install_action(
+                "processes.context-kill-process-tree",
+                None,
+                move |res_processes, _, _| {
+                    if let Some(process_entry) =
+                        res_processes.imp().popped_over_process.borrow().as_ref()
+                    {
+                        res_processes
+                            .open_process_tree_action_dialog(process_entry, ProcessAction::KILL);
+                    }
+                },
+            );
+
+            klass.install_action(
+                "processes.context-send-signal",
+                None,
+                move |res_processes, _, _| {
+                    if let Some(process_entry) =
+                        res_processes.imp().popped_over_process.borrow().as_ref()
+                    {
+                        res_processes.open_signal_dialog(vec![process_entry.clone()]);
+                    }
+                },
+            );
+
             klass.install_action(
                 "processes.context-information",
                 None,
@@ -264,6 +307,18 @@ mod imp {
                 },
             );
 
+            klass.install_action(
+                "processes.context-open-files",
+                None,
+                move |res_processes, _, _| {
+                    if let Some(process_entry) =
+                        res_processes.imp().popped_over_process.borrow().as_ref()
+                    {
+                        res_processes.open_open_files_dialog(process_entry);
+                    }
+                },
+            );
+
             klass.install_action(
                 "processes.context-options",
                 None,
@@ -276,6 +331,18 @@ mod imp {
                 },
             );
 
+            klass.install_action(
+                "processes.context-io-priority",
+                None,
+                move |res_processes, _, _| {
+                    if let Some(process_entry) =
+                        res_processes.imp().popped_over_process.borrow().as_ref()
+                    {
+                        res_processes.open_io_priority_dialog(process_entry);
+                    }
+                },
+            );
+
             klass.install_action("processes.end-process", None, move |res_processes, _, _| {
                 let selected = res_processes.get_selected_process_entries();
                 if !selected.is_empty() {
@@ -316,6 +383,13 @@ mod imp {
                 },
             );
 
+            klass.install_action("processes.send-signal", None, move |res_processes, _, _| {
+                let selected = res_processes.get_selected_process_entries();
+                if !selected.is_empty() {
+                    res_processes.open_signal_dialog(selected);
+                }
+            });
+
             Self::bind_template(klass);
         }
 
@@ -463,6 +537,11 @@ impl ResProcesses {
         columns.push(self.add_system_cpu_time_column(&column_view));
         columns.push(self.add_priority_column(&column_view));
         columns.push(self.add_swap_column(&column_view));
+        columns.push(self.add_major_faults_column(&column_view));
+        columns.push(self.add_involuntary_switches_column(&column_view));
+        columns.push(self.add_scheduling_policy_column(&column_view));
+        columns.push(self.add_realtime_priority_column(&column_view));
+        columns.push(self.add_power_usage_column(&column_view));
 
         let store = gio::ListStore::new::<ProcessEntry>();
 
@@ -694,6 +773,132 @@ impl ResProcesses {
         *imp.open_info_dialog.borrow_mut() = Some((process.pid(), dialog));
     }
 
+    pub fn open_io_priority_dialog(&self, process: &ProcessEntry) {
+        let io_priority = if process.io_priority() == -1 {
+            IoPriority::default()
+        } else {
+            IoPriority::from_raw(process.io_priority())
+        }
+        .effective(Niceness::try_from(process.niceness()).unwrap_or_default());
+
+        let class_names = gtk::StringList::new(&[]);
+        for class in SETTABLE_IO_PRIORITY_CLASSES {
+            class_names.append(&io_priority_class_name(class));
+        }
+
+        let class_row = adw::ComboRow::builder()
+            .title(i18n("Class"))
+            .model(&class_names)
+            .selected(
+                SETTABLE_IO_PRIORITY_CLASSES
+                    .iter()
+                    .position(|class| *class == io_priority.class)
+                    .unwrap_or_default() as u32,
+            )
+            .build();
+
+        let level_row = adw::SpinRow::builder()
+            .title(i18n("Level"))
+            .subtitle(i18n(
+                "0 is the highest priority within the class, 7 the lowest",
+            ))
+            .adjustment(&gtk::Adjustment::new(
+                f64::from(io_priority.level),
+                0.0,
+                7.0,
+                1.0,
+                1.0,
+                0.0,
+            ))
+            .snap_to_ticks(true)
+            .build();
+
+        // processes of the idle class don't have a level
+        let selected_class = move |class_row: &adw::ComboRow| {
+            SETTABLE_IO_PRIORITY_CLASSES
+                .get(class_row.selected() as usize)
+                .copied()
+                .unwrap_or_default()
+        };
+        level_row.set_sensitive(selected_class(&class_row) != IoPriorityClass::Idle);
+        class_row.connect_selected_notify(clone!(
+            #[weak]
+            level_row,
+            move |class_row| {
+                level_row.set_sensitive(selected_class(class_row) != IoPriorityClass::Idle);
+            }
+        ));
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.append(&class_row);
+        list_box.append(&level_row);
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(i18n_f("I/O Priority of {}", &[&process.name()]))
+            .body(i18n(
+                "Processes with a lower I/O priority only get access to the disks after those with a higher one. This only has an effect on disks whose I/O scheduler supports priorities, such as BFQ.",
+            ))
+            .extra_child(&list_box)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("apply", &i18n("Apply"));
+        dialog.set_response_appearance("apply", ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("apply"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("apply"),
+            clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[weak]
+                process,
+                move |_, _| {
+                    let io_priority = IoPriority {
+                        class: selected_class(&class_row),
+                        level: level_row.value() as u8,
+                    };
+
+                    let main_context = MainContext::default();
+                    main_context.spawn_local(clone!(
+                        #[weak]
+                        this,
+                        #[weak]
+                        process,
+                        async move {
+                            let imp = this.imp();
+                            let _ = imp
+                                .sender
+                                .get()
+                                .unwrap()
+                                .send(Action::SetIoPriority(
+                                    process.pid(),
+                                    io_priority,
+                                    process.name().to_string(),
+                                    imp.toast_overlay.get(),
+                                ))
+                                .await;
+                        }
+                    ));
+                }
+            ),
+        );
+
+        dialog.present(Some(&MainWindow::default()));
+    }
+
+    pub fn open_open_files_dialog(&self, process: &ProcessEntry) {
+        let dialog = ResOpenFilesDialog::new();
+
+        dialog.init(process);
+
+        dialog.present(Some(&MainWindow::default()));
+    }
+
     fn search_filter(&self, obj: &Object) -> bool {
         let imp = self.imp();
         let item = obj.downcast_ref::<ProcessEntry>().unwrap();
@@ -902,6 +1107,142 @@ impl ResProcesses {
         dialog.present(Some(&MainWindow::default()));
     }
 
+    /// Like `open_process_action_dialog()`, but the action is applied to all descendants of the process as well
+    pub fn open_process_tree_action_dialog(&self, process: &ProcessEntry, action: ProcessAction) {
+        let dialog = adw::AlertDialog::builder()
+            .heading(get_tree_action_name(action, &process.name()))
+            .body(get_action_warning(action))
+            .build();
+
+        dialog.add_response("yes", &get_tree_action_description(action));
+        dialog.set_response_appearance("yes", ResponseAppearance::Destructive);
+
+        dialog.add_response("no", &i18n("Cancel"));
+        dialog.set_default_response(Some("no"));
+        dialog.set_close_response("no");
+
+        let pid = process.pid();
+
+        dialog.connect_response(
+            Some("yes"),
+            clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_, _| {
+                    let main_context = MainContext::default();
+                    main_context.spawn_local(clone!(
+                        #[weak]
+                        this,
+                        async move {
+                            let imp = this.imp();
+                            let _ = imp
+                                .sender
+                                .get()
+                                .unwrap()
+                                .send(Action::ManipulateProcessTree(
+                                    action,
+                                    pid,
+                                    imp.toast_overlay.get(),
+                                ))
+                                .await;
+                        }
+                    ));
+                }
+            ),
+        );
+
+        dialog.present(Some(&MainWindow::default()));
+    }
+
+    pub fn open_signal_dialog(&self, processes: Vec<ProcessEntry>) {
+        let signals: Vec<Signal> = Signal::iterator().collect();
+
+        let signal_names = gtk::StringList::new(&[]);
+        for signal in &signals {
+            signal_names.append(&format!("{} ({})", signal.as_str(), *signal as i32));
+        }
+
+        let signal_row = adw::ComboRow::builder()
+            .title(i18n("Signal"))
+            .model(&signal_names)
+            .enable_search(true)
+            .expression(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<&gtk::Expression>,
+                "string",
+            ))
+            .selected(
+                signals
+                    .iter()
+                    .position(|signal| *signal == Signal::SIGTERM)
+                    .unwrap_or_default() as u32,
+            )
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.append(&signal_row);
+
+        let heading = if processes.len() == 1 {
+            i18n_f("Send Signal to {}", &[&processes[0].name()])
+        } else {
+            ni18n_f(
+                "Send Signal to Process",
+                "Send Signal to {} Processes",
+                processes.len() as u32,
+                &[&processes.len().to_string()],
+            )
+        };
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(heading)
+            .body(i18n(
+                "Depending on the signal and how the process handles it, this may end the process or cause it to lose data.",
+            ))
+            .extra_child(&list_box)
+            .build();
+
+        dialog.add_response("cancel", &i18n("Cancel"));
+        dialog.add_response("send", &i18n("Send"));
+        dialog.set_response_appearance("send", ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("send"),
+            clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_, _| {
+                    let Some(signal) = signals.get(signal_row.selected() as usize).copied() else {
+                        return;
+                    };
+
+                    let pids: Vec<_> = processes.iter().map(ProcessEntry::pid).collect();
+
+                    let main_context = MainContext::default();
+                    main_context.spawn_local(clone!(
+                        #[weak]
+                        this,
+                        async move {
+                            let imp = this.imp();
+                            let _ = imp
+                                .sender
+                                .get()
+                                .unwrap()
+                                .send(Action::SendSignal(signal, pids, imp.toast_overlay.get()))
+                                .await;
+                        }
+                    ));
+                }
+            ),
+        );
+
+        dialog.present(Some(&MainWindow::default()));
+    }
+
     fn add_name_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
         let name_col_factory = gtk::SignalListItemFactory::new();
 
@@ -938,6 +1279,18 @@ impl ResProcesses {
                     .chain_property::<ProcessEntry>("symbolic")
                     .bind(&row, "symbolic", Widget::NONE);
 
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("state")
+                    .bind(&row, "state", Widget::NONE);
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("container")
+                    .bind(&row, "container", Widget::NONE);
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("container_id")
+                    .bind(&row, "container_id", Widget::NONE);
+
                 this.add_gestures(item);
             }
         ));
@@ -1992,6 +2345,329 @@ impl ResProcesses {
 
         swap_col
     }
+
+    fn add_major_faults_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
+        let major_faults_col_factory = gtk::SignalListItemFactory::new();
+
+        let major_faults_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Major Faults")),
+            Some(major_faults_col_factory.clone()),
+        );
+
+        major_faults_col.set_resizable(true);
+
+        major_faults_col_factory.connect_setup(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_factory, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+
+                let row = gtk::Inscription::new(None);
+                row.set_min_chars(8);
+
+                item.set_child(Some(&row));
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("major_fault_rate")
+                    .chain_closure::<String>(closure!(
+                        |_: Option<Object>, major_fault_rate: f64| {
+                            i18n_f("{}/s", &[&format!("{major_fault_rate:.0}")])
+                        }
+                    ))
+                    .bind(&row, "text", Widget::NONE);
+
+                this.add_gestures(item);
+            }
+        ));
+
+        major_faults_col_factory.connect_teardown(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            item.set_child(None::<&gtk::Inscription>);
+        });
+
+        let major_faults_col_sorter = NumericSorter::builder()
+            .sort_order(SortType::Ascending)
+            .expression(gtk::PropertyExpression::new(
+                ProcessEntry::static_type(),
+                None::<&gtk::Expression>,
+                "major_fault_rate",
+            ))
+            .build();
+
+        major_faults_col.set_sorter(Some(&major_faults_col_sorter));
+        major_faults_col.set_visible(SETTINGS.processes_show_major_faults());
+
+        column_view.append_column(&major_faults_col);
+
+        SETTINGS.connect_processes_show_major_faults(clone!(
+            #[weak]
+            major_faults_col,
+            move |visible| major_faults_col.set_visible(visible)
+        ));
+
+        major_faults_col
+    }
+
+    fn add_involuntary_switches_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
+        let involuntary_switches_col_factory = gtk::SignalListItemFactory::new();
+
+        let involuntary_switches_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Involuntary Switches")),
+            Some(involuntary_switches_col_factory.clone()),
+        );
+
+        involuntary_switches_col.set_resizable(true);
+
+        involuntary_switches_col_factory.connect_setup(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_factory, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+
+                let row = gtk::Inscription::new(None);
+                row.set_min_chars(8);
+
+                item.set_child(Some(&row));
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("involuntary_switch_rate")
+                    .chain_closure::<String>(closure!(
+                        |_: Option<Object>, involuntary_switch_rate: f64| {
+                            if involuntary_switch_rate == -1.0 {
+                                i18n("N/A")
+                            } else {
+                                i18n_f("{}/s", &[&format!("{involuntary_switch_rate:.0}")])
+                            }
+                        }
+                    ))
+                    .bind(&row, "text", Widget::NONE);
+
+                this.add_gestures(item);
+            }
+        ));
+
+        involuntary_switches_col_factory.connect_teardown(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            item.set_child(None::<&gtk::Inscription>);
+        });
+
+        let involuntary_switches_col_sorter = NumericSorter::builder()
+            .sort_order(SortType::Ascending)
+            .expression(gtk::PropertyExpression::new(
+                ProcessEntry::static_type(),
+                None::<&gtk::Expression>,
+                "involuntary_switch_rate",
+            ))
+            .build();
+
+        involuntary_switches_col.set_sorter(Some(&involuntary_switches_col_sorter));
+        involuntary_switches_col.set_visible(SETTINGS.processes_show_involuntary_switches());
+
+        column_view.append_column(&involuntary_switches_col);
+
+        SETTINGS.connect_processes_show_involuntary_switches(clone!(
+            #[weak]
+            involuntary_switches_col,
+            move |visible| involuntary_switches_col.set_visible(visible)
+        ));
+
+        involuntary_switches_col
+    }
+
+    fn add_scheduling_policy_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
+        let scheduling_policy_col_factory = gtk::SignalListItemFactory::new();
+
+        let scheduling_policy_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Scheduling Policy")),
+            Some(scheduling_policy_col_factory.clone()),
+        );
+
+        scheduling_policy_col.set_resizable(true);
+
+        scheduling_policy_col_factory.connect_setup(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_factory, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+
+                let row = gtk::Inscription::new(None);
+                row.set_min_chars(8);
+
+                item.set_child(Some(&row));
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("scheduling_policy")
+                    .chain_closure::<String>(closure!(
+                        |_: Option<Object>, scheduling_policy: i32| {
+                            scheduling_policy_name(SchedulingPolicy::from_raw(scheduling_policy))
+                        }
+                    ))
+                    .bind(&row, "text", Widget::NONE);
+
+                this.add_gestures(item);
+            }
+        ));
+
+        scheduling_policy_col_factory.connect_teardown(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            item.set_child(None::<&gtk::Inscription>);
+        });
+
+        let scheduling_policy_col_sorter = NumericSorter::builder()
+            .sort_order(SortType::Ascending)
+            .expression(gtk::PropertyExpression::new(
+                ProcessEntry::static_type(),
+                None::<&gtk::Expression>,
+                "scheduling_policy",
+            ))
+            .build();
+
+        scheduling_policy_col.set_sorter(Some(&scheduling_policy_col_sorter));
+        scheduling_policy_col.set_visible(SETTINGS.processes_show_scheduling_policy());
+
+        column_view.append_column(&scheduling_policy_col);
+
+        SETTINGS.connect_processes_show_scheduling_policy(clone!(
+            #[weak]
+            scheduling_policy_col,
+            move |visible| scheduling_policy_col.set_visible(visible)
+        ));
+
+        scheduling_policy_col
+    }
+
+    fn add_realtime_priority_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
+        let realtime_priority_col_factory = gtk::SignalListItemFactory::new();
+
+        let realtime_priority_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Realtime Priority")),
+            Some(realtime_priority_col_factory.clone()),
+        );
+
+        realtime_priority_col.set_resizable(true);
+
+        realtime_priority_col_factory.connect_setup(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_factory, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+
+                let row = gtk::Inscription::new(None);
+                row.set_min_chars(4);
+
+                item.set_child(Some(&row));
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("realtime_priority")
+                    .chain_closure::<String>(closure!(
+                        |_: Option<Object>, realtime_priority: u32| {
+                            if realtime_priority == 0 {
+                                i18n("N/A")
+                            } else {
+                                realtime_priority.to_string()
+                            }
+                        }
+                    ))
+                    .bind(&row, "text", Widget::NONE);
+
+                this.add_gestures(item);
+            }
+        ));
+
+        realtime_priority_col_factory.connect_teardown(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            item.set_child(None::<&gtk::Inscription>);
+        });
+
+        let realtime_priority_col_sorter = NumericSorter::builder()
+            .sort_order(SortType::Ascending)
+            .expression(gtk::PropertyExpression::new(
+                ProcessEntry::static_type(),
+                None::<&gtk::Expression>,
+                "realtime_priority",
+            ))
+            .build();
+
+        realtime_priority_col.set_sorter(Some(&realtime_priority_col_sorter));
+        realtime_priority_col.set_visible(SETTINGS.processes_show_realtime_priority());
+
+        column_view.append_column(&realtime_priority_col);
+
+        SETTINGS.connect_processes_show_realtime_priority(clone!(
+            #[weak]
+            realtime_priority_col,
+            move |visible| realtime_priority_col.set_visible(visible)
+        ));
+
+        realtime_priority_col
+    }
+
+    fn add_power_usage_column(&self, column_view: &ColumnView) -> ColumnViewColumn {
+        let power_usage_col_factory = gtk::SignalListItemFactory::new();
+
+        let power_usage_col = gtk::ColumnViewColumn::new(
+            Some(&i18n("Power Usage")),
+            Some(power_usage_col_factory.clone()),
+        );
+
+        power_usage_col.set_resizable(true);
+
+        power_usage_col_factory.connect_setup(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_factory, item| {
+                let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+
+                let row = gtk::Inscription::new(None);
+                row.set_min_chars(7);
+
+                item.set_child(Some(&row));
+
+                item.property_expression("item")
+                    .chain_property::<ProcessEntry>("power_usage")
+                    .chain_closure::<String>(closure!(|_: Option<Object>, power_usage: f64| {
+                        if power_usage == -1.0 {
+                            i18n("N/A")
+                        } else {
+                            convert_power(power_usage)
+                        }
+                    }))
+                    .bind(&row, "text", Widget::NONE);
+
+                this.add_gestures(item);
+            }
+        ));
+
+        power_usage_col_factory.connect_teardown(move |_factory, item| {
+            let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+            item.set_child(None::<&gtk::Inscription>);
+        });
+
+        let power_usage_col_sorter = NumericSorter::builder()
+            .sort_order(SortType::Ascending)
+            .expression(gtk::PropertyExpression::new(
+                ProcessEntry::static_type(),
+                None::<&gtk::Expression>,
+                "power_usage",
+            ))
+            .build();
+
+        power_usage_col.set_sorter(Some(&power_usage_col_sorter));
+
+        // without readable energy counters, the column would only ever show N/A (see the settings dialog)
+        let power_usage_available = RaplSampler::new().is_readable();
+        power_usage_col.set_visible(power_usage_available && SETTINGS.processes_show_power_usage());
+
+        column_view.append_column(&power_usage_col);
+
+        SETTINGS.connect_processes_show_power_usage(clone!(
+            #[weak]
+            power_usage_col,
+            move |visible| power_usage_col.set_visible(power_usage_available && visible)
+        ));
+
+        power_usage_col
+    }
 }
 
 fn get_action_name(action: ProcessAction, name: &str) -> String {
@@ -2032,6 +2708,24 @@ fn get_action_name_multiple(action: ProcessAction, count: usize) -> String {
     }
 }
 
+fn get_tree_action_name(action: ProcessAction, name: &str) -> String {
+    match action {
+        ProcessAction::TERM => i18n_f("End {} and Its Subprocesses?", &[name]),
+        ProcessAction::STOP => i18n_f("Halt {} and Its Subprocesses?", &[name]),
+        ProcessAction::KILL => i18n_f("Kill {} and Its Subprocesses?", &[name]),
+        ProcessAction::CONT => i18n_f("Continue {} and Its Subprocesses?", &[name]),
+    }
+}
+
+fn get_tree_action_description(action: ProcessAction) -> String {
+    match action {
+        ProcessAction::TERM => i18n("End Process Tree"),
+        ProcessAction::STOP => i18n("Halt Process Tree"),
+        ProcessAction::KILL => i18n("Kill Process Tree"),
+        ProcessAction::CONT => i18n("Continue Process Tree"),
+    }
+}
+
 fn get_action_warning(action: ProcessAction) -> String {
     match action {
         ProcessAction::TERM => i18n("Unsaved work might be lost."),
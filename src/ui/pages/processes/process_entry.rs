@@ -6,7 +6,7 @@ use log::trace;
 use process_data::Containerization;
 
 use crate::{
-    i18n::i18n,
+    i18n::{i18n, i18n_f},
     utils::{TICK_RATE, process::Process},
 };
 
@@ -85,12 +85,51 @@ mod imp {
         #[property(get, set)]
         niceness: Cell<i8>,
 
+        #[property(get, set)]
+        major_fault_rate: Cell<f64>,
+
+        #[property(get, set)]
+        involuntary_switch_rate: Cell<f64>, // will be -1.0 if the context switches are not available
+
+        #[property(get, set)]
+        scheduling_policy: Cell<i32>, // raw policy as used by sched_setscheduler(), -1 if it's unknown
+
+        #[property(get, set)]
+        realtime_priority: Cell<u32>,
+
+        #[property(get, set)]
+        power_usage: Cell<f64>, // estimated, will be -1.0 if the power draw of the CPUs is not available
+
+        #[property(get, set)]
+        io_priority: Cell<i32>, // raw I/O priority as used by ioprio_set(), -1 if it's not available
+
+        #[property(get, set)]
+        oom_score: Cell<i32>, // will be -1 if the OOM score is not available
+
+        #[property(get, set)]
+        oom_score_adj: Cell<i32>,
+
         #[property(get = Self::cgroup, set = Self::set_cgroup)]
         cgroup: Cell<Option<glib::GString>>,
 
         #[property(get = Self::containerization, set = Self::set_containerization)]
         containerization: Cell<glib::GString>,
 
+        #[property(get = Self::container, set = Self::set_container)]
+        container: Cell<glib::GString>, // empty unless the process runs in a Docker, Podman or LXC container
+
+        #[property(get = Self::container_id, set = Self::set_container_id)]
+        container_id: Cell<Option<glib::GString>>,
+
+        #[property(get = Self::security_context, set = Self::set_security_context)]
+        security_context: Cell<Option<glib::GString>>,
+
+        #[property(get, set)]
+        confined: Cell<bool>,
+
+        #[property(get = Self::state, set = Self::set_state)]
+        state: Cell<glib::GString>, // single-letter state as shown by ps, e.g. "R" or "Z"
+
         #[property(get = Self::running_since, set = Self::set_running_since)]
         running_since: Cell<Option<glib::GString>>,
 
@@ -125,8 +164,21 @@ mod imp {
                 user_cpu_time: Cell::new(0.0),
                 system_cpu_time: Cell::new(0.0),
                 niceness: Cell::new(0),
+                major_fault_rate: Cell::new(0.0),
+                involuntary_switch_rate: Cell::new(0.0),
+                scheduling_policy: Cell::new(0),
+                realtime_priority: Cell::new(0),
+                power_usage: Cell::new(-1.0),
+                io_priority: Cell::new(-1),
+                oom_score: Cell::new(0),
+                oom_score_adj: Cell::new(0),
                 cgroup: Cell::new(None),
                 containerization: Cell::new(glib::GString::default()),
+                container: Cell::new(glib::GString::default()),
+                container_id: Cell::new(None),
+                security_context: Cell::new(None),
+                confined: Cell::new(false),
+                state: Cell::new(glib::GString::default()),
                 running_since: Cell::new(None),
                 symbolic: Cell::new(false),
                 affinity: Default::default(),
@@ -135,8 +187,8 @@ mod imp {
     }
 
     impl ProcessEntry {
-        gstring_getter_setter!(user, commandline, name, containerization);
-        gstring_option_getter_setter!(cgroup, running_since);
+        gstring_getter_setter!(user, commandline, name, containerization, container, state);
+        gstring_option_getter_setter!(cgroup, running_since, container_id, security_context);
 
         pub fn icon(&self) -> Icon {
             let icon = self.icon.replace(ThemedIcon::new("generic-process").into());
@@ -207,6 +259,27 @@ impl ProcessEntry {
             Containerization::None => i18n("No"),
             Containerization::Flatpak => i18n("Yes (Flatpak)"),
             Containerization::Snap => i18n("Yes (Snap)"),
+            Containerization::AppImage => i18n("Yes (AppImage)"),
+            Containerization::Docker => i18n_f(
+                "Yes (Docker, {})",
+                &[process.data.container_display_name().unwrap_or_default()],
+            ),
+            Containerization::Podman => i18n_f(
+                "Yes (Podman, {})",
+                &[process.data.container_display_name().unwrap_or_default()],
+            ),
+            Containerization::Lxc => i18n_f(
+                "Yes (LXC, {})",
+                &[process.data.container_display_name().unwrap_or_default()],
+            ),
+        };
+
+        // shown as a badge next to the process name
+        let container = match process.data.containerization {
+            Containerization::Docker => i18n("Docker"),
+            Containerization::Podman => i18n("Podman"),
+            Containerization::Lxc => i18n("LXC"),
+            _ => String::new(),
         };
 
         let this: Self = glib::Object::builder()
@@ -217,6 +290,16 @@ impl ProcessEntry {
             .property("pid", process.data.pid)
             .property("cgroup", process.data.cgroup.clone().map(GString::from))
             .property("containerization", containerization)
+            .property("container", container)
+            .property(
+                "container_id",
+                process.data.container_id.clone().map(GString::from),
+            )
+            .property(
+                "security_context",
+                process.data.security_context.clone().map(GString::from),
+            )
+            .property("confined", process.data.is_confined().unwrap_or(false))
             .property("running_since", process.running_since().ok())
             .build();
         this.update(process);
@@ -251,6 +334,30 @@ impl ProcessEntry {
         self.set_system_cpu_time((process.data.system_cpu_time as f64) / (*TICK_RATE as f64));
         self.set_total_cpu_time(self.user_cpu_time() + self.system_cpu_time());
         self.set_niceness(*process.data.niceness);
+        self.set_scheduling_policy(process.data.scheduling_policy.as_raw().unwrap_or(-1));
+        self.set_realtime_priority(process.data.realtime_priority);
+        self.set_power_usage(process.power_usage.unwrap_or(-1.0));
+        self.set_io_priority(
+            process
+                .data
+                .io_priority
+                .map_or(-1, |io_priority| io_priority.as_raw()),
+        );
+        // processes in a frozen cgroup are halted as well, even though they're not in the stopped state
+        if process.data.frozen {
+            self.set_state("T");
+        } else {
+            self.set_state(&process.data.state.as_char().to_string());
+        }
+        self.set_major_fault_rate(process.major_fault_rate());
+        self.set_involuntary_switch_rate(process.involuntary_switch_rate().unwrap_or(-1.0));
+        self.set_oom_score(
+            process
+                .data
+                .oom_score
+                .map_or(-1, |oom_score| oom_score as i32),
+        );
+        self.set_oom_score_adj(process.data.oom_score_adj.map_or(0, i32::from));
         (*self.imp().affinity.borrow_mut()).clone_from(&process.data.affinity);
     }
 
@@ -2,6 +2,8 @@ use adw::{glib::property::PropertySet, prelude::*, subclass::prelude::*};
 use gtk::{gio::Icon, glib};
 use log::trace;
 
+use crate::i18n::i18n;
+
 mod imp {
     use std::cell::{Cell, RefCell};
 
@@ -21,6 +23,10 @@ mod imp {
         pub image: TemplateChild<gtk::Image>,
         #[template_child]
         pub inscription: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub state_badge: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub container_badge: TemplateChild<gtk::Label>,
 
         #[property(get = Self::name, set = Self::set_name, type = glib::GString)]
         name: Cell<glib::GString>,
@@ -30,6 +36,12 @@ mod imp {
         icon: RefCell<Icon>,
         #[property(get, set = Self::set_symbolic)]
         symbolic: Cell<bool>,
+        #[property(get = Self::state, set = Self::set_state, type = glib::GString)]
+        state: Cell<glib::GString>,
+        #[property(get = Self::container, set = Self::set_container, type = glib::GString)]
+        container: Cell<glib::GString>,
+        #[property(get = Self::container_id, set = Self::set_container_id, type = Option<glib::GString>)]
+        container_id: Cell<Option<glib::GString>>,
     }
 
     impl Default for ResProcessNameCell {
@@ -41,6 +53,9 @@ mod imp {
                 tooltip: Default::default(),
                 icon: RefCell::new(ThemedIcon::new("generic-process").into()),
                 symbolic: Default::default(),
+                state: Default::default(),
+                container: Default::default(),
+                container_id: Default::default(),
             }
         }
     }
@@ -68,6 +83,72 @@ mod imp {
             self.inscription.set_tooltip_text(Some(tooltip));
         }
 
+        pub fn state(&self) -> glib::GString {
+            let state = self.state.take();
+            self.state.set(state.clone());
+            state
+        }
+
+        /// Flags processes in states that usually indicate a problem, i.e. zombies that haven't been reaped by their
+        /// parent and processes stuck in uninterruptible sleep
+        pub fn set_state(&self, state: &str) {
+            self.state.set(glib::GString::from(state));
+
+            let badge = match state {
+                "Z" => Some((
+                    i18n("Zombie"),
+                    i18n("This process has exited but has not been reaped by its parent yet"),
+                    "error",
+                )),
+                "D" => Some((
+                    i18n("Uninterruptible"),
+                    i18n("This process is waiting for I/O and cannot be interrupted"),
+                    "warning",
+                )),
+                "T" => Some((
+                    i18n("Halted"),
+                    i18n("This process has been halted and will not run until it is continued"),
+                    "dim-label",
+                )),
+                _ => None,
+            };
+
+            if let Some((label, tooltip, css_class)) = badge {
+                self.state_badge.set_label(&label);
+                self.state_badge.set_tooltip_text(Some(&tooltip));
+                self.state_badge
+                    .set_css_classes(&["caption-heading", css_class]);
+                self.state_badge.set_visible(true);
+            } else {
+                self.state_badge.set_visible(false);
+            }
+        }
+
+        pub fn container(&self) -> glib::GString {
+            let container = self.container.take();
+            self.container.set(container.clone());
+            container
+        }
+
+        /// Shows the kind of container (e.g. "Docker") the process runs in, nothing if `container` is empty
+        pub fn set_container(&self, container: &str) {
+            self.container.set(glib::GString::from(container));
+
+            self.container_badge.set_label(container);
+            self.container_badge.set_visible(!container.is_empty());
+        }
+
+        pub fn container_id(&self) -> Option<glib::GString> {
+            let container_id = self.container_id.take();
+            self.container_id.set(container_id.clone());
+            container_id
+        }
+
+        pub fn set_container_id(&self, container_id: Option<&str>) {
+            self.container_id.set(container_id.map(glib::GString::from));
+            self.container_badge.set_tooltip_text(container_id);
+        }
+
         pub fn icon(&self) -> Icon {
             let icon = self
                 .icon
@@ -1,4 +1,5 @@
-use process_data::{Niceness, ProcessData};
+use nix::sys::signal::Signal;
+use process_data::{IoPriority, Niceness, ProcessData, SchedulingPolicy};
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
@@ -36,8 +37,20 @@ use super::pages::npu::ResNPU;
 #[derive(Debug, Clone)]
 pub enum Action {
     ManipulateProcesses(ProcessAction, Vec<libc::pid_t>, ToastOverlay),
+    ManipulateProcessTree(ProcessAction, libc::pid_t, ToastOverlay),
     ManipulateApp(ProcessAction, String, ToastOverlay),
-    AdjustProcess(libc::pid_t, Niceness, Vec<bool>, String, ToastOverlay),
+    SendSignal(Signal, Vec<libc::pid_t>, ToastOverlay),
+    AdjustProcess(
+        libc::pid_t,
+        Niceness,
+        Vec<bool>,
+        i16,
+        SchedulingPolicy,
+        u32,
+        String,
+        ToastOverlay,
+    ),
+    SetIoPriority(libc::pid_t, IoPriority, String, ToastOverlay),
 }
 
 mod imp {
@@ -1133,8 +1146,18 @@ impl MainWindow {
                 toast_overlay.add_toast(Toast::new(&toast_message));
             }
 
+            Action::ManipulateProcessTree(action, pid, toast_overlay) => {
+                let pids = apps_context.process_tree(pid);
+                drop(apps_context);
+
+                self.process_action(Action::ManipulateProcesses(action, pids, toast_overlay));
+            }
+
             Action::ManipulateApp(action, id, toast_overlay) => {
-                let app = apps_context.get_app(&Some(id.clone())).unwrap();
+                // container apps are dropped as soon as their container is gone, which may have happened in between
+                let Some(app) = apps_context.get_app(&Some(id.clone())) else {
+                    return;
+                };
                 let result = app.execute_process_action(&apps_context, action);
 
                 let processes_tried = result.len();
@@ -1150,9 +1173,24 @@ impl MainWindow {
                 toast_overlay.add_toast(Toast::new(&toast_message));
             }
 
-            Action::AdjustProcess(pid, niceness, affinity, display_name, toast_overlay) => {
+            Action::AdjustProcess(
+                pid,
+                niceness,
+                affinity,
+                oom_score_adj,
+                scheduling_policy,
+                realtime_priority,
+                display_name,
+                toast_overlay,
+            ) => {
                 if let Some(process) = apps_context.get_process(pid) {
-                    let result = process.adjust(niceness, affinity);
+                    let result = process.adjust(
+                        niceness,
+                        affinity,
+                        oom_score_adj,
+                        scheduling_policy,
+                        realtime_priority,
+                    );
 
                     let toast_message = match result {
                         Ok(()) => i18n_f("Successfully adjusted {}", &[&display_name]),
@@ -1161,6 +1199,55 @@ impl MainWindow {
                     toast_overlay.add_toast(Toast::new(&toast_message));
                 }
             }
+
+            Action::SetIoPriority(pid, io_priority, display_name, toast_overlay) => {
+                if let Some(process) = apps_context.get_process(pid) {
+                    let toast_message = match process.set_io_priority(io_priority) {
+                        Ok(()) => i18n_f("Successfully adjusted {}", &[&display_name]),
+                        Err(_) => i18n_f("There was a problem adjusting {}", &[&display_name]),
+                    };
+                    toast_overlay.add_toast(Toast::new(&toast_message));
+                }
+            }
+
+            Action::SendSignal(signal, pids, toast_overlay) => {
+                let processes: Vec<_> = pids
+                    .iter()
+                    .filter_map(|pid| apps_context.get_process(*pid))
+                    .collect();
+
+                let processes_unsuccessful = processes
+                    .iter()
+                    .filter(|process| process.send_signal(signal).is_err())
+                    .count();
+
+                let signal_name = signal.as_str();
+
+                let toast_message = match processes.as_slice() {
+                    [process] if processes_unsuccessful == 0 => i18n_f(
+                        "Successfully sent {} to {}",
+                        &[signal_name, &process.display_name],
+                    ),
+                    [process] => i18n_f(
+                        "There was a problem sending {} to {}",
+                        &[signal_name, &process.display_name],
+                    ),
+                    _ if processes_unsuccessful == 0 => ni18n_f(
+                        "Successfully sent {} to the process",
+                        "Successfully sent {} to {} processes",
+                        processes.len() as u32,
+                        &[signal_name, &processes.len().to_string()],
+                    ),
+                    _ => ni18n_f(
+                        "There was a problem sending {} to a process",
+                        "There were problems sending {} to {} processes",
+                        processes_unsuccessful as u32,
+                        &[signal_name, &processes_unsuccessful.to_string()],
+                    ),
+                };
+
+                toast_overlay.add_toast(Toast::new(&toast_message));
+            }
         };
     }
 
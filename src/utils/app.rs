@@ -12,9 +12,10 @@ use gtk::{
 };
 use lazy_regex::{Lazy, Regex, lazy_regex};
 use log::{debug, info, trace};
-use process_data::{Containerization, GpuIdentifier, ProcessData};
+use process_data::energy::{RaplSampler, attribute_power};
+use process_data::{Containerization, GpuEngine, GpuIdentifier, ProcessData};
 
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f};
 
 use super::{
     FiniteOr, boot_time,
@@ -25,6 +26,9 @@ use super::{
 /// considered instead to enhance app detection
 const DESKTOP_ENVIRONMENT_CGROUPS: &[&str] = &["org.gnome.Shell"];
 
+/// Prefix of the IDs of the apps created for containers, see `App::container()`
+const CONTAINER_APP_ID_PREFIX: &str = "container:";
+
 // This contains executable names that are blocklisted from being recognized as applications
 const DESKTOP_EXEC_BLOCKLIST: &[&str] = &["bash", "zsh", "fish", "sh", "ksh", "flatpak"];
 
@@ -163,6 +167,7 @@ pub struct AppsContext {
     apps: HashMap<Option<String>, App>,
     processes: HashMap<i32, Process>,
     gpus_with_combined_media_engine: Vec<GpuIdentifier>,
+    rapl: RaplSampler,
 }
 
 /// Represents an application installed on the system. It doesn't
@@ -231,6 +236,34 @@ impl App {
         apps
     }
 
+    /// Creates an app grouping the processes of the container the process described by `process_data` runs in
+    pub fn container(process_data: &ProcessData) -> App {
+        let container_name = process_data.container_display_name().unwrap_or_default();
+
+        let display_name = match process_data.containerization {
+            Containerization::Docker => i18n_f("Docker Container {}", &[container_name]),
+            Containerization::Podman => i18n_f("Podman Container {}", &[container_name]),
+            Containerization::Lxc => i18n_f("LXC Container {}", &[container_name]),
+            _ => container_name.to_string(),
+        };
+
+        App {
+            processes: Vec::new(),
+            commandline: None,
+            executable_name: None,
+            display_name,
+            description: process_data.container_id.clone(),
+            icon: ThemedIcon::new("generic-process").into(),
+            id: process_data
+                .container_id
+                .as_ref()
+                .map(|container_id| format!("{CONTAINER_APP_ID_PREFIX}{container_id}")),
+            read_bytes_from_dead_processes: 0,
+            write_bytes_from_dead_processes: 0,
+            containerization: process_data.containerization,
+        }
+    }
+
     pub fn from_desktop_file<P: AsRef<Path>>(file_path: P) -> Result<App> {
         let file_path = file_path.as_ref();
         trace!("Reading {file_path:?}…");
@@ -388,6 +421,13 @@ impl App {
         !self.processes.is_empty()
     }
 
+    /// Whether this app has been created for a container rather than for a graphical app, see `App::container()`
+    pub fn is_container(&self) -> bool {
+        self.id
+            .as_deref()
+            .is_some_and(|id| id.starts_with(CONTAINER_APP_ID_PREFIX))
+    }
+
     pub fn processes_iter<'a>(
         &'a self,
         apps: &'a AppsContext,
@@ -488,11 +528,53 @@ impl App {
         apps: &AppsContext,
         action: ProcessAction,
     ) -> Vec<Result<()>> {
+        // prefer freezing the app's cgroup as a whole when halting it, fall back to sending signals to every process
+        // otherwise. Continuing always signals every process: `Process::execute_process_action()` thaws the frozen
+        // cgroups and SIGCONT resumes the processes that have been halted individually.
+        let cgroup_path = (action == ProcessAction::STOP)
+            .then(|| self.dedicated_cgroup(apps))
+            .flatten();
+
+        if let Some(cgroup_path) = cgroup_path {
+            match ProcessData::set_cgroup_frozen(&cgroup_path, true) {
+                Ok(()) => {
+                    info!(
+                        "Successfully froze cgroup {cgroup_path} of {}",
+                        self.display_name
+                    );
+                    return self.processes_iter(apps).map(|_| Ok(())).collect();
+                }
+                Err(err) => debug!(
+                    "Unable to use cgroup freezer for {}: {err}",
+                    self.display_name
+                ),
+            }
+        }
+
         self.processes_iter(apps)
             .map(|process| process.execute_process_action(action))
             .collect()
     }
 
+    /// Returns the cgroup all processes of this app belong to if no other processes are part of it or its descendant
+    /// cgroups, e.g. the systemd scope a desktop environment launched the app in. Such a cgroup can be frozen without
+    /// affecting anything else.
+    fn dedicated_cgroup(&self, apps: &AppsContext) -> Option<String> {
+        let mut cgroup_paths = self
+            .processes_iter(apps)
+            .map(|process| process.data.cgroup_path.as_deref());
+
+        let cgroup_path = cgroup_paths.next()??;
+        if !cgroup_paths.all(|other| other == Some(cgroup_path)) {
+            return None;
+        }
+
+        ProcessData::cgroup_pids(cgroup_path)?
+            .iter()
+            .all(|pid| self.processes.contains(pid))
+            .then(|| cgroup_path.to_string())
+    }
+
     pub fn running_since(&self, apps: &AppsContext) -> Result<GString> {
         boot_time()
             .and_then(|boot_time| {
@@ -522,6 +604,7 @@ impl AppsContext {
             apps,
             processes: HashMap::new(),
             gpus_with_combined_media_engine,
+            rapl: RaplSampler::new(),
         }
     }
 
@@ -550,6 +633,8 @@ impl AppsContext {
             .map(|(new, old, timestamp, timestamp_last)| {
                 if new.nvidia {
                     new.gfx as f32 / 100.0
+                } else if let Some(fraction) = new.cycles_fraction_since(old, GpuEngine::Graphics) {
+                    fraction as f32
                 } else if old.gfx == 0 {
                     0.0
                 } else {
@@ -588,6 +673,8 @@ impl AppsContext {
             .map(|(new, old, timestamp, timestamp_last)| {
                 if new.nvidia {
                     new.enc as f32 / 100.0
+                } else if let Some(fraction) = new.cycles_fraction_since(old, GpuEngine::Encode) {
+                    fraction as f32
                 } else if old.enc == 0 {
                     0.0
                 } else {
@@ -626,6 +713,8 @@ impl AppsContext {
             .map(|(new, old, timestamp, timestamp_last)| {
                 if new.nvidia {
                     new.dec as f32 / 100.0
+                } else if let Some(fraction) = new.cycles_fraction_since(old, GpuEngine::Decode) {
+                    fraction as f32
                 } else if old.dec == 0 {
                     0.0
                 } else {
@@ -737,6 +826,17 @@ impl AppsContext {
         self.processes.get(&pid)
     }
 
+    /// Returns the PID of the given process followed by those of all of its descendants, parents coming before their
+    /// children
+    pub fn process_tree(&self, pid: i32) -> Vec<i32> {
+        let mut pids = vec![pid];
+        pids.extend(ProcessData::descendant_pids(
+            pid,
+            self.processes.values().map(|process| &process.data),
+        ));
+        pids
+    }
+
     pub fn get_app(&self, id: &Option<String>) -> Option<&App> {
         self.apps.get(id)
     }
@@ -765,6 +865,31 @@ impl AppsContext {
         })
     }
 
+    /// Estimates the power draw of every process by splitting the power the CPU packages have drawn since the last
+    /// refresh among the processes according to the CPU time they've used in the meantime
+    fn attribute_power(&mut self) {
+        let Some(package_power) = self.rapl.sample() else {
+            self.processes
+                .values_mut()
+                .for_each(|process| process.power_usage = None);
+            return;
+        };
+
+        let total_cpu_time: u64 = self
+            .processes
+            .values()
+            .filter_map(Process::cpu_time_delta)
+            .sum();
+
+        trace!("CPU packages drew {package_power:.2} W, attributing it to processes");
+
+        for process in self.processes.values_mut() {
+            process.power_usage = process
+                .cpu_time_delta()
+                .map(|cpu_time| attribute_power(package_power, cpu_time, total_cpu_time));
+        }
+    }
+
     /// Refreshes the statistics about the running applications and processes.
     pub fn refresh(&mut self, new_process_data: Vec<ProcessData>) {
         trace!("Refreshing AppsContext…");
@@ -801,6 +926,9 @@ impl AppsContext {
                 old_process.timestamp_last = old_process.data.timestamp;
                 old_process.read_bytes_last = old_process.data.read_bytes;
                 old_process.write_bytes_last = old_process.data.write_bytes;
+                old_process.major_faults_last = old_process.data.major_faults;
+                old_process.nonvoluntary_ctxt_switches_last =
+                    old_process.data.nonvoluntary_ctxt_switches;
                 old_process.gpu_usage_stats_last = old_process.data.gpu_usage_stats.clone();
 
                 old_process.data = process_data.clone();
@@ -810,8 +938,17 @@ impl AppsContext {
 
                 let mut new_process = Process::from_process_data(process_data);
 
+                let mut app_id = self.app_associated_with_process(&new_process);
+
+                // group the processes of containers that don't belong to a graphical app by their container
+                if app_id.is_none() && new_process.data.container_id.is_some() {
+                    let container_app = App::container(&new_process.data);
+                    app_id = container_app.id.clone();
+                    self.apps.entry(app_id.clone()).or_insert(container_app);
+                }
+
                 self.apps
-                    .get_mut(&self.app_associated_with_process(&new_process))
+                    .get_mut(&app_id)
                     .unwrap()
                     .add_process(&mut new_process);
 
@@ -865,6 +1002,13 @@ impl AppsContext {
         self.processes
             .retain(|pid, _| updated_processes.contains(pid));
 
+        // unlike graphical apps, container apps are only created on demand, so drop them again once their container
+        // is gone instead of accumulating an entry for every short-lived container
+        self.apps
+            .retain(|_, app| !app.is_container() || app.is_running());
+
+        self.attribute_power();
+
         trace!("AppsContext refresh done within {:.2?}", start.elapsed());
     }
 }
@@ -10,6 +10,10 @@ use std::{
 
 const PROC_STAT: &str = "/proc/stat";
 
+/// Hybrid Intel CPUs expose their performance and efficiency cores as separate PMUs, listing the CPUs they consist of
+const PERFORMANCE_CORES_PATH: &str = "/sys/devices/cpu_core/cpus";
+const EFFICIENCY_CORES_PATH: &str = "/sys/devices/cpu_atom/cpus";
+
 const KNOWN_HWMONS: &[&str] = &["zenpower", "coretemp", "k10temp"];
 
 const KNOWN_THERMAL_ZONES: &[&str] = &["cpu-thermal", "x86_pkg_temp", "acpitz"];
@@ -261,6 +265,65 @@ pub fn get_cpu_freq(core: usize) -> Result<u64> {
     .inspect(|freq| trace!("Frequency of core {core}: {freq} Hz"))
 }
 
+/// The kinds of cores of hybrid CPUs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreType {
+    Performance,
+    Efficiency,
+}
+
+/// Returns the core type of each logical CPU (indexed by its number) on hybrid CPUs, empty for CPUs whose cores are
+/// all the same
+pub fn get_core_types() -> Vec<Option<CoreType>> {
+    let read_cpu_list = |path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default()
+    };
+
+    let performance_cores = read_cpu_list(PERFORMANCE_CORES_PATH);
+    let efficiency_cores = read_cpu_list(EFFICIENCY_CORES_PATH);
+
+    if performance_cores.is_empty() || efficiency_cores.is_empty() {
+        return Vec::new();
+    }
+
+    let cpus = performance_cores
+        .iter()
+        .chain(&efficiency_cores)
+        .max()
+        .map_or(0, |max| max + 1);
+
+    let mut core_types = vec![None; cpus];
+    for cpu in performance_cores {
+        core_types[cpu] = Some(CoreType::Performance);
+    }
+    for cpu in efficiency_cores {
+        core_types[cpu] = Some(CoreType::Efficiency);
+    }
+
+    debug!("Found hybrid CPU with core types {core_types:?}");
+
+    core_types
+}
+
+/// Parses CPU lists as used by the kernel, e.g. `0-3,8,10-11`
+fn parse_cpu_list<S: AsRef<str>>(list: S) -> Vec<usize> {
+    list.as_ref()
+        .trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+            None => {
+                let cpu = range.parse().ok()?;
+                Some(cpu..=cpu)
+            }
+        })
+        .flatten()
+        .collect()
+}
+
 fn parse_proc_stat_line<S: AsRef<str>>(line: S) -> Result<(u64, u64)> {
     let captures = RE_PROC_STAT
         .captures(line.as_ref())
@@ -346,7 +409,7 @@ fn read_sysfs_thermal<P: AsRef<Path>>(path: P) -> Result<f32> {
 mod test {
     use pretty_assertions::assert_eq;
 
-    use crate::utils::cpu::CpuInfo;
+    use crate::utils::cpu::{CpuInfo, parse_cpu_list};
 
     const LSCPU_OUTPUT: &str = concat!(
         "Architecture:             x86_64\n",
@@ -396,4 +459,11 @@ mod test {
 
         assert_eq!(parsed, expected)
     }
+
+    #[test]
+    fn cpu_list() {
+        assert_eq!(vec![0, 1, 2, 3, 8, 10, 11], parse_cpu_list("0-3,8,10-11\n"));
+        assert_eq!(vec![5], parse_cpu_list("5"));
+        assert_eq!(Vec::<usize>::new(), parse_cpu_list("\n"));
+    }
 }
@@ -1,25 +1,86 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use lazy_regex::{Lazy, Regex, lazy_regex};
 use log::{debug, trace, warn};
 use process_data::GpuIdentifier;
 
-use std::{collections::HashMap, path::PathBuf, sync::LazyLock, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Instant,
+};
 
 use crate::utils::{
     IS_FLATPAK,
     pci::{self, Device},
+    read_sysfs_isize,
 };
 
-use super::GpuImpl;
+use super::{EccErrors, GpuImpl, GpuVendor, PowerState};
 
 static RE_AMDGPU_IDS: Lazy<Regex> = lazy_regex!(r"([0-9A-F]{4}),\s*([0-9A-F]{2}),\s*(.*)");
 
+static RE_PP_DPM_CURRENT: Lazy<Regex> = lazy_regex!(r"(?m)^\d+:\s*(\d+)\s*[Mm][Hh]z\s*\*");
+
+static RE_PP_DPM_ACTIVE_LEVEL: Lazy<Regex> = lazy_regex!(r"(?m)^(\d+:[^*\n]*?)\s*\*\s*$");
+
 static AMDGPU_IDS: LazyLock<HashMap<(u16, u8), String>> = LazyLock::new(|| {
     AmdGpu::read_libdrm_ids()
         .inspect_err(|e| warn!("Unable to parse amdgpu.ids!\n{e}\n{}", e.backtrace()))
         .unwrap_or_default()
 });
 
+/// The parts of amdgpu's binary `gpu_metrics` table (see `kgd_pp_interface.h` in the Linux kernel) that are of
+/// interest to us. Only the layouts of the table revisions 1.1 to 1.3 (dGPUs) and 2.0 to 2.2 (APUs) are known, every
+/// value the GPU reports as unavailable (0xFFFF) is `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuMetrics {
+    /// In percent
+    pub average_gfx_activity: Option<u16>,
+    /// In percent, only available for dGPUs
+    pub average_umc_activity: Option<u16>,
+    /// In percent
+    pub average_mm_activity: Option<u16>,
+    /// Power draw of the whole board (dGPUs) or the whole package (APUs) in milliwatts
+    pub average_socket_power: Option<u32>,
+    /// Power draw of the SoC rail in milliwatts, only available for APUs
+    pub average_soc_power: Option<u32>,
+}
+
+impl GpuMetrics {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let format_revision = *bytes.get(2).context("gpu_metrics header too short")?;
+        let content_revision = *bytes.get(3).context("gpu_metrics header too short")?;
+
+        let read_u16 = |offset: usize| {
+            bytes
+                .get(offset..offset + 2)
+                .map(|slice| u16::from_le_bytes([slice[0], slice[1]]))
+                .filter(|value| *value != u16::MAX)
+        };
+
+        match (format_revision, content_revision) {
+            // power values are in watts here…
+            (1, 1..=3) => Ok(Self {
+                average_gfx_activity: read_u16(16),
+                average_umc_activity: read_u16(18),
+                average_mm_activity: read_u16(20),
+                average_socket_power: read_u16(22).map(|watts| u32::from(watts) * 1000),
+                average_soc_power: None,
+            }),
+            // …and in milliwatts here
+            (2, 0..=2) => Ok(Self {
+                average_gfx_activity: read_u16(40),
+                average_umc_activity: None,
+                average_mm_activity: read_u16(42),
+                average_socket_power: read_u16(44).map(u32::from),
+                average_soc_power: read_u16(48).map(u32::from),
+            }),
+            _ => bail!("unsupported gpu_metrics revision {format_revision}.{content_revision}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 
 pub struct AmdGpu {
@@ -58,6 +119,7 @@ impl AmdGpu {
     }
 
     pub fn read_libdrm_ids() -> Result<HashMap<(u16, u8), String>> {
+        // contrary to sysfs, the host's /usr is only available below /run/host within Flatpak
         let path = if *IS_FLATPAK {
             PathBuf::from("/run/host/usr/share/libdrm/amdgpu.ids")
         } else {
@@ -94,6 +156,168 @@ impl AmdGpu {
 
         Ok(map)
     }
+
+    /// Returns the board's unique ID which, unlike the PCI slot, stays the same when moving the card around. Only
+    /// some (mostly professional or datacenter) cards expose it.
+    pub fn unique_id(&self) -> Result<String> {
+        let unique_id = self.read_device_file("unique_id")?;
+        if unique_id.trim().is_empty() {
+            bail!("unique_id is empty");
+        }
+        Ok(unique_id.trim().to_string())
+    }
+
+    /// Reads the current frequency (in Hz) of every clock domain that has a `pp_dpm_*` file within `device_path`,
+    /// keyed by the name of the domain (e.g. "sclk", "mclk", "fclk" or "socclk")
+    pub fn read_pp_dpm_clocks<P: AsRef<Path>>(device_path: P) -> BTreeMap<String, f64> {
+        let Ok(entries) = std::fs::read_dir(device_path) else {
+            return BTreeMap::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let domain = file_name.to_str()?.strip_prefix("pp_dpm_")?.to_string();
+
+                trace!("Reading {:?}…", entry.path());
+                let levels = std::fs::read_to_string(entry.path()).ok()?;
+
+                // pp_dpm_pcie lists link speeds rather than frequencies and won't match
+                let mhz = RE_PP_DPM_CURRENT
+                    .captures(&levels)?
+                    .get(1)?
+                    .as_str()
+                    .parse::<f64>()
+                    .ok()?;
+
+                Some((domain, mhz * 1_000_000.0))
+            })
+            .collect()
+    }
+
+    /// Returns the active DPM level of the memory clock as listed in `pp_dpm_mclk` (e.g. "0: 96Mhz" when the memory
+    /// clock is parked while idling)
+    pub fn memory_clock_state(&self) -> Result<String> {
+        Self::active_dpm_level(&self.read_device_file("pp_dpm_mclk")?)
+            .context("no active level in pp_dpm_mclk")
+    }
+
+    fn active_dpm_level(levels: &str) -> Option<String> {
+        RE_PP_DPM_ACTIVE_LEVEL
+            .captures(levels)
+            .and_then(|captures| captures.get(1))
+            .map(|level| level.as_str().trim().to_string())
+    }
+
+    /// Parses an `*_err_count` file of the RAS (reliability, availability, serviceability) interface, which contains
+    /// the number of uncorrectable ("ue") and correctable ("ce") errors of one hardware block
+    fn parse_ras_err_count(err_count: &str) -> Option<EccErrors> {
+        let mut errors = EccErrors::default();
+
+        for line in err_count.lines() {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim().parse().ok()?;
+            match key.trim() {
+                "ue" => errors.uncorrected = value,
+                "ce" => errors.corrected = value,
+                _ => (),
+            }
+        }
+
+        Some(errors)
+    }
+
+    /// Writes `watts` as the new power cap to `power1_cap` within `hwmon_path` after validating it against
+    /// `power1_cap_max`. Errors of the write itself are returned as is, so that a missing permission can be told apart.
+    pub fn write_power_cap<P: AsRef<Path>>(hwmon_path: P, watts: f64) -> Result<()> {
+        let hwmon_path = hwmon_path.as_ref();
+
+        let max_watts = read_sysfs_isize(hwmon_path.join("power1_cap_max"))? as f64 / 1_000_000.0;
+        if !watts.is_finite() || watts <= 0.0 {
+            bail!("invalid power cap of {watts} W");
+        }
+        if watts > max_watts {
+            bail!("power cap of {watts} W exceeds the maximum of {max_watts} W");
+        }
+
+        let path = hwmon_path.join("power1_cap");
+        let microwatts = (watts * 1_000_000.0).round() as u64;
+        debug!("Writing {microwatts} to {path:?}…");
+        std::fs::write(&path, microwatts.to_string())?;
+
+        Ok(())
+    }
+
+    /// Returns how many resets of this GPU happened recently, based on the device coredumps amdgpu creates whenever
+    /// it has to reset the GPU after a hang. The kernel discards these dumps once they've been read or after five
+    /// minutes, so this isn't a lifetime counter. amdgpu doesn't expose one (nor whether the GPU is currently hung) in
+    /// sysfs, and its debugfs recovery node triggers a reset when read, so it must not be used for this.
+    ///
+    /// Fails if the kernel has no devcoredump support.
+    pub fn reset_count(&self) -> Result<u64> {
+        Self::count_devcoredumps("/sys/class/devcoredump", self.sysfs_path.join("device"))
+    }
+
+    /// Whether the GPU has been reset recently (i.e. within the last five minutes at most), see `reset_count()`. This
+    /// doesn't tell whether the GPU is hung right now.
+    pub fn was_reset_recently(&self) -> Result<bool> {
+        self.reset_count().map(|count| count > 0)
+    }
+
+    fn count_devcoredumps<P: AsRef<Path>, Q: AsRef<Path>>(
+        devcoredump_class: P,
+        device_path: Q,
+    ) -> Result<u64> {
+        let device_path = std::fs::canonicalize(device_path)?;
+
+        let count = std::fs::read_dir(devcoredump_class.as_ref())
+            .context("no devcoredump support")?
+            .flatten()
+            .filter(|entry| {
+                std::fs::canonicalize(entry.path().join("failing_device"))
+                    .is_ok_and(|failing_device| failing_device == device_path)
+            })
+            .count();
+
+        Ok(count as u64)
+    }
+
+    pub fn gpu_metrics(&self) -> Result<GpuMetrics> {
+        let path = self.sysfs_path.join("device").join("gpu_metrics");
+        trace!("Reading {path:?}…");
+        GpuMetrics::from_bytes(&std::fs::read(&path)?)
+    }
+
+    /// Returns how busy the different engines of the GPU are (as fractions): "gfx" for graphics and compute, "memory"
+    /// for the memory controller and "media" for the video engines.
+    ///
+    /// If `gpu_metrics` is unavailable, only "gfx" containing the aggregated usage is returned.
+    ///
+    /// Note that this can't tell a compute-saturated card apart from a busy graphics ring: none of the known
+    /// `gpu_metrics` layouts contain separate compute or SDMA activity (the graphics activity covers the whole
+    /// graphics/compute block) and amdgpu exposes no device-wide per-ring counters in sysfs. Compute and SDMA usage is
+    /// only available per process through the `drm-engine-compute` and `drm-engine-dma` keys of fdinfo.
+    pub fn ring_usage(&self) -> Result<BTreeMap<String, f64>> {
+        if let Ok(metrics) = self.gpu_metrics() {
+            let rings: BTreeMap<String, f64> = [
+                ("gfx", metrics.average_gfx_activity),
+                ("memory", metrics.average_umc_activity),
+                ("media", metrics.average_mm_activity),
+            ]
+            .into_iter()
+            .filter_map(|(ring, activity)| {
+                activity.map(|activity| (ring.to_string(), f64::from(activity) / 100.0))
+            })
+            .collect();
+
+            if !rings.is_empty() {
+                return Ok(rings);
+            }
+        }
+
+        Ok(BTreeMap::from([("gfx".to_string(), self.usage()?)]))
+    }
 }
 
 impl GpuImpl for AmdGpu {
@@ -109,6 +333,10 @@ impl GpuImpl for AmdGpu {
         self.driver.clone()
     }
 
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Amd
+    }
+
     fn sysfs_path(&self) -> PathBuf {
         self.sysfs_path.clone()
     }
@@ -165,11 +393,73 @@ impl GpuImpl for AmdGpu {
     }
 
     fn core_frequency(&self) -> Result<f64> {
-        self.hwmon_core_frequency()
+        self.hwmon_core_frequency().or_else(|_| {
+            self.clock_domains()?
+                .remove("sclk")
+                .context("no sclk clock domain")
+        })
     }
 
     fn vram_frequency(&self) -> Result<f64> {
-        self.hwmon_vram_frequency()
+        self.hwmon_vram_frequency().or_else(|_| {
+            self.clock_domains()?
+                .remove("mclk")
+                .context("no mclk clock domain")
+        })
+    }
+
+    fn clock_domains(&self) -> Result<BTreeMap<String, f64>> {
+        let domains = Self::read_pp_dpm_clocks(self.sysfs_path.join("device"));
+        if domains.is_empty() {
+            bail!("no pp_dpm_* clock domains found");
+        }
+        Ok(domains)
+    }
+
+    fn board_power_usage(&self) -> Result<f64> {
+        self.gpu_metrics()
+            .ok()
+            .and_then(|metrics| metrics.average_socket_power)
+            .map(|milliwatts| f64::from(milliwatts) / 1000.0)
+            .map_or_else(|| self.hwmon_power_usage(), Ok)
+    }
+
+    fn soc_power_usage(&self) -> Result<f64> {
+        self.gpu_metrics()?
+            .average_soc_power
+            .map(|milliwatts| f64::from(milliwatts) / 1000.0)
+            .context("gpu_metrics doesn't contain the SoC power usage")
+    }
+
+    fn ecc_errors(&self) -> Result<EccErrors> {
+        // RAS is only available on cards with ECC, so consumer cards don't have this directory at all
+        let counters: Vec<PathBuf> = std::fs::read_dir(self.sysfs_path.join("device").join("ras"))
+            .context("no RAS interface found")?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .is_some_and(|file_name| file_name.ends_with("_err_count"))
+            })
+            .collect();
+
+        if counters.is_empty() {
+            bail!("no RAS error counters found");
+        }
+
+        counters
+            .into_iter()
+            .try_fold(EccErrors::default(), |sum, counter| {
+                trace!("Reading {counter:?}…");
+                let errors = Self::parse_ras_err_count(&std::fs::read_to_string(&counter)?)
+                    .with_context(|| format!("unable to parse {counter:?}"))?;
+
+                Ok(EccErrors {
+                    corrected: sum.corrected.saturating_add(errors.corrected),
+                    uncorrected: sum.uncorrected.saturating_add(errors.uncorrected),
+                })
+            })
     }
 
     fn power_cap(&self) -> Result<f64> {
@@ -179,4 +469,188 @@ impl GpuImpl for AmdGpu {
     fn power_cap_max(&self) -> Result<f64> {
         self.hwmon_power_cap_max()
     }
+
+    fn power_cap_default(&self) -> Result<f64> {
+        self.hwmon_power_cap_default()
+    }
+
+    fn set_power_cap(&self, watts: f64) -> Result<()> {
+        Self::write_power_cap(self.first_hwmon().context("no hwmon found")?, watts)
+    }
+
+    fn power_state(&self) -> Result<PowerState> {
+        self.drm_power_state()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{AmdGpu, EccErrors, GpuMetrics};
+
+    fn metrics_table(
+        format_revision: u8,
+        content_revision: u8,
+        values: &[(usize, u16)],
+    ) -> Vec<u8> {
+        let mut bytes = vec![0; 128];
+        bytes[0..2].copy_from_slice(&128_u16.to_le_bytes());
+        bytes[2] = format_revision;
+        bytes[3] = content_revision;
+        for (offset, value) in values {
+            bytes[*offset..*offset + 2].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn gpu_metrics_v1_3() {
+        let bytes = metrics_table(1, 3, &[(16, 87), (18, 12), (20, 0xFFFF), (22, 254)]);
+
+        let expected = GpuMetrics {
+            average_gfx_activity: Some(87),
+            average_umc_activity: Some(12),
+            average_mm_activity: None,
+            average_socket_power: Some(254_000),
+            average_soc_power: None,
+        };
+
+        assert_eq!(expected, GpuMetrics::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn gpu_metrics_v2_1() {
+        let bytes = metrics_table(2, 1, &[(40, 33), (42, 5), (44, 15_250), (48, 3_100)]);
+
+        let expected = GpuMetrics {
+            average_gfx_activity: Some(33),
+            average_umc_activity: None,
+            average_mm_activity: Some(5),
+            average_socket_power: Some(15_250),
+            average_soc_power: Some(3_100),
+        };
+
+        assert_eq!(expected, GpuMetrics::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn gpu_metrics_unsupported() {
+        let bytes = metrics_table(3, 0, &[]);
+
+        assert!(GpuMetrics::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn gpu_metrics_too_short() {
+        assert!(GpuMetrics::from_bytes(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn active_dpm_level() {
+        assert_eq!(
+            Some("0: 96Mhz".to_string()),
+            AmdGpu::active_dpm_level("0: 96Mhz *\n1: 456Mhz \n2: 1249Mhz \n")
+        );
+        assert_eq!(
+            Some("2: 1249Mhz".to_string()),
+            AmdGpu::active_dpm_level("0: 96Mhz \n1: 456Mhz \n2: 1249Mhz *\n")
+        );
+        assert_eq!(None, AmdGpu::active_dpm_level("0: 96Mhz \n1: 456Mhz \n"));
+    }
+
+    #[test]
+    fn pp_dpm_clocks() {
+        let device_path =
+            std::env::temp_dir().join(format!("resources-pp-dpm-{}", std::process::id()));
+        std::fs::create_dir_all(&device_path).unwrap();
+
+        for (file, content) in [
+            ("pp_dpm_sclk", "0: 500Mhz \n1: 1850Mhz *\n2: 2615Mhz \n"),
+            ("pp_dpm_mclk", "0: 96Mhz \n1: 456Mhz \n2: 1249Mhz *\n"),
+            ("pp_dpm_fclk", "0: 1940Mhz *\n"),
+            ("pp_dpm_socclk", "0: 500Mhz \n1: 1200Mhz \n"),
+            (
+                "pp_dpm_pcie",
+                "0: 2.5GT/s, x1 310Mhz \n1: 16.0GT/s, x16 619Mhz *\n",
+            ),
+        ] {
+            std::fs::write(device_path.join(file), content).unwrap();
+        }
+
+        let clocks = AmdGpu::read_pp_dpm_clocks(&device_path);
+
+        std::fs::remove_dir_all(&device_path).unwrap();
+
+        let expected = BTreeMap::from([
+            ("fclk".to_string(), 1_940_000_000.0),
+            ("mclk".to_string(), 1_249_000_000.0),
+            ("sclk".to_string(), 1_850_000_000.0),
+        ]);
+
+        assert_eq!(expected, clocks);
+    }
+
+    #[test]
+    fn parse_ras_err_count() {
+        assert_eq!(
+            Some(EccErrors {
+                corrected: 12,
+                uncorrected: 1,
+            }),
+            AmdGpu::parse_ras_err_count("ue: 1\nce: 12\n")
+        );
+        assert_eq!(None, AmdGpu::parse_ras_err_count("ue: many\n"));
+    }
+
+    #[test]
+    fn write_power_cap() {
+        let hwmon_path =
+            std::env::temp_dir().join(format!("resources-power-cap-{}", std::process::id()));
+        std::fs::create_dir_all(&hwmon_path).unwrap();
+        std::fs::write(hwmon_path.join("power1_cap_max"), "300000000\n").unwrap();
+        std::fs::write(hwmon_path.join("power1_cap"), "200000000\n").unwrap();
+
+        let valid = AmdGpu::write_power_cap(&hwmon_path, 250.0);
+        let too_high = AmdGpu::write_power_cap(&hwmon_path, 350.0);
+        let power_cap = std::fs::read_to_string(hwmon_path.join("power1_cap")).unwrap();
+
+        std::fs::remove_dir_all(&hwmon_path).unwrap();
+
+        assert!(valid.is_ok());
+        assert!(too_high.is_err());
+        assert_eq!("250000000", power_cap);
+    }
+
+    #[test]
+    fn count_devcoredumps() {
+        let root =
+            std::env::temp_dir().join(format!("resources-devcoredump-{}", std::process::id()));
+        let gpu = root.join("devices/0000:03:00.0");
+        let other_gpu = root.join("devices/0000:0a:00.0");
+        let class = root.join("class/devcoredump");
+
+        for dir in [
+            &gpu,
+            &other_gpu,
+            &class.join("devcd1"),
+            &class.join("devcd2"),
+            &class.join("devcd3"),
+        ] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::os::unix::fs::symlink(&gpu, class.join("devcd1/failing_device")).unwrap();
+        std::os::unix::fs::symlink(&other_gpu, class.join("devcd2/failing_device")).unwrap();
+        std::os::unix::fs::symlink(&gpu, class.join("devcd3/failing_device")).unwrap();
+
+        let count = AmdGpu::count_devcoredumps(&class, &gpu);
+        let missing = AmdGpu::count_devcoredumps(root.join("class/nonexistent"), &gpu);
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(2, count.unwrap());
+        assert!(missing.is_err());
+    }
 }
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use crate::utils::pci::Device;
 
-use super::GpuImpl;
+use super::{GpuImpl, GpuVendor, PowerState};
 
 #[derive(Debug, Clone, Default)]
 
@@ -48,6 +48,10 @@ impl GpuImpl for IntelGpu {
         self.driver.clone()
     }
 
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Intel
+    }
+
     fn sysfs_path(&self) -> PathBuf {
         self.sysfs_path.clone()
     }
@@ -85,7 +89,13 @@ impl GpuImpl for IntelGpu {
     }
 
     fn temperature(&self) -> Result<f64> {
-        self.hwmon_temperature()
+        // discrete GPUs label their sensors, integrated GPUs don't have a dedicated sensor at all
+        if self.first_hwmon_path.is_none() {
+            bail!("no dedicated temperature sensor for this Intel GPU");
+        }
+
+        self.hwmon_labeled_temperature(&["pkg", "vram"])
+            .or_else(|_| self.hwmon_temperature())
     }
 
     fn power_usage(&self) -> Result<f64> {
@@ -107,4 +117,8 @@ impl GpuImpl for IntelGpu {
     fn power_cap_max(&self) -> Result<f64> {
         self.hwmon_power_cap_max()
     }
+
+    fn power_state(&self) -> Result<PowerState> {
+        self.drm_power_state()
+    }
 }
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result, bail};
+use glob::glob;
+use process_data::GpuIdentifier;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::utils::{pci::Device, read_sysfs_isize};
+
+use super::{GpuImpl, GpuVendor, PowerState};
+
+/// Intel GPUs driven by the xe driver (Lunar Lake, Battlemage and newer), which doesn't share i915's sysfs interface
+#[derive(Debug, Clone, Default)]
+
+pub struct IntelXeGpu {
+    pub device: Option<&'static Device>,
+    pub gpu_identifier: GpuIdentifier,
+    pub driver: String,
+    sysfs_path: PathBuf,
+    first_hwmon_path: Option<PathBuf>,
+    /// xe only exposes an energy counter rather than the current power, so we remember the last reading
+    last_energy: Arc<Mutex<Option<(Instant, isize)>>>,
+}
+
+impl IntelXeGpu {
+    pub fn new(
+        device: Option<&'static Device>,
+        gpu_identifier: GpuIdentifier,
+        driver: String,
+        sysfs_path: PathBuf,
+        first_hwmon_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            device,
+            gpu_identifier,
+            driver,
+            sysfs_path,
+            first_hwmon_path,
+            last_energy: Arc::default(),
+        }
+    }
+
+    /// Returns the paths of the frequency directories of all GTs (graphics and media) of all tiles, e.g.
+    /// `device/tile0/gt0/freq0`
+    fn gt_frequency_paths(&self) -> Vec<PathBuf> {
+        glob(&format!(
+            "{}/device/tile*/gt*/freq0",
+            self.sysfs_path.to_string_lossy()
+        ))
+        .map(|paths| paths.flatten().collect())
+        .unwrap_or_default()
+    }
+
+    /// Calculates the average power in W drawn since the last call from the energy counter (in µJ) of the hwmon
+    fn energy_power_usage(&self) -> Result<f64> {
+        let energy = self.read_hwmon_int("energy1_input")?;
+        let now = Instant::now();
+
+        let last_energy = self
+            .last_energy
+            .lock()
+            .unwrap()
+            .replace((now, energy))
+            .context("no previous energy reading")?;
+
+        Self::average_power(last_energy, (now, energy))
+    }
+
+    fn average_power(last: (Instant, isize), current: (Instant, isize)) -> Result<f64> {
+        let elapsed = current.0.duration_since(last.0).as_secs_f64();
+        if elapsed <= 0.0 || current.1 < last.1 {
+            bail!("unable to calculate power from energy readings");
+        }
+
+        Ok((current.1 - last.1) as f64 / 1_000_000.0 / elapsed)
+    }
+}
+
+impl GpuImpl for IntelXeGpu {
+    fn device(&self) -> Option<&'static Device> {
+        self.device
+    }
+
+    fn gpu_identifier(&self) -> GpuIdentifier {
+        self.gpu_identifier
+    }
+
+    fn driver(&self) -> String {
+        self.driver.clone()
+    }
+
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Intel
+    }
+
+    fn sysfs_path(&self) -> PathBuf {
+        self.sysfs_path.clone()
+    }
+
+    fn first_hwmon(&self) -> Option<PathBuf> {
+        self.first_hwmon_path.clone()
+    }
+
+    fn name(&self) -> Result<String> {
+        self.drm_name()
+    }
+
+    fn usage(&self) -> Result<f64> {
+        // xe has no gpu_busy_percent, the usage is derived from the fdinfo cycles of the processes instead
+        bail!("usage is only available through processes for xe")
+    }
+
+    fn encode_usage(&self) -> Result<f64> {
+        bail!("encode usage not implemented for Intel")
+    }
+
+    fn decode_usage(&self) -> Result<f64> {
+        bail!("decode usage not implemented for Intel")
+    }
+
+    fn combined_media_engine(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn used_vram(&self) -> Result<usize> {
+        // xe has no equivalent to amdgpu's mem_info_vram_used, only the total size of each tile's VRAM
+        bail!("used VRAM is not exposed by xe")
+    }
+
+    fn total_vram(&self) -> Result<usize> {
+        // integrated GPUs don't have any VRAM and thus no tiles with VRAM
+        let total_vram = glob(&format!(
+            "{}/device/tile*/physical_vram_size_bytes",
+            self.sysfs_path.to_string_lossy()
+        ))?
+        .flatten()
+        .filter_map(|path| read_sysfs_isize(path).ok())
+        .sum::<isize>();
+
+        if total_vram > 0 {
+            Ok(total_vram as usize)
+        } else {
+            bail!("no tiles with VRAM")
+        }
+    }
+
+    fn temperature(&self) -> Result<f64> {
+        if self.first_hwmon_path.is_none() {
+            bail!("no dedicated temperature sensor for this Intel GPU");
+        }
+
+        self.hwmon_labeled_temperature(&["pkg", "vram"])
+            .or_else(|_| self.hwmon_temperature())
+    }
+
+    fn power_usage(&self) -> Result<f64> {
+        self.hwmon_power_usage()
+            .or_else(|_| self.energy_power_usage())
+    }
+
+    fn core_frequency(&self) -> Result<f64> {
+        // act_freq is the frequency the GT is actually running at (0 while it's idle), cur_freq the requested one.
+        // The graphics GT comes first, media GTs are only used if there is none.
+        self.gt_frequency_paths()
+            .into_iter()
+            .find_map(|path| {
+                read_sysfs_isize(path.join("act_freq"))
+                    .or_else(|_| read_sysfs_isize(path.join("cur_freq")))
+                    .ok()
+            })
+            .map(|mhz| mhz as f64 * 1_000_000.0)
+            .context("no GT frequency found")
+    }
+
+    fn vram_frequency(&self) -> Result<f64> {
+        self.hwmon_vram_frequency()
+    }
+
+    fn power_cap(&self) -> Result<f64> {
+        self.hwmon_power_cap()
+            .or_else(|_| Ok(self.read_hwmon_int("power1_max")? as f64 / 1_000_000.0))
+    }
+
+    fn power_cap_max(&self) -> Result<f64> {
+        self.hwmon_power_cap_max()
+    }
+
+    fn power_state(&self) -> Result<PowerState> {
+        self.drm_power_state()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use std::time::{Duration, Instant};
+
+    use super::IntelXeGpu;
+
+    #[test]
+    fn average_power() {
+        let start = Instant::now();
+
+        assert_eq!(
+            12.5,
+            IntelXeGpu::average_power(
+                (start, 1_000_000),
+                (start + Duration::from_secs(2), 26_000_000)
+            )
+            .unwrap()
+        );
+        // the counter wrapped around or was reset
+        assert!(
+            IntelXeGpu::average_power(
+                (start, 26_000_000),
+                (start + Duration::from_secs(2), 1_000_000)
+            )
+            .is_err()
+        );
+        assert!(IntelXeGpu::average_power((start, 0), (start, 1_000_000)).is_err());
+    }
+}
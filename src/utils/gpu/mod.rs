@@ -1,5 +1,7 @@
 mod amd;
 mod intel;
+mod intel_xe;
+mod nouveau;
 mod nvidia;
 mod other;
 mod v3d;
@@ -11,15 +13,20 @@ use process_data::{GpuIdentifier, pci_slot::PciSlot};
 use v3d::V3dGpu;
 
 use std::{
+    collections::BTreeMap,
+    fmt::Display,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
-use self::{amd::AmdGpu, intel::IntelGpu, nvidia::NvidiaGpu, other::OtherGpu};
+use self::{
+    amd::AmdGpu, intel::IntelGpu, intel_xe::IntelXeGpu, nouveau::NouveauGpu, nvidia::NvidiaGpu,
+    other::OtherGpu,
+};
 use crate::utils::link::{Link, LinkData};
 use crate::{
     i18n::i18n,
-    utils::{pci::Device, read_uevent},
+    utils::{pci::Device, read_sysfs_isize, read_sysfs_string, read_uevent},
 };
 use glob::glob;
 
@@ -113,11 +120,70 @@ impl GpuData {
 pub enum Gpu {
     Amd(AmdGpu),
     Intel(IntelGpu),
+    IntelXe(IntelXeGpu),
     Nvidia(NvidiaGpu),
+    Nouveau(NouveauGpu),
     V3d(V3dGpu),
     Other(OtherGpu),
 }
 
+/// Manufacturer of a GPU, as determined by the backend that drives it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GpuVendor {
+    Amd,
+    Intel,
+    Nvidia,
+    Other,
+}
+
+/// Numbers of memory errors a GPU has detected and corrected (or failed to correct) using ECC since the driver was
+/// loaded. A rising number of uncorrected errors is a sign of failing VRAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EccErrors {
+    pub corrected: u64,
+    pub uncorrected: u64,
+}
+
+/// Runtime power management state of a GPU as reported by the kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerState {
+    Active,
+    Suspended,
+    Suspending,
+    #[default]
+    Unknown,
+}
+
+impl FromStr for PowerState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let state = match s.trim() {
+            "active" | "resuming" => PowerState::Active,
+            "suspended" => PowerState::Suspended,
+            "suspending" => PowerState::Suspending,
+            _ => PowerState::Unknown,
+        };
+
+        Ok(state)
+    }
+}
+
+impl Display for PowerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PowerState::Active => i18n("Active"),
+                PowerState::Suspended => i18n("Suspended"),
+                PowerState::Suspending => i18n("Suspending"),
+                PowerState::Unknown => i18n("Unknown"),
+            }
+        )
+    }
+}
+
 impl Default for Gpu {
     fn default() -> Self {
         Gpu::Other(OtherGpu::default())
@@ -128,6 +194,7 @@ pub trait GpuImpl {
     fn device(&self) -> Option<&'static Device>;
     fn gpu_identifier(&self) -> GpuIdentifier;
     fn driver(&self) -> String;
+    fn vendor(&self) -> GpuVendor;
     fn sysfs_path(&self) -> PathBuf;
     fn first_hwmon(&self) -> Option<PathBuf>;
 
@@ -144,36 +211,80 @@ pub trait GpuImpl {
     fn vram_frequency(&self) -> Result<f64>;
     fn power_cap(&self) -> Result<f64>;
     fn power_cap_max(&self) -> Result<f64>;
+    fn power_state(&self) -> Result<PowerState>;
+
+    /// Sets the power cap of the GPU to `watts`, which must not exceed `power_cap_max()`.
+    ///
+    /// This requires root privileges, if they're missing the returned error can be downcast to a `std::io::Error`
+    /// of kind `PermissionDenied` so that callers can ask for elevation.
+    fn set_power_cap(&self, _watts: f64) -> Result<()> {
+        bail!("setting the power cap is not supported by this GPU")
+    }
+
+    /// Power cap in watts that the GPU uses unless the user sets another one, see `power_cap()` for the current one
+    fn power_cap_default(&self) -> Result<f64> {
+        bail!("default power cap is not supported by this GPU")
+    }
+
+    /// Power draw of the whole board in watts (i.e. what a wattmeter would attribute to the card), as opposed to
+    /// `power_usage()` which may only cover the GPU chip itself depending on the vendor
+    fn board_power_usage(&self) -> Result<f64> {
+        bail!("board power usage is not supported by this GPU")
+    }
+
+    /// Power draw of the SoC rail in watts
+    fn soc_power_usage(&self) -> Result<f64> {
+        bail!("SoC power usage is not supported by this GPU")
+    }
+
+    /// Returns the number of memory errors detected through ECC, fails for GPUs without ECC (i.e. most consumer cards)
+    fn ecc_errors(&self) -> Result<EccErrors> {
+        bail!("ECC error counters are not supported by this GPU")
+    }
+
+    /// Returns the current frequency (in Hz) of every clock domain of the GPU that is known to us, keyed by the name
+    /// of the domain. Unless a backend knows about more, this consists of "core" and "memory".
+    fn clock_domains(&self) -> Result<BTreeMap<String, f64>> {
+        let domains: BTreeMap<String, f64> = [
+            ("core", self.core_frequency()),
+            ("memory", self.vram_frequency()),
+        ]
+        .into_iter()
+        .filter_map(|(domain, frequency)| {
+            frequency
+                .ok()
+                .map(|frequency| (domain.to_string(), frequency))
+        })
+        .collect();
+
+        if domains.is_empty() {
+            bail!("no clock domains found");
+        }
+
+        Ok(domains)
+    }
+
+    /// Whether this GPU was used by the firmware to drive the primary display during boot. Only PCI GPUs expose
+    /// this, so it's `false` for everything else.
+    fn is_boot_vga(&self) -> bool {
+        self.read_device_int("boot_vga")
+            .is_ok_and(|boot_vga| boot_vga == 1)
+    }
 
     fn read_sysfs_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.sysfs_path().join(file);
-        trace!("Reading {path:?}…");
-        std::fs::read_to_string(&path)?
-            .replace('\n', "")
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.sysfs_path().join(file))
     }
 
     fn read_device_file<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<String> {
-        let path = self.sysfs_path().join("device").join(file);
-        trace!("Reading {path:?}…");
-        Ok(std::fs::read_to_string(path)?.replace('\n', ""))
+        read_sysfs_string(self.sysfs_path().join("device").join(file))
     }
 
     fn read_device_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.sysfs_path().join("device").join(file);
-        self.read_device_file(&path)?
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.sysfs_path().join("device").join(file))
     }
 
     fn read_hwmon_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.first_hwmon().context("no hwmon found")?.join(file);
-        trace!("Reading {path:?}…");
-        std::fs::read_to_string(&path)?
-            .replace('\n', "")
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.first_hwmon().context("no hwmon found")?.join(file))
     }
 
     // These are preimplemented ways of getting information through the DRM and hwmon interface.
@@ -195,10 +306,39 @@ pub trait GpuImpl {
         self.read_device_int("mem_info_vram_total")
     }
 
+    fn drm_power_state(&self) -> Result<PowerState> {
+        self.read_device_file("power/runtime_status")?.parse()
+    }
+
     fn hwmon_temperature(&self) -> Result<f64> {
         Ok(self.read_hwmon_int("temp1_input")? as f64 / 1000.0)
     }
 
+    /// Reads the temperature of the first hwmon sensor whose `temp*_label` matches one of `labels`, in the order of
+    /// `labels`
+    fn hwmon_labeled_temperature(&self, labels: &[&str]) -> Result<f64> {
+        let hwmon = self.first_hwmon().context("no hwmon found")?;
+
+        let sensors: Vec<(String, PathBuf)> =
+            glob(&format!("{}/temp*_label", hwmon.to_string_lossy()))?
+                .flatten()
+                .filter_map(|label_path| {
+                    let label = read_sysfs_string(&label_path).ok()?;
+                    let input_path =
+                        PathBuf::from(label_path.to_string_lossy().replace("_label", "_input"));
+                    Some((label, input_path))
+                })
+                .collect();
+
+        for label in labels {
+            if let Some((_, input_path)) = sensors.iter().find(|(sensor, _)| sensor == label) {
+                return Ok(read_sysfs_isize(input_path)? as f64 / 1000.0);
+            }
+        }
+
+        bail!("no temperature sensor labeled {labels:?} found")
+    }
+
     fn hwmon_power_usage(&self) -> Result<f64> {
         Ok(self
             .read_hwmon_int("power1_average")
@@ -221,6 +361,10 @@ pub trait GpuImpl {
     fn hwmon_power_cap_max(&self) -> Result<f64> {
         Ok(self.read_hwmon_int("power1_cap_max")? as f64 / 1_000_000.0)
     }
+
+    fn hwmon_power_cap_default(&self) -> Result<f64> {
+        Ok(self.read_hwmon_int("power1_cap_default")? as f64 / 1_000_000.0)
+    }
 }
 
 impl Gpu {
@@ -311,6 +455,17 @@ impl Gpu {
                 )),
                 "AMD",
             )
+        } else if driver == "xe" {
+            (
+                Gpu::IntelXe(IntelXeGpu::new(
+                    device,
+                    gpu_identifier,
+                    driver,
+                    path.to_path_buf(),
+                    hwmon_vec.first().cloned(),
+                )),
+                "Intel (xe)",
+            )
         } else if vid == VID_INTEL || driver == "i915" {
             (
                 Gpu::Intel(IntelGpu::new(
@@ -322,6 +477,17 @@ impl Gpu {
                 )),
                 "Intel",
             )
+        } else if driver == "nouveau" {
+            (
+                Gpu::Nouveau(NouveauGpu::new(
+                    device,
+                    gpu_identifier,
+                    driver,
+                    path.to_path_buf(),
+                    hwmon_vec.first().cloned(),
+                )),
+                "nouveau",
+            )
         } else if vid == VID_NVIDIA || driver == "nvidia" {
             (
                 Gpu::Nvidia(NvidiaGpu::new(
@@ -372,7 +538,9 @@ impl Gpu {
         Ok(match self {
             Gpu::Amd(gpu) => gpu.device(),
             Gpu::Intel(gpu) => gpu.device(),
+            Gpu::IntelXe(gpu) => gpu.device(),
             Gpu::Nvidia(gpu) => gpu.device(),
+            Gpu::Nouveau(gpu) => gpu.device(),
             Gpu::V3d(gpu) => gpu.device(),
             Gpu::Other(gpu) => gpu.device(),
         }
@@ -384,7 +552,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.gpu_identifier(),
             Gpu::Intel(gpu) => gpu.gpu_identifier(),
+            Gpu::IntelXe(gpu) => gpu.gpu_identifier(),
             Gpu::Nvidia(gpu) => gpu.gpu_identifier(),
+            Gpu::Nouveau(gpu) => gpu.gpu_identifier(),
             Gpu::V3d(gpu) => gpu.gpu_identifier(),
             Gpu::Other(gpu) => gpu.gpu_identifier(),
         }
@@ -394,17 +564,33 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.driver(),
             Gpu::Intel(gpu) => gpu.driver(),
+            Gpu::IntelXe(gpu) => gpu.driver(),
             Gpu::Nvidia(gpu) => gpu.driver(),
+            Gpu::Nouveau(gpu) => gpu.driver(),
             Gpu::V3d(gpu) => gpu.driver(),
             Gpu::Other(gpu) => gpu.driver(),
         }
     }
 
+    pub fn vendor(&self) -> GpuVendor {
+        match self {
+            Gpu::Amd(gpu) => gpu.vendor(),
+            Gpu::Intel(gpu) => gpu.vendor(),
+            Gpu::IntelXe(gpu) => gpu.vendor(),
+            Gpu::Nvidia(gpu) => gpu.vendor(),
+            Gpu::Nouveau(gpu) => gpu.vendor(),
+            Gpu::V3d(gpu) => gpu.vendor(),
+            Gpu::Other(gpu) => gpu.vendor(),
+        }
+    }
+
     pub fn name(&self) -> Result<String> {
         match self {
             Gpu::Amd(gpu) => gpu.name(),
             Gpu::Intel(gpu) => gpu.name(),
+            Gpu::IntelXe(gpu) => gpu.name(),
             Gpu::Nvidia(gpu) => gpu.name(),
+            Gpu::Nouveau(gpu) => gpu.name(),
             Gpu::V3d(gpu) => gpu.name(),
             Gpu::Other(gpu) => gpu.name(),
         }
@@ -414,7 +600,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.usage(),
             Gpu::Intel(gpu) => gpu.usage(),
+            Gpu::IntelXe(gpu) => gpu.usage(),
             Gpu::Nvidia(gpu) => gpu.usage(),
+            Gpu::Nouveau(gpu) => gpu.usage(),
             Gpu::V3d(gpu) => gpu.usage(),
             Gpu::Other(gpu) => gpu.usage(),
         }
@@ -424,7 +612,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.encode_usage(),
             Gpu::Intel(gpu) => gpu.encode_usage(),
+            Gpu::IntelXe(gpu) => gpu.encode_usage(),
             Gpu::Nvidia(gpu) => gpu.encode_usage(),
+            Gpu::Nouveau(gpu) => gpu.encode_usage(),
             Gpu::V3d(gpu) => gpu.encode_usage(),
             Gpu::Other(gpu) => gpu.encode_usage(),
         }
@@ -434,7 +624,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.decode_usage(),
             Gpu::Intel(gpu) => gpu.decode_usage(),
+            Gpu::IntelXe(gpu) => gpu.decode_usage(),
             Gpu::Nvidia(gpu) => gpu.decode_usage(),
+            Gpu::Nouveau(gpu) => gpu.decode_usage(),
             Gpu::V3d(gpu) => gpu.decode_usage(),
             Gpu::Other(gpu) => gpu.decode_usage(),
         }
@@ -444,7 +636,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.combined_media_engine(),
             Gpu::Intel(gpu) => gpu.combined_media_engine(),
+            Gpu::IntelXe(gpu) => gpu.combined_media_engine(),
             Gpu::Nvidia(gpu) => gpu.combined_media_engine(),
+            Gpu::Nouveau(gpu) => gpu.combined_media_engine(),
             Gpu::V3d(gpu) => gpu.combined_media_engine(),
             Gpu::Other(gpu) => gpu.combined_media_engine(),
         }
@@ -454,7 +648,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.used_vram(),
             Gpu::Intel(gpu) => gpu.used_vram(),
+            Gpu::IntelXe(gpu) => gpu.used_vram(),
             Gpu::Nvidia(gpu) => gpu.used_vram(),
+            Gpu::Nouveau(gpu) => gpu.used_vram(),
             Gpu::V3d(gpu) => gpu.used_vram(),
             Gpu::Other(gpu) => gpu.used_vram(),
         }
@@ -464,7 +660,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.total_vram(),
             Gpu::Intel(gpu) => gpu.total_vram(),
+            Gpu::IntelXe(gpu) => gpu.total_vram(),
             Gpu::Nvidia(gpu) => gpu.total_vram(),
+            Gpu::Nouveau(gpu) => gpu.total_vram(),
             Gpu::V3d(gpu) => gpu.total_vram(),
             Gpu::Other(gpu) => gpu.total_vram(),
         }
@@ -474,7 +672,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.temperature(),
             Gpu::Intel(gpu) => gpu.temperature(),
+            Gpu::IntelXe(gpu) => gpu.temperature(),
             Gpu::Nvidia(gpu) => gpu.temperature(),
+            Gpu::Nouveau(gpu) => gpu.temperature(),
             Gpu::V3d(gpu) => gpu.temperature(),
             Gpu::Other(gpu) => gpu.temperature(),
         }
@@ -484,7 +684,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.power_usage(),
             Gpu::Intel(gpu) => gpu.power_usage(),
+            Gpu::IntelXe(gpu) => gpu.power_usage(),
             Gpu::Nvidia(gpu) => gpu.power_usage(),
+            Gpu::Nouveau(gpu) => gpu.power_usage(),
             Gpu::V3d(gpu) => gpu.power_usage(),
             Gpu::Other(gpu) => gpu.power_usage(),
         }
@@ -494,7 +696,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.core_frequency(),
             Gpu::Intel(gpu) => gpu.core_frequency(),
+            Gpu::IntelXe(gpu) => gpu.core_frequency(),
             Gpu::Nvidia(gpu) => gpu.core_frequency(),
+            Gpu::Nouveau(gpu) => gpu.core_frequency(),
             Gpu::V3d(gpu) => gpu.core_frequency(),
             Gpu::Other(gpu) => gpu.core_frequency(),
         }
@@ -504,7 +708,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.vram_frequency(),
             Gpu::Intel(gpu) => gpu.vram_frequency(),
+            Gpu::IntelXe(gpu) => gpu.vram_frequency(),
             Gpu::Nvidia(gpu) => gpu.vram_frequency(),
+            Gpu::Nouveau(gpu) => gpu.vram_frequency(),
             Gpu::V3d(gpu) => gpu.vram_frequency(),
             Gpu::Other(gpu) => gpu.vram_frequency(),
         }
@@ -514,7 +720,9 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.power_cap(),
             Gpu::Intel(gpu) => gpu.power_cap(),
+            Gpu::IntelXe(gpu) => gpu.power_cap(),
             Gpu::Nvidia(gpu) => gpu.power_cap(),
+            Gpu::Nouveau(gpu) => gpu.power_cap(),
             Gpu::V3d(gpu) => gpu.power_cap(),
             Gpu::Other(gpu) => gpu.power_cap(),
         }
@@ -524,12 +732,110 @@ impl Gpu {
         match self {
             Gpu::Amd(gpu) => gpu.power_cap_max(),
             Gpu::Intel(gpu) => gpu.power_cap_max(),
+            Gpu::IntelXe(gpu) => gpu.power_cap_max(),
             Gpu::Nvidia(gpu) => gpu.power_cap_max(),
+            Gpu::Nouveau(gpu) => gpu.power_cap_max(),
             Gpu::V3d(gpu) => gpu.power_cap_max(),
             Gpu::Other(gpu) => gpu.power_cap_max(),
         }
     }
 
+    pub fn set_power_cap(&self, watts: f64) -> Result<()> {
+        match self {
+            Gpu::Amd(gpu) => gpu.set_power_cap(watts),
+            Gpu::Intel(gpu) => gpu.set_power_cap(watts),
+            Gpu::IntelXe(gpu) => gpu.set_power_cap(watts),
+            Gpu::Nvidia(gpu) => gpu.set_power_cap(watts),
+            Gpu::Nouveau(gpu) => gpu.set_power_cap(watts),
+            Gpu::V3d(gpu) => gpu.set_power_cap(watts),
+            Gpu::Other(gpu) => gpu.set_power_cap(watts),
+        }
+    }
+
+    pub fn power_cap_default(&self) -> Result<f64> {
+        match self {
+            Gpu::Amd(gpu) => gpu.power_cap_default(),
+            Gpu::Intel(gpu) => gpu.power_cap_default(),
+            Gpu::IntelXe(gpu) => gpu.power_cap_default(),
+            Gpu::Nvidia(gpu) => gpu.power_cap_default(),
+            Gpu::Nouveau(gpu) => gpu.power_cap_default(),
+            Gpu::V3d(gpu) => gpu.power_cap_default(),
+            Gpu::Other(gpu) => gpu.power_cap_default(),
+        }
+    }
+
+    pub fn power_state(&self) -> Result<PowerState> {
+        match self {
+            Gpu::Amd(gpu) => gpu.power_state(),
+            Gpu::Intel(gpu) => gpu.power_state(),
+            Gpu::IntelXe(gpu) => gpu.power_state(),
+            Gpu::Nvidia(gpu) => gpu.power_state(),
+            Gpu::Nouveau(gpu) => gpu.power_state(),
+            Gpu::V3d(gpu) => gpu.power_state(),
+            Gpu::Other(gpu) => gpu.power_state(),
+        }
+    }
+
+    pub fn board_power_usage(&self) -> Result<f64> {
+        match self {
+            Gpu::Amd(gpu) => gpu.board_power_usage(),
+            Gpu::Intel(gpu) => gpu.board_power_usage(),
+            Gpu::IntelXe(gpu) => gpu.board_power_usage(),
+            Gpu::Nvidia(gpu) => gpu.board_power_usage(),
+            Gpu::Nouveau(gpu) => gpu.board_power_usage(),
+            Gpu::V3d(gpu) => gpu.board_power_usage(),
+            Gpu::Other(gpu) => gpu.board_power_usage(),
+        }
+    }
+
+    pub fn soc_power_usage(&self) -> Result<f64> {
+        match self {
+            Gpu::Amd(gpu) => gpu.soc_power_usage(),
+            Gpu::Intel(gpu) => gpu.soc_power_usage(),
+            Gpu::IntelXe(gpu) => gpu.soc_power_usage(),
+            Gpu::Nvidia(gpu) => gpu.soc_power_usage(),
+            Gpu::Nouveau(gpu) => gpu.soc_power_usage(),
+            Gpu::V3d(gpu) => gpu.soc_power_usage(),
+            Gpu::Other(gpu) => gpu.soc_power_usage(),
+        }
+    }
+
+    pub fn ecc_errors(&self) -> Result<EccErrors> {
+        match self {
+            Gpu::Amd(gpu) => gpu.ecc_errors(),
+            Gpu::Intel(gpu) => gpu.ecc_errors(),
+            Gpu::IntelXe(gpu) => gpu.ecc_errors(),
+            Gpu::Nvidia(gpu) => gpu.ecc_errors(),
+            Gpu::Nouveau(gpu) => gpu.ecc_errors(),
+            Gpu::V3d(gpu) => gpu.ecc_errors(),
+            Gpu::Other(gpu) => gpu.ecc_errors(),
+        }
+    }
+
+    pub fn clock_domains(&self) -> Result<BTreeMap<String, f64>> {
+        match self {
+            Gpu::Amd(gpu) => gpu.clock_domains(),
+            Gpu::Intel(gpu) => gpu.clock_domains(),
+            Gpu::IntelXe(gpu) => gpu.clock_domains(),
+            Gpu::Nvidia(gpu) => gpu.clock_domains(),
+            Gpu::Nouveau(gpu) => gpu.clock_domains(),
+            Gpu::V3d(gpu) => gpu.clock_domains(),
+            Gpu::Other(gpu) => gpu.clock_domains(),
+        }
+    }
+
+    pub fn is_boot_vga(&self) -> bool {
+        match self {
+            Gpu::Amd(gpu) => gpu.is_boot_vga(),
+            Gpu::Intel(gpu) => gpu.is_boot_vga(),
+            Gpu::IntelXe(gpu) => gpu.is_boot_vga(),
+            Gpu::Nvidia(gpu) => gpu.is_boot_vga(),
+            Gpu::Nouveau(gpu) => gpu.is_boot_vga(),
+            Gpu::V3d(gpu) => gpu.is_boot_vga(),
+            Gpu::Other(gpu) => gpu.is_boot_vga(),
+        }
+    }
+
     pub fn link(&self) -> Result<Link> {
         if let GpuIdentifier::PciSlot(pci_slot) = self.gpu_identifier() {
             let pcie_link = LinkData::from_pci_slot(&pci_slot)?;
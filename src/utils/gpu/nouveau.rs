@@ -0,0 +1,184 @@
+use anyhow::{Context, Result, bail};
+use lazy_regex::{Lazy, Regex, lazy_regex};
+use process_data::GpuIdentifier;
+
+use std::path::PathBuf;
+
+use crate::utils::pci::Device;
+
+use super::{GpuImpl, GpuVendor, PowerState};
+
+static RE_PSTATE_CORE: Lazy<Regex> = lazy_regex!(r"core (\d+)(?:-\d+)? MHz");
+
+static RE_PSTATE_MEMORY: Lazy<Regex> = lazy_regex!(r"memory (\d+)(?:-\d+)? MHz");
+
+/// NVIDIA GPUs driven by the open-source nouveau driver, which (unlike the proprietary driver) can't be queried
+/// through NVML
+#[derive(Debug, Clone, Default)]
+
+pub struct NouveauGpu {
+    pub device: Option<&'static Device>,
+    pub gpu_identifier: GpuIdentifier,
+    pub driver: String,
+    sysfs_path: PathBuf,
+    first_hwmon_path: Option<PathBuf>,
+}
+
+impl NouveauGpu {
+    pub fn new(
+        device: Option<&'static Device>,
+        gpu_identifier: GpuIdentifier,
+        driver: String,
+        sysfs_path: PathBuf,
+        first_hwmon_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            device,
+            gpu_identifier,
+            driver,
+            sysfs_path,
+            first_hwmon_path,
+        }
+    }
+
+    /// Reads the performance level table of nouveau, which older kernels expose in sysfs and newer ones only in
+    /// debugfs (readable by root only)
+    fn pstate(&self) -> Result<String> {
+        self.read_device_file("pstate").or_else(|_| {
+            let card = self
+                .sysfs_path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_prefix("card"))
+                .context("unable to determine DRM card number")?;
+            std::fs::read_to_string(format!("/sys/kernel/debug/dri/{card}/pstate"))
+                .context("unable to read pstate from debugfs")
+        })
+    }
+
+    /// Returns the current frequency in Hz of the clock domain matched by `regex` from nouveau's pstate table, whose
+    /// `AC` line contains the clocks the GPU is running at right now, e.g. `AC: core 405 MHz memory 810 MHz`
+    fn parse_pstate_frequency(pstate: &str, regex: &Regex) -> Result<f64> {
+        let current = pstate
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("AC:"))
+            .context("no current clocks in pstate")?;
+
+        regex
+            .captures(current)
+            .and_then(|captures| captures.get(1))
+            .and_then(|capture| capture.as_str().parse::<f64>().ok())
+            .map(|mhz| mhz * 1_000_000.0)
+            .context("clock domain not found in pstate")
+    }
+}
+
+impl GpuImpl for NouveauGpu {
+    fn device(&self) -> Option<&'static Device> {
+        self.device
+    }
+
+    fn gpu_identifier(&self) -> GpuIdentifier {
+        self.gpu_identifier
+    }
+
+    fn driver(&self) -> String {
+        self.driver.clone()
+    }
+
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Nvidia
+    }
+
+    fn sysfs_path(&self) -> PathBuf {
+        self.sysfs_path.clone()
+    }
+
+    fn first_hwmon(&self) -> Option<PathBuf> {
+        self.first_hwmon_path.clone()
+    }
+
+    fn name(&self) -> Result<String> {
+        self.drm_name()
+    }
+
+    fn usage(&self) -> Result<f64> {
+        // nouveau has no equivalent to amdgpu's gpu_busy_percent
+        bail!("usage is not exposed by nouveau")
+    }
+
+    fn encode_usage(&self) -> Result<f64> {
+        bail!("encode usage not implemented for nouveau")
+    }
+
+    fn decode_usage(&self) -> Result<f64> {
+        bail!("decode usage not implemented for nouveau")
+    }
+
+    fn combined_media_engine(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn used_vram(&self) -> Result<usize> {
+        // nouveau has no equivalent to amdgpu's mem_info_vram_used
+        bail!("used VRAM is not exposed by nouveau")
+    }
+
+    fn total_vram(&self) -> Result<usize> {
+        bail!("total VRAM is not exposed by nouveau")
+    }
+
+    fn temperature(&self) -> Result<f64> {
+        self.hwmon_temperature()
+    }
+
+    fn power_usage(&self) -> Result<f64> {
+        self.hwmon_power_usage()
+    }
+
+    fn core_frequency(&self) -> Result<f64> {
+        Self::parse_pstate_frequency(&self.pstate()?, &RE_PSTATE_CORE)
+    }
+
+    fn vram_frequency(&self) -> Result<f64> {
+        Self::parse_pstate_frequency(&self.pstate()?, &RE_PSTATE_MEMORY)
+    }
+
+    fn power_cap(&self) -> Result<f64> {
+        self.hwmon_power_cap()
+    }
+
+    fn power_cap_max(&self) -> Result<f64> {
+        self.hwmon_power_cap_max()
+    }
+
+    fn power_state(&self) -> Result<PowerState> {
+        self.drm_power_state()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{NouveauGpu, RE_PSTATE_CORE, RE_PSTATE_MEMORY};
+
+    #[test]
+    fn pstate_frequencies() {
+        let pstate = concat!(
+            "07: core 405 MHz memory 810 MHz\n",
+            "0f: core 405-1110 MHz memory 5010 MHz AC DC *\n",
+            "AC: core 540 MHz memory 5010 MHz\n",
+        );
+
+        assert_eq!(
+            540_000_000.0,
+            NouveauGpu::parse_pstate_frequency(pstate, &RE_PSTATE_CORE).unwrap()
+        );
+        assert_eq!(
+            5_010_000_000.0,
+            NouveauGpu::parse_pstate_frequency(pstate, &RE_PSTATE_MEMORY).unwrap()
+        );
+        assert!(NouveauGpu::parse_pstate_frequency("07: core 405 MHz\n", &RE_PSTATE_CORE).is_err());
+    }
+}
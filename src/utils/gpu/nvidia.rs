@@ -1,8 +1,8 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use log::{debug, warn};
 use nvml_wrapper::{
     Nvml,
-    enum_wrappers::device::{Clock, TemperatureSensor},
+    enum_wrappers::device::{Clock, EccCounter, MemoryError, TemperatureSensor},
     error::NvmlError,
 };
 use process_data::GpuIdentifier;
@@ -30,7 +30,7 @@ static NVML: LazyLock<Result<Nvml, NvmlError>> = LazyLock::new(|| {
 
 use crate::utils::{IS_FLATPAK, pci::Device};
 
-use super::GpuImpl;
+use super::{EccErrors, GpuImpl, GpuVendor, PowerState};
 
 #[derive(Debug, Default, Clone)]
 
@@ -84,6 +84,10 @@ impl GpuImpl for NvidiaGpu {
         self.driver.clone()
     }
 
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Nvidia
+    }
+
     fn sysfs_path(&self) -> PathBuf {
         self.sysfs_path.clone()
     }
@@ -211,4 +215,42 @@ impl GpuImpl for NvidiaGpu {
             .map(|constraints| (f64::from(constraints.max_limit)) / 1000.0)
             .or_else(|_| self.hwmon_power_cap_max())
     }
+
+    fn board_power_usage(&self) -> Result<f64> {
+        // NVML already reports the power draw of the entire board
+        self.power_usage()
+    }
+
+    fn ecc_errors(&self) -> Result<EccErrors> {
+        let dev = Self::nvml_device(&self.pci_slot_string)?;
+
+        let ecc_mode = dev
+            .is_ecc_enabled()
+            .context("unable to get ECC mode through NVML")?;
+        if !ecc_mode.currently_enabled {
+            bail!("ECC is disabled for this GPU");
+        }
+
+        // volatile counters are reset with the driver, just like the ones of other vendors
+        Ok(EccErrors {
+            corrected: dev
+                .total_ecc_errors(MemoryError::Corrected, EccCounter::Volatile)
+                .context("unable to get corrected ECC errors through NVML")?,
+            uncorrected: dev
+                .total_ecc_errors(MemoryError::Uncorrected, EccCounter::Volatile)
+                .context("unable to get uncorrected ECC errors through NVML")?,
+        })
+    }
+
+    fn power_state(&self) -> Result<PowerState> {
+        // NVML wakes up a suspended GPU when queried, so prefer the kernel's view
+        self.drm_power_state().or_else(|_| {
+            Self::nvml_device(&self.pci_slot_string)
+                .and_then(|dev| {
+                    dev.performance_state()
+                        .context("unable to get performance state through NVML")
+                })
+                .map(|_| PowerState::Active)
+        })
+    }
 }
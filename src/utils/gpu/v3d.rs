@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use crate::utils::pci::Device;
 
-use super::GpuImpl;
+use super::{GpuImpl, GpuVendor, PowerState};
 
 #[derive(Debug, Clone, Default)]
 
@@ -48,6 +48,10 @@ impl GpuImpl for V3dGpu {
         self.driver.clone()
     }
 
+    fn vendor(&self) -> GpuVendor {
+        GpuVendor::Other
+    }
+
     fn sysfs_path(&self) -> PathBuf {
         self.sysfs_path.clone()
     }
@@ -107,4 +111,8 @@ impl GpuImpl for V3dGpu {
     fn power_cap_max(&self) -> Result<f64> {
         self.hwmon_power_cap_max()
     }
+
+    fn power_state(&self) -> Result<PowerState> {
+        self.drm_power_state()
+    }
 }
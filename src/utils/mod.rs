@@ -107,6 +107,24 @@ pub fn read_uevent<P: AsRef<Path>>(uevent_path: P) -> Result<HashMap<String, Str
     read_uevent_contents(std::fs::read_to_string(uevent_path)?)
 }
 
+/// Reads a (sysfs) file and strips its trailing newline
+///
+/// Flatpak shares the host's `/sys` with the sandbox, so unlike files below the host's `/usr` or `/etc`, sysfs
+/// paths must not be redirected to `/run/host` when `IS_FLATPAK` is set.
+pub fn read_sysfs_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    trace!("Reading {path:?}…");
+    Ok(std::fs::read_to_string(path)?.replace('\n', ""))
+}
+
+/// Reads a (sysfs) file that is supposed to contain a single integer
+pub fn read_sysfs_isize<P: AsRef<Path>>(path: P) -> Result<isize> {
+    let path = path.as_ref();
+    read_sysfs_string(path)?
+        .parse::<isize>()
+        .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+}
+
 pub trait FiniteOr {
     /// Returns the given `x` value if the variable is NaN or infinite,
     /// and returns itself otherwise.
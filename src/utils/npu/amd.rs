@@ -0,0 +1,101 @@
+use anyhow::Result;
+use process_data::pci_slot::PciSlot;
+
+use std::path::PathBuf;
+
+use crate::utils::pci::Device;
+
+use super::NpuImpl;
+
+/// NPUs of AMD's Ryzen AI processors driven by `amdxdna`. The driver doesn't offer device-wide utilization or memory
+/// statistics through sysfs (yet), so this relies on the generic DRM fallbacks and per-process fdinfo stats have to be
+/// used for everything else.
+#[derive(Debug, Clone, Default)]
+
+pub struct AmdNpu {
+    pub device: Option<&'static Device>,
+    pub pci_slot: PciSlot,
+    pub driver: String,
+    sysfs_path: PathBuf,
+    first_hwmon_path: Option<PathBuf>,
+}
+
+impl AmdNpu {
+    pub fn new(
+        device: Option<&'static Device>,
+        pci_slot: PciSlot,
+        driver: String,
+        sysfs_path: PathBuf,
+        first_hwmon_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            device,
+            pci_slot,
+            driver,
+            sysfs_path,
+            first_hwmon_path,
+        }
+    }
+}
+
+impl NpuImpl for AmdNpu {
+    fn device(&self) -> Option<&'static Device> {
+        self.device
+    }
+
+    fn pci_slot(&self) -> PciSlot {
+        self.pci_slot
+    }
+
+    fn driver(&self) -> String {
+        self.driver.clone()
+    }
+
+    fn sysfs_path(&self) -> PathBuf {
+        self.sysfs_path.clone()
+    }
+
+    fn first_hwmon(&self) -> Option<PathBuf> {
+        self.first_hwmon_path.clone()
+    }
+
+    fn name(&self) -> Result<String> {
+        self.drm_name()
+    }
+
+    fn usage(&self) -> Result<f64> {
+        self.drm_usage().map(|usage| usage as f64 / 100.0)
+    }
+
+    fn used_memory(&self) -> Result<usize> {
+        self.drm_used_memory().map(|usage| usage as usize)
+    }
+
+    fn total_memory(&self) -> Result<usize> {
+        self.drm_total_memory().map(|usage| usage as usize)
+    }
+
+    fn temperature(&self) -> Result<f64> {
+        self.hwmon_temperature()
+    }
+
+    fn power_usage(&self) -> Result<f64> {
+        self.hwmon_power_usage()
+    }
+
+    fn core_frequency(&self) -> Result<f64> {
+        self.hwmon_core_frequency()
+    }
+
+    fn memory_frequency(&self) -> Result<f64> {
+        self.hwmon_memory_frequency()
+    }
+
+    fn power_cap(&self) -> Result<f64> {
+        self.hwmon_power_cap()
+    }
+
+    fn power_cap_max(&self) -> Result<f64> {
+        self.hwmon_power_cap_max()
+    }
+}
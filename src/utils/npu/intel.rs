@@ -82,11 +82,11 @@ impl NpuImpl for IntelNpu {
         Ok((delta_busy_time / delta_timestamp) / 1000.0)
     }
 
-    fn used_vram(&self) -> Result<usize> {
+    fn used_memory(&self) -> Result<usize> {
         self.drm_used_memory().map(|usage| usage as usize)
     }
 
-    fn total_vram(&self) -> Result<usize> {
+    fn total_memory(&self) -> Result<usize> {
         self.drm_total_memory().map(|usage| usage as usize)
     }
 
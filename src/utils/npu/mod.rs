@@ -1,3 +1,4 @@
+mod amd;
 mod intel;
 mod other;
 
@@ -14,16 +15,18 @@ use glob::glob;
 
 use crate::{
     i18n::i18n,
-    utils::{pci::Device, read_uevent},
+    utils::{pci::Device, read_sysfs_isize, read_sysfs_string, read_uevent},
 };
 
-use self::{intel::IntelNpu, other::OtherNpu};
+use self::{amd::AmdNpu, intel::IntelNpu, other::OtherNpu};
 
 use super::{
     link::{Link, LinkData},
     pci::Vendor,
 };
 
+// AMD's NPUs are listed under the vendor ID AMD uses for its CPUs, not the one of their GPUs
+pub const VID_AMD: u16 = 0x1022;
 pub const VID_INTEL: u16 = 0x8086;
 
 #[derive(Debug)]
@@ -55,8 +58,8 @@ impl NpuData {
 
         let usage_fraction = npu.usage().ok();
 
-        let total_memory = npu.total_vram().ok();
-        let used_memory = npu.used_vram().ok();
+        let total_memory = npu.total_memory().ok();
+        let used_memory = npu.used_memory().ok();
 
         let clock_speed = npu.core_frequency().ok();
         let vram_speed = npu.memory_frequency().ok();
@@ -91,6 +94,7 @@ impl NpuData {
 
 #[derive(Debug, Clone)]
 pub enum Npu {
+    Amd(AmdNpu),
     Intel(IntelNpu),
     Other(OtherNpu),
 }
@@ -110,8 +114,8 @@ pub trait NpuImpl {
 
     fn name(&self) -> Result<String>;
     fn usage(&self) -> Result<f64>;
-    fn used_vram(&self) -> Result<usize>;
-    fn total_vram(&self) -> Result<usize>;
+    fn used_memory(&self) -> Result<usize>;
+    fn total_memory(&self) -> Result<usize>;
     fn temperature(&self) -> Result<f64>;
     fn power_usage(&self) -> Result<f64>;
     fn core_frequency(&self) -> Result<f64>;
@@ -120,35 +124,19 @@ pub trait NpuImpl {
     fn power_cap_max(&self) -> Result<f64>;
 
     fn read_sysfs_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.sysfs_path().join(file);
-        trace!("Reading {path:?}…");
-        std::fs::read_to_string(&path)?
-            .replace('\n', "")
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.sysfs_path().join(file))
     }
 
     fn read_device_file<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<String> {
-        let path = self.sysfs_path().join("device").join(file);
-        trace!("Reading {path:?}…");
-        Ok(std::fs::read_to_string(path)?.replace('\n', ""))
+        read_sysfs_string(self.sysfs_path().join("device").join(file))
     }
 
     fn read_device_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.sysfs_path().join("device").join(file);
-        trace!("Reading {path:?}…");
-        self.read_device_file(&path)?
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.sysfs_path().join("device").join(file))
     }
 
     fn read_hwmon_int<P: AsRef<Path> + std::marker::Send>(&self, file: P) -> Result<isize> {
-        let path = self.first_hwmon().context("no hwmon found")?.join(file);
-        trace!("Reading {path:?}…");
-        std::fs::read_to_string(&path)?
-            .replace('\n', "")
-            .parse::<isize>()
-            .with_context(|| format!("error parsing file {}", &path.to_string_lossy()))
+        read_sysfs_isize(self.first_hwmon().context("no hwmon found")?.join(file))
     }
 
     // These are preimplemented ways of getting information through the DRM and hwmon interface.
@@ -270,7 +258,18 @@ impl Npu {
             bail!("this is a simple framebuffer");
         }
 
-        let (npu, npu_category) = if vid == VID_INTEL || driver == "intel_vpu" {
+        let (npu, npu_category) = if vid == VID_AMD || driver == "amdxdna" {
+            (
+                Npu::Amd(AmdNpu::new(
+                    device,
+                    pci_slot,
+                    driver,
+                    path.to_path_buf(),
+                    hwmon_vec.first().cloned(),
+                )),
+                "AMD",
+            )
+        } else if vid == VID_INTEL || driver == "intel_vpu" {
             (
                 Npu::Intel(IntelNpu::new(
                     device,
@@ -307,6 +306,7 @@ impl Npu {
 
     pub fn get_vendor(&self) -> Result<&'static Vendor> {
         Ok(match self {
+            Npu::Amd(npu) => npu.device(),
             Npu::Intel(npu) => npu.device(),
             Npu::Other(npu) => npu.device(),
         }
@@ -316,6 +316,7 @@ impl Npu {
 
     pub fn pci_slot(&self) -> PciSlot {
         match self {
+            Npu::Amd(npu) => npu.pci_slot(),
             Npu::Intel(npu) => npu.pci_slot(),
             Npu::Other(npu) => npu.pci_slot(),
         }
@@ -323,6 +324,7 @@ impl Npu {
 
     pub fn driver(&self) -> String {
         match self {
+            Npu::Amd(npu) => npu.driver(),
             Npu::Intel(npu) => npu.driver(),
             Npu::Other(npu) => npu.driver(),
         }
@@ -330,6 +332,7 @@ impl Npu {
 
     pub fn name(&self) -> Result<String> {
         match self {
+            Npu::Amd(npu) => npu.name(),
             Npu::Intel(npu) => npu.name(),
             Npu::Other(npu) => npu.name(),
         }
@@ -337,27 +340,31 @@ impl Npu {
 
     pub fn usage(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.usage(),
             Npu::Intel(npu) => npu.usage(),
             Npu::Other(npu) => npu.usage(),
         }
     }
 
-    pub fn used_vram(&self) -> Result<usize> {
+    pub fn used_memory(&self) -> Result<usize> {
         match self {
-            Npu::Intel(npu) => npu.used_vram(),
-            Npu::Other(npu) => npu.used_vram(),
+            Npu::Amd(npu) => npu.used_memory(),
+            Npu::Intel(npu) => npu.used_memory(),
+            Npu::Other(npu) => npu.used_memory(),
         }
     }
 
-    pub fn total_vram(&self) -> Result<usize> {
+    pub fn total_memory(&self) -> Result<usize> {
         match self {
-            Npu::Intel(npu) => npu.total_vram(),
-            Npu::Other(npu) => npu.total_vram(),
+            Npu::Amd(npu) => npu.total_memory(),
+            Npu::Intel(npu) => npu.total_memory(),
+            Npu::Other(npu) => npu.total_memory(),
         }
     }
 
     pub fn temperature(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.temperature(),
             Npu::Intel(npu) => npu.temperature(),
             Npu::Other(npu) => npu.temperature(),
         }
@@ -365,6 +372,7 @@ impl Npu {
 
     pub fn power_usage(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.power_usage(),
             Npu::Intel(npu) => npu.power_usage(),
             Npu::Other(npu) => npu.power_usage(),
         }
@@ -372,6 +380,7 @@ impl Npu {
 
     pub fn core_frequency(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.core_frequency(),
             Npu::Intel(npu) => npu.core_frequency(),
             Npu::Other(npu) => npu.core_frequency(),
         }
@@ -379,6 +388,7 @@ impl Npu {
 
     pub fn memory_frequency(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.memory_frequency(),
             Npu::Intel(npu) => npu.memory_frequency(),
             Npu::Other(npu) => npu.memory_frequency(),
         }
@@ -386,6 +396,7 @@ impl Npu {
 
     pub fn power_cap(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.power_cap(),
             Npu::Intel(npu) => npu.power_cap(),
             Npu::Other(npu) => npu.power_cap(),
         }
@@ -393,6 +404,7 @@ impl Npu {
 
     pub fn power_cap_max(&self) -> Result<f64> {
         match self {
+            Npu::Amd(npu) => npu.power_cap_max(),
             Npu::Intel(npu) => npu.power_cap_max(),
             Npu::Other(npu) => npu.power_cap_max(),
         }
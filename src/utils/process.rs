@@ -1,12 +1,17 @@
 use anyhow::{Context, Result, bail};
 use config::LIBEXECDIR;
 use log::{debug, error, info, trace};
-use process_data::{GpuIdentifier, GpuUsageStats, Niceness, ProcessData};
+use nix::sys::signal::Signal;
+use process_data::{
+    GpuEngine, GpuIdentifier, GpuUsageStats, IoPriority, Niceness, ProcessData, SchedulingPolicy,
+    open_files::OpenFile,
+};
+use serde::de::DeserializeOwned;
 use std::{
     collections::BTreeMap,
     ffi::{OsStr, OsString},
     io::{Read, Write},
-    process::{ChildStdin, ChildStdout, Command, Stdio},
+    process::{ChildStdin, ChildStdout, Command, Output, Stdio},
     sync::{LazyLock, Mutex},
     time::Instant,
 };
@@ -69,8 +74,12 @@ pub struct Process {
     pub timestamp_last: u64,
     pub read_bytes_last: Option<u64>,
     pub write_bytes_last: Option<u64>,
+    pub major_faults_last: u64,
+    pub nonvoluntary_ctxt_switches_last: Option<u64>,
     pub gpu_usage_stats_last: BTreeMap<GpuIdentifier, GpuUsageStats>,
     pub display_name: String,
+    /// Estimated power draw in watts, see `AppsContext::refresh()`
+    pub power_usage: Option<f64>,
 }
 
 // TODO: Better name?
@@ -171,8 +180,11 @@ impl Process {
             timestamp_last: 0,
             read_bytes_last,
             write_bytes_last,
+            major_faults_last: 0,
+            nonvoluntary_ctxt_switches_last: None,
             gpu_usage_stats_last: Default::default(),
             display_name,
+            power_usage: None,
         }
     }
 
@@ -181,12 +193,23 @@ impl Process {
         command: S,
         args: I,
     ) -> Result<i32> {
+        Self::maybe_pkexec_output(command, args)?
+            .status
+            .code()
+            .context("no status code?")
+    }
+
+    /// Like `maybe_pkexec_command()`, but returns the whole output of the command
+    fn maybe_pkexec_output<S: AsRef<OsStr>, I: IntoIterator<Item = S>>(
+        command: S,
+        args: I,
+    ) -> Result<Output> {
         let args = args
             .into_iter()
             .map(|s| s.as_ref().to_os_string())
             .collect::<Vec<_>>();
 
-        let status_code = if *IS_FLATPAK {
+        let output = if *IS_FLATPAK {
             debug!(
                 "Executing command: {} --host {} {}",
                 FLATPAK_SPAWN,
@@ -199,9 +222,6 @@ impl Process {
                 .arg(command.as_ref())
                 .args(args.clone())
                 .output()?
-                .status
-                .code()
-                .context("no status code?")?
         } else {
             debug!(
                 "Executing command: {} {}",
@@ -209,56 +229,80 @@ impl Process {
                 args.join(&OsString::from(" ")).to_string_lossy()
             );
 
-            Command::new(command.as_ref())
-                .args(args.clone())
-                .output()?
-                .status
-                .code()
-                .context("no status code?")?
+            Command::new(command.as_ref()).args(args.clone()).output()?
         };
 
+        let status_code = output.status.code().context("no status code?")?;
+
         if status_code == libc::EPERM || status_code == libc::EACCES {
-            let pkexec_status_code = if *IS_FLATPAK {
+            if *IS_FLATPAK {
                 debug!(
                     "Received EPERM, executing command: {} --host pkexec --disable-internal-agent {} {}",
                     FLATPAK_SPAWN,
                     command.as_ref().to_string_lossy(),
                     args.join(&OsString::from(" ")).to_string_lossy()
                 );
-                Command::new(FLATPAK_SPAWN)
+                Ok(Command::new(FLATPAK_SPAWN)
                     .args(["--host", "pkexec", "--disable-internal-agent"])
                     .arg(command)
                     .args(args)
-                    .output()?
-                    .status
-                    .code()
-                    .context("no status code?")?
+                    .output()?)
             } else {
                 debug!(
                     "Received EPERM or EACCES, executing command: pkexec --disable-internal-agent {} {}",
                     command.as_ref().to_string_lossy(),
                     args.join(&OsString::from(" ")).to_string_lossy()
                 );
-                Command::new("pkexec")
+                Ok(Command::new("pkexec")
                     .arg("--disable-internal-agent")
                     .arg(command)
                     .args(args)
-                    .output()?
-                    .status
-                    .code()
-                    .context("no status code?")?
-            };
+                    .output()?)
+            }
+        } else {
+            Ok(output)
+        }
+    }
 
-            Ok(pkexec_status_code)
+    /// Returns the open file descriptors of the process with the given PID, asking for elevated privileges if the
+    /// process belongs to another user
+    pub fn open_files(pid: libc::pid_t) -> Result<Vec<OpenFile>> {
+        Self::query_companion("--open-files", pid).context("unable to get open files")
+    }
+
+    /// Returns the environment variables of the process with the given PID, asking for elevated privileges if the
+    /// process belongs to another user
+    pub fn environ(pid: libc::pid_t) -> Result<Vec<(String, String)>> {
+        Self::query_companion("--environ", pid).context("unable to get environment variables")
+    }
+
+    /// Runs `resources-processes` once (possibly privileged) to get a single piece of information about a process
+    fn query_companion<T: DeserializeOwned>(argument: &str, pid: libc::pid_t) -> Result<T> {
+        let processes_path = if *IS_FLATPAK {
+            format!(
+                "{}/libexec/resources/resources-processes",
+                FLATPAK_APP_PATH.as_str()
+            )
         } else {
-            Ok(status_code)
+            format!("{LIBEXECDIR}/resources-processes")
+        };
+
+        let output = Self::maybe_pkexec_output(processes_path, [argument, &pid.to_string()])?;
+
+        if !output.status.success() {
+            bail!("non-zero return code: {:?}", output.status.code());
         }
+
+        rmp_serde::from_slice(&output.stdout).context("unable to decode companion process output")
     }
 
     pub fn adjust<I: IntoIterator<Item = bool>>(
         &self,
         niceness: Niceness,
         affinity: I,
+        oom_score_adj: i16,
+        scheduling_policy: SchedulingPolicy,
+        realtime_priority: u32,
     ) -> Result<()> {
         let adjust_path = if *IS_FLATPAK {
             format!(
@@ -274,17 +318,24 @@ impl Process {
             .map(|b| if b { '1' } else { '0' })
             .collect::<String>();
 
-        debug!("Trying to adjust with niceness = {niceness} and affinity = {affinity_string}");
-
-        let result = Self::maybe_pkexec_command(
-            adjust_path,
-            [
-                self.data.pid.to_string(),
-                niceness.to_string(),
-                affinity_string,
-            ],
+        debug!(
+            "Trying to adjust with niceness = {niceness}, affinity = {affinity_string}, OOM score adjustment = {oom_score_adj} and scheduling policy = {scheduling_policy:?} (realtime priority: {realtime_priority})"
         );
 
+        let mut arguments = vec![
+            self.data.pid.to_string(),
+            niceness.to_string(),
+            affinity_string,
+            oom_score_adj.to_string(),
+        ];
+
+        if let Some(policy) = scheduling_policy.as_raw() {
+            arguments.push(policy.to_string());
+            arguments.push(realtime_priority.to_string());
+        }
+
+        let result = Self::maybe_pkexec_command(adjust_path, arguments);
+
         if let Ok(return_code) = result {
             if return_code == 0 {
                 info!("Successfully adjusted {}", self.data.pid);
@@ -299,20 +350,85 @@ impl Process {
         }
     }
 
-    pub fn execute_process_action(&self, action: ProcessAction) -> Result<()> {
-        let action_string = action.to_string();
+    pub fn set_io_priority(&self, io_priority: IoPriority) -> Result<()> {
+        let adjust_path = if *IS_FLATPAK {
+            format!(
+                "{}/libexec/resources/resources-adjust",
+                FLATPAK_APP_PATH.as_str()
+            )
+        } else {
+            format!("{LIBEXECDIR}/resources-adjust")
+        };
+
+        debug!(
+            "Trying to set the I/O priority of {} to {io_priority:?}",
+            self.data.pid
+        );
+
+        let return_code = Self::maybe_pkexec_command(
+            adjust_path,
+            [
+                "--io-priority".to_string(),
+                self.data.pid.to_string(),
+                io_priority.as_raw().to_string(),
+            ],
+        )?;
 
-        let kill_path = if *IS_FLATPAK {
+        if return_code == 0 {
+            info!("Successfully set the I/O priority of {}", self.data.pid);
+            Ok(())
+        } else {
+            bail!("non-zero return code: {return_code}")
+        }
+    }
+
+    fn kill_path() -> String {
+        if *IS_FLATPAK {
             format!(
                 "{}/libexec/resources/resources-kill",
                 FLATPAK_APP_PATH.as_str()
             )
         } else {
             format!("{LIBEXECDIR}/resources-kill")
-        };
+        }
+    }
+
+    /// Sends an arbitrary signal to the process, asking for elevated privileges if necessary
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the signal couldn't be sent, e.g. because the process has ended in the meantime or the
+    /// required privileges were not granted
+    pub fn send_signal(&self, signal: Signal) -> Result<()> {
+        let return_code = Self::maybe_pkexec_command(
+            Self::kill_path(),
+            [self.data.pid.to_string(), signal.as_str().to_string()],
+        )?;
+
+        // 3 is ESRCH, i.e. the process has already ended
+        if return_code == 0 || return_code == 3 {
+            info!("Successfully sent {signal} to {}", self.data.pid);
+            Ok(())
+        } else {
+            error!(
+                "Couldn't send {signal} to {}, return code: {return_code}",
+                self.data.pid
+            );
+            bail!("non-zero return code: {return_code}")
+        }
+    }
+
+    pub fn execute_process_action(&self, action: ProcessAction) -> Result<()> {
+        let action_string = action.to_string();
+
+        // the process might have been halted by freezing its cgroup (see `App::execute_process_action()`), which
+        // SIGCONT alone doesn't undo
+        if action == ProcessAction::CONT {
+            self.thaw_cgroup();
+        }
 
         let result = Self::maybe_pkexec_command(
-            kill_path,
+            Self::kill_path(),
             [self.data.pid.to_string(), action_string.clone()],
         );
 
@@ -339,6 +455,45 @@ impl Process {
         }
     }
 
+    /// Thaws the process' cgroup (and its ancestors) if it's frozen, this also thaws the other processes in these
+    /// cgroups
+    fn thaw_cgroup(&self) {
+        let Some(cgroup_path) = self.data.cgroup_path.as_deref() else {
+            return;
+        };
+
+        if !ProcessData::is_cgroup_frozen(cgroup_path) {
+            return;
+        }
+
+        match ProcessData::thaw_cgroup(cgroup_path) {
+            Ok(()) => info!(
+                "Successfully thawed cgroup {cgroup_path} of {}",
+                self.data.pid
+            ),
+            Err(err) => error!(
+                "Unable to thaw cgroup {cgroup_path} of {}: {err}",
+                self.data.pid
+            ),
+        }
+    }
+
+    /// Returns the CPU time in ticks the process has used since the last refresh, `None` if it hasn't been refreshed
+    /// before
+    #[must_use]
+    pub fn cpu_time_delta(&self) -> Option<u64> {
+        if self.cpu_time_last == 0 {
+            None
+        } else {
+            Some(
+                self.data
+                    .user_cpu_time
+                    .saturating_add(self.data.system_cpu_time)
+                    .saturating_sub(self.cpu_time_last),
+            )
+        }
+    }
+
     #[must_use]
     pub fn cpu_time_ratio(&self) -> f32 {
         if self.cpu_time_last == 0 {
@@ -394,6 +549,35 @@ impl Process {
         }
     }
 
+    /// Number of major page faults per second since the last refresh
+    #[must_use]
+    pub fn major_fault_rate(&self) -> f64 {
+        self.rate_per_second(self.data.major_faults, self.major_faults_last)
+    }
+
+    /// Number of times per second the process was forced off the CPU since the last refresh
+    #[must_use]
+    pub fn involuntary_switch_rate(&self) -> Option<f64> {
+        if let (Some(switches), Some(switches_last)) = (
+            self.data.nonvoluntary_ctxt_switches,
+            self.nonvoluntary_ctxt_switches_last,
+        ) {
+            Some(self.rate_per_second(switches, switches_last))
+        } else {
+            None
+        }
+    }
+
+    fn rate_per_second(&self, current: u64, last: u64) -> f64 {
+        if self.timestamp_last == 0 {
+            0.0
+        } else {
+            let delta = current.saturating_sub(last) as f64;
+            let time_delta = self.data.timestamp.saturating_sub(self.timestamp_last) as f64;
+            ((delta / time_delta) * 1000.0).finite_or_default()
+        }
+    }
+
     #[must_use]
     pub fn gpu_usage(&self) -> f32 {
         let mut returned_gpu_usage = 0.0;
@@ -401,6 +585,10 @@ impl Process {
             if let Some(old_usage) = self.gpu_usage_stats_last.get(gpu) {
                 let this_gpu_usage = if usage.nvidia {
                     usage.gfx as f32 / 100.0
+                } else if let Some(fraction) =
+                    usage.cycles_fraction_since(old_usage, GpuEngine::Graphics)
+                {
+                    fraction as f32
                 } else if old_usage.gfx == 0 {
                     0.0
                 } else {
@@ -426,6 +614,10 @@ impl Process {
             if let Some(old_usage) = self.gpu_usage_stats_last.get(gpu) {
                 let this_gpu_usage = if usage.nvidia {
                     usage.enc as f32 / 100.0
+                } else if let Some(fraction) =
+                    usage.cycles_fraction_since(old_usage, GpuEngine::Encode)
+                {
+                    fraction as f32
                 } else if old_usage.enc == 0 {
                     0.0
                 } else {
@@ -451,6 +643,10 @@ impl Process {
             if let Some(old_usage) = self.gpu_usage_stats_last.get(gpu) {
                 let this_gpu_usage = if usage.nvidia {
                     usage.dec as f32 / 100.0
+                } else if let Some(fraction) =
+                    usage.cycles_fraction_since(old_usage, GpuEngine::Decode)
+                {
+                    fraction as f32
                 } else if old_usage.dec == 0 {
                     0.0
                 } else {
@@ -375,6 +375,11 @@ impl Settings {
         processes_show_system_cpu_time,
         processes_show_priority,
         processes_show_swap,
+        processes_show_major_faults,
+        processes_show_involuntary_switches,
+        processes_show_scheduling_policy,
+        processes_show_realtime_priority,
+        processes_show_power_usage,
         show_logical_cpus,
         show_graph_grids,
         normalize_cpu_usage,